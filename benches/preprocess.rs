@@ -0,0 +1,77 @@
+//! Camera buffer -> 224x224 model input, old path against `TensorInput`.
+//!
+//! `cargo bench --bench preprocess`
+
+use air_link::core::frame::{Frame, PixelFormat};
+use air_link::core::preprocess::TensorInput;
+use criterion::{criterion_group, criterion_main, Criterion};
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{ExtendedColorType, RgbImage};
+use std::hint::black_box;
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 480;
+const SIZE: u32 = 224;
+
+/// A smooth gradient with some texture, so the JPEG is not trivially small.
+fn test_image() -> RgbImage {
+    RgbImage::from_fn(WIDTH, HEIGHT, |x, y| {
+        image::Rgb([(x / 3) as u8, (y / 2) as u8, ((x * y) % 251) as u8])
+    })
+}
+
+fn yuyv_frame() -> Frame {
+    let data = (0..WIDTH * HEIGHT / 2)
+        .flat_map(|i| [(i % 220) as u8 + 16, 110, (i % 200) as u8 + 20, 150])
+        .collect();
+    Frame::new(data, WIDTH, HEIGHT, PixelFormat::Yuyv)
+}
+
+fn nv12_frame() -> Frame {
+    let mut data: Vec<u8> = (0..WIDTH * HEIGHT).map(|i| (i % 220) as u8 + 16).collect();
+    data.extend((0..WIDTH * HEIGHT / 4).flat_map(|_| [110, 150]));
+    Frame::new(data, WIDTH, HEIGHT, PixelFormat::Nv12)
+}
+
+fn mjpeg_frame() -> Frame {
+    let mut data = Vec::new();
+    JpegEncoder::new_with_quality(&mut data, 85)
+        .encode(&test_image(), WIDTH, HEIGHT, ExtendedColorType::Rgb8)
+        .unwrap();
+    Frame::new(data, WIDTH, HEIGHT, PixelFormat::Mjpeg)
+}
+
+/// What `HandDetector::detect` used to do: full RGB decode, Triangle resize,
+/// then a `get_pixel` loop into a fresh `Vec`.
+fn legacy(frame: &Frame) -> Vec<f32> {
+    let img = frame.to_image().unwrap();
+    let resized = img.resize_exact(SIZE, SIZE, FilterType::Triangle);
+    let rgb_img = resized.to_rgb8();
+
+    let mut pixels = Vec::with_capacity(3 * (SIZE * SIZE) as usize);
+    for c in 0..3 {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                pixels.push(rgb_img.get_pixel(x, y)[c] as f32 / 255.0);
+            }
+        }
+    }
+    pixels
+}
+
+fn preprocess(c: &mut Criterion) {
+    let mut group = c.benchmark_group("640x480_to_224");
+    for (name, frame) in [("yuyv", yuyv_frame()), ("nv12", nv12_frame()), ("mjpeg", mjpeg_frame())] {
+        group.bench_function(format!("{}/legacy", name), |b| b.iter(|| legacy(black_box(&frame))));
+
+        let mut input = TensorInput::new(SIZE);
+        group.bench_function(format!("{}/tensor_input", name), |b| {
+            b.iter(|| input.fill(black_box(&frame)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, preprocess);
+criterion_main!(benches);
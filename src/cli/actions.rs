@@ -1,40 +1,92 @@
-use crate::cli::{Cli, Commands};
+use crate::cli::{
+    CameraArgs, CaptureFormat, Cli, ClutchArgs, ClutchPose, Commands, ControlArgs, CurveKind,
+    DwellArgs, DwellMode, FaceGesture, FilterProfile, MappingArgs, OutputArgs, PenPressure,
+    PowerArgs, PredictArgs, RecordFormat, RemoteTransport, ScreenArgs, StreamArgs, StreamFormat,
+    TouchTrigger, TrackingArgs, TrackingMode,
+};
 use crate::Result;
-use crate::core::app::{AppContext, ScreenConfig};
+use crate::core::app::AppContext;
+use crate::core::camera::{self, CameraConfig, DeviceInfo};
+use crate::core::clutch::ClutchConfig;
+use crate::core::control::{self, ControlServer};
+use crate::core::dataset::{DatasetSession, SessionInfo};
+use crate::core::display::{self, Monitor};
+use crate::core::frame::Frame;
+use crate::core::layout::DesktopLayout;
+use crate::core::dump::{DumpFormat, Record, RecordWriter};
+use crate::core::dwell::{DwellAction, DwellClicker, DwellConfig};
+use crate::core::gesture::HandPose;
+use crate::core::idle::{IdleConfig, IdleScheduler};
+use crate::core::eval::{self, EvalStats};
+use crate::core::face::{FaceClick, FaceDetector};
+use crate::core::gamepad::GamepadConfig;
+use crate::core::logic::{PointFilter, SmoothFilter, TremorConfig, TremorFilter};
+use crate::core::mapping::{CoordinateMapper, MapperConfig, ScreenConfig, TransferCurve};
+use crate::core::osc::{AddressTemplate, OscSink};
+use crate::core::pen::PressureSource;
+use crate::core::input::{GamepadManager, PenManager, TouchManager};
+use crate::core::MouseManager;
+use crate::core::predict::{Predictor, PredictorConfig};
+use crate::core::remote::{self, RemoteSender, Transport};
+use crate::core::source::FrameSource;
+use crate::core::stream::{StreamServer, WireFormat};
+use crate::core::touch::TouchTest;
+use crate::core::tracker::Tracker;
+use crate::core::vision::HandDetector;
 use crate::gui::app::AirLinkApp;
-use tracing::info;
-use nokhwa::query;
-use nokhwa::utils::ApiBackend;
+use crate::gui::dataset::DatasetApp;
+use tracing::{info, warn};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use nokhwa::utils::FrameFormat;
+use serde_json::{json, Value};
 
 pub fn handle_command(cli: Cli) -> Result<()> {
     match cli.command {
         Commands::Run { 
-            camera_id, sensitivity, mode,
-            screen_width, screen_height, screen_x_offset, screen_y_offset
+            camera, tracking, filter, screen, mapping, clutch, dwell, power, predict, control, stream, output,
         } => {
-            info!("Starting Air Mouse in {:?} mode...", mode);
-            
-            let screen_config = ScreenConfig {
-                width: screen_width,
-                height: screen_height,
-                x_offset: screen_x_offset,
-                y_offset: screen_y_offset,
-            };
+            info!("Starting Air Mouse in {:?} mode...", tracking.mode);
+
+            let layout = resolve_layout(&screen)?;
 
-            let mut app = AppContext::new(camera_id, sensitivity, screen_config)?;
+            let tracker = build_tracker(&tracking, &output)?;
+            let screen = layout.current_screen();
+            let mapper = CoordinateMapper::new(mapper_config(&mapping)?, screen, tracker.active_zone());
+
+            let mut app = AppContext::new(
+                camera_config(&camera)?, tracker, mapper, layout,
+                clutch_config(&clutch), dwell_clicker(&dwell), point_filter(filter),
+            )?.with_idle(idle_scheduler(&power)).with_predictor(predictor(&predict))
+                .with_control(control_server(&control)).with_stream(landmark_stream(&stream)?)
+                .with_mouse(pointer_device(&output)?).with_gamepad(gamepad(&output)?)
+                .with_touch(touch_device(&output, screen)?).with_pen(pen_device(&output, screen)?)
+                .with_osc(osc_sink(&output)?);
             app.run_loop()?;
         }
-        Commands::Gui { camera_id } => {
+        Commands::Gui {
+            camera, tracking, filter, screen, mapping, clutch, dwell, power, predict, control, stream, output,
+        } => {
             info!("Launching GUI Mode...");
-            
-            let screen_config = ScreenConfig {
-                width: 1920,
-                height: 1080,
-                x_offset: 0,
-                y_offset: 0,
-            };
 
-            let app_ctx = AppContext::new(camera_id, 1.5, screen_config)?;
+            let layout = resolve_layout(&screen)?;
+
+            let tracker = build_tracker(&tracking, &output)?;
+            let screen = layout.current_screen();
+            let mapper = CoordinateMapper::new(mapper_config(&mapping)?, screen, tracker.active_zone());
+
+            let app_ctx = AppContext::new(
+                camera_config(&camera)?, tracker, mapper, layout,
+                clutch_config(&clutch), dwell_clicker(&dwell), point_filter(filter),
+            )?.with_idle(idle_scheduler(&power)).with_predictor(predictor(&predict))
+                .with_control(control_server(&control)).with_stream(landmark_stream(&stream)?)
+                .with_mouse(pointer_device(&output)?).with_gamepad(gamepad(&output)?)
+                .with_touch(touch_device(&output, screen)?).with_pen(pen_device(&output, screen)?)
+                .with_osc(osc_sink(&output)?);
 
             let native_options = eframe::NativeOptions::default();
             eframe::run_native(
@@ -43,23 +95,550 @@ pub fn handle_command(cli: Cli) -> Result<()> {
                 Box::new(|cc| Ok(Box::new(AirLinkApp::new(cc).with_context(app_ctx)))),
             ).map_err(|e| crate::AirLinkError::CoreError(format!("GUI failed: {}", e)))?;
         }
-        Commands::ListCameras => {
+        Commands::ListCameras { json } => {
             info!("Searching for available cameras...");
-            
-            let devices = query(ApiBackend::Auto)
-                .map_err(|e| crate::AirLinkError::CoreError(format!("Query failed: {}", e)))?;
 
-            if devices.is_empty() {
+            let devices = camera::list_devices()?;
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&cameras_json(&devices))?);
+            } else if devices.is_empty() {
                 println!("No cameras detected. Check your connection, bro!");
             } else {
                 println!("\n📸 DETECTED CAMERAS:");
                 println!("-------------------");
-                for dev in devices {
-                    println!("=> Name: {} | Index: {:?}", dev.human_name(), dev.index());
+                for dev in &devices {
+                    println!("=> Name: {} | Index: {} | Bus: {}", dev.name, dev.index, dev.bus);
+                    if dev.modes.is_empty() {
+                        println!("     (modes unavailable, camera busy?)");
+                    }
+                    // One line per format and resolution, frame rates collected
+                    for group in dev.modes.chunk_by(|a, b| a.format() == b.format() && a.resolution() == b.resolution()) {
+                        let rates: Vec<String> = group.iter().map(|m| m.frame_rate().to_string()).collect();
+                        println!("     {:<6} {:>4}x{:<4} @ {} fps",
+                            group[0].format().to_string(), group[0].width(), group[0].height(), rates.join(", "));
+                    }
+                }
+                println!("-------------------");
+                println!("Use `--camera <name>` to pick one by name.\n");
+            }
+        }
+        Commands::ListScreens { screen_geometry } => {
+            let monitors = discover_monitors(screen_geometry.as_deref())?;
+
+            if monitors.is_empty() {
+                println!("No connected screens found.");
+            } else {
+                println!("\n🖥️  DETECTED SCREENS:");
+                println!("-------------------");
+                for m in &monitors {
+                    let (lw, lh) = m.logical_size();
+                    println!(
+                        "=> Name: {} | Mode: {}x{} | Scale: {} | Layout: {}x{}@{},{}{}",
+                        m.name, m.width, m.height, m.scale, lw, lh, m.x, m.y,
+                        if m.focused { " (focused)" } else { "" },
+                    );
+                }
+                println!("-------------------");
+                println!("Use `--screen <name>` to pick one.\n");
+            }
+        }
+        Commands::Dump { camera, input, hand_model, output_format, output, max_frames } => {
+            let mut source = match &input {
+                Some(path) => FrameSource::open(path)?,
+                None => FrameSource::camera(camera_config(&camera)?)?,
+            };
+            let mut detector = HandDetector::new(&hand_model)?;
+            let out: Box<dyn Write> = match output.as_deref() {
+                None | Some("-") => Box::new(std::io::stdout()),
+                Some(path) => Box::new(File::create(path)?),
+            };
+            let format = match output_format {
+                RecordFormat::Jsonl => DumpFormat::Jsonl,
+                RecordFormat::Csv => DumpFormat::Csv,
+            };
+            let mut writer = RecordWriter::new(out, format)?;
+
+            let (mut frames, mut hands) = (0u64, 0u64);
+            while max_frames.is_none_or(|max| frames < max) {
+                let Some(next) = source.next_frame()? else { break };
+                let detection = detector.infer(&next.frame)?;
+                writer.write(&Record {
+                    frame: next.frame.sequence,
+                    time: next.time,
+                    source: next.name.as_deref(),
+                    presence: detection.presence,
+                    hand: detection.hand.as_ref(),
+                })?;
+                frames += 1;
+                hands += detection.hand.is_some() as u64;
+            }
+            info!("📝 Dumped {} frames, a hand in {}", frames, hands);
+        }
+        Commands::CaptureDataset {
+            camera, hand_model, output, interval_ms, session, notes, headless, max_frames,
+        } => {
+            let dir = Path::new(&output);
+            let name = session.unwrap_or_else(|| {
+                dir.file_name().map_or_else(|| output.clone(), |n| n.to_string_lossy().into_owned())
+            });
+            let info = SessionInfo { name, notes, ..Default::default() };
+            let interval = interval_ms.map(Duration::from_millis);
+            let mut session = DatasetSession::new(camera_config(&camera)?, &hand_model, dir, info, interval)?;
+
+            if headless {
+                // SIGINT skips destructors; stop the loop instead so the labels get written
+                let stop = ctrl_c_flag();
+                while !stop.load(Ordering::Relaxed)
+                    && max_frames.is_none_or(|max| (session.writer.len() as u64) < max)
+                {
+                    match session.step(false)? {
+                        Some(preview) => {
+                            if let Some(id) = preview.saved {
+                                info!("📸 Saved frame {}", id);
+                            }
+                        }
+                        // Camera reconnecting
+                        None => std::thread::sleep(Duration::from_millis(100)),
+                    }
                 }
-                println!("-------------------\n");
+                session.writer.flush()?;
+                info!("🗂️  {} frames in {}", session.writer.len(), dir.display());
+            } else {
+                let native_options = eframe::NativeOptions::default();
+                eframe::run_native(
+                    "Air-Link Dataset Capture",
+                    native_options,
+                    Box::new(|cc| Ok(Box::new(DatasetApp::new(cc, session)))),
+                ).map_err(|e| crate::AirLinkError::CoreError(format!("GUI failed: {}", e)))?;
+            }
+        }
+        Commands::Eval { labels, images, hand_model, pck, report, baseline } => {
+            let labels = eval::load_labels(Path::new(&labels), images.as_deref().map(Path::new))?;
+            info!("🎯 Evaluating {} on {} labelled images", hand_model, labels.len());
+            let mut detector = HandDetector::new(&hand_model)?;
+            let mut stats = EvalStats::new(&pck);
+
+            let mut warmed_up = false;
+            for label in &labels {
+                let frame = match image::open(&label.path) {
+                    Ok(img) => Frame::from_image(&img),
+                    Err(e) => {
+                        warn!("Skipping {}: {}", label.path.display(), e);
+                        continue;
+                    }
+                };
+                // The first run sets up the session; keep it out of the latency numbers
+                if !warmed_up {
+                    detector.infer(&frame)?;
+                    warmed_up = true;
+                }
+                let start = Instant::now();
+                let detection = detector.infer(&frame)?;
+                let latency = start.elapsed();
+                let predicted = detection.hand.as_ref().map(|h| h.landmarks.as_slice());
+                stats.add(label.joints.as_deref(), predicted, (frame.width, frame.height), latency);
+            }
+
+            let mut result = stats.report();
+            result["model"] = json!(hand_model);
+            print_eval(&result);
+            if let Some(path) = &report {
+                std::fs::write(path, serde_json::to_string_pretty(&result)?)?;
+                info!("📝 Report written to {}", path);
             }
+            if let Some(path) = &baseline {
+                let previous: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+                let found = eval::regressions(&result, &previous);
+                for regression in &found {
+                    println!("⚠️  REGRESSION: {}", regression);
+                }
+                if !found.is_empty() {
+                    return Err(crate::AirLinkError::CoreError(format!("{} regressions against {}", found.len(), path)));
+                }
+                println!("✅ No regressions against {}", path);
+            }
+        }
+        Commands::ServeInput { listen, key, transport } => {
+            let mut mouse = MouseManager::new()?;
+            remote::serve(&listen, &key, transport_kind(transport), &mut mouse)?;
+        }
+        Commands::Ctl { socket, method, params } => {
+            let path = socket.map_or_else(control::default_socket_path, PathBuf::from);
+            let result = control::call(&path, &method.replace('-', "_"), ctl_params(&params)?)?;
+            println!("{}", serde_json::to_string_pretty(&result)?);
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Human-readable summary of an `eval` report.
+fn print_eval(report: &Value) {
+    let number = |v: &Value, digits: usize| v.as_f64().map_or("n/a".to_string(), |v| format!("{:.*}", digits, v));
+    println!("\n🎯 EVALUATION: {} images, {} with a labelled and detected hand", report["images"], report["matched"]);
+    println!("-------------------");
+    if let Some(pck) = report["pck"].as_object() {
+        for (k, v) in pck {
+            println!("PCK@{:<5} {}", k, number(v, 3));
+        }
+    }
+    println!("Mean joint error: {} px", number(&report["mean_error_px"], 2));
+    let presence = &report["presence"];
+    println!("Presence: precision {} | recall {}", number(&presence["precision"], 3), number(&presence["recall"], 3));
+    let latency = &report["latency_ms"];
+    println!(
+        "Latency: mean {} ms | p50 {} ms | p95 {} ms",
+        number(&latency["mean"], 2), number(&latency["p50"], 2), number(&latency["p95"], 2),
+    );
+    println!("-------------------\n");
+}
+
+/// `key=value` pairs into a params object; `gain=2` is a number, `profile=tremor` a string.
+fn ctl_params(pairs: &[String]) -> Result<Value> {
+    let mut params = serde_json::Map::new();
+    for pair in pairs {
+        let (key, value) = pair.split_once('=').ok_or_else(|| {
+            crate::AirLinkError::CliError(format!("Invalid parameter `{}`, expected key=value", pair))
+        })?;
+        let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+        params.insert(key.replace('-', "_"), value);
+    }
+    Ok(Value::Object(params))
+}
+
+fn camera_config(args: &CameraArgs) -> Result<CameraConfig> {
+    let resolution = match &args.resolution {
+        Some(spec) => {
+            let parsed = spec.split_once(['x', 'X'])
+                .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)));
+            Some(parsed.ok_or_else(|| {
+                crate::AirLinkError::CliError(format!("Invalid --resolution `{}`, expected e.g. 1280x720", spec))
+            })?)
+        }
+        None => None,
+    };
+
+    let format = args.format.map(|f| match f {
+        CaptureFormat::Mjpeg => FrameFormat::MJPEG,
+        CaptureFormat::Yuyv => FrameFormat::YUYV,
+        CaptureFormat::Nv12 => FrameFormat::NV12,
+        CaptureFormat::Gray => FrameFormat::GRAY,
+    });
+
+    Ok(CameraConfig {
+        index: args.camera_id,
+        name: args.camera.clone(),
+        resolution,
+        fps: args.fps,
+        format,
+        fallback: args.camera_fallback.clone(),
+    })
+}
+
+fn cameras_json(devices: &[DeviceInfo]) -> Value {
+    devices.iter().map(|dev| json!({
+        "index": dev.index,
+        "name": dev.name,
+        "description": dev.description,
+        "bus": dev.bus,
+        "modes": dev.modes.iter().map(|m| json!({
+            "format": m.format().to_string(),
+            "width": m.width(),
+            "height": m.height(),
+            "fps": m.frame_rate(),
+        })).collect::<Vec<_>>(),
+    })).collect()
+}
+
+fn build_tracker(args: &TrackingArgs, output: &OutputArgs) -> Result<Box<dyn Tracker>> {
+    Ok(match args.mode {
+        TrackingMode::Color => {
+            return Err(crate::AirLinkError::CliError(
+                "--mode color is not implemented yet; use --mode ai or --mode face".into()
+            ));
+        }
+        TrackingMode::Ai => {
+            let detector = HandDetector::new(&args.hand_model)?;
+            // The touchscreen takes fingers from both hands
+            Box::new(if output.touch { detector.with_two_hands() } else { detector })
+        }
+        TrackingMode::Face => {
+            let click = match args.face_click {
+                FaceGesture::Blink => FaceClick::Blink,
+                FaceGesture::MouthOpen => FaceClick::MouthOpen,
+            };
+            Box::new(FaceDetector::new(&args.face_model, click)?)
+        }
+    })
+}
+
+fn discover_monitors(geometry: Option<&str>) -> Result<Vec<Monitor>> {
+    match geometry {
+        Some(source) => display::load_geometry(source),
+        None => display::discover_drm(),
+    }
+}
+
+/// Discovered monitors (or 1920x1080 at the origin), with manual flags on top.
+fn resolve_layout(args: &ScreenArgs) -> Result<DesktopLayout> {
+    let manual = args.screen_width.is_some() || args.screen_height.is_some()
+        || args.screen_x_offset.is_some() || args.screen_y_offset.is_some();
+    // Failures only matter when the user explicitly asked for discovery
+    let explicit = args.screen.is_some() || args.screen_geometry.is_some();
+
+    let discovered = if manual && !explicit {
+        Vec::new()
+    } else {
+        match discover_monitors(args.screen_geometry.as_deref()) {
+            Ok(monitors) => monitors,
+            Err(e) if explicit => return Err(e),
+            Err(e) => {
+                warn!("Screen discovery failed ({}), assuming 1920x1080", e);
+                Vec::new()
+            }
+        }
+    };
+
+    let mut monitors = discovered;
+    if monitors.is_empty() || manual {
+        // Manual flags describe the single target, on top of whatever was picked
+        let base = match display::select(&monitors, args.screen.as_deref()) {
+            Ok(m) => m.clone(),
+            Err(e) if args.screen.is_some() => return Err(e),
+            Err(_) => Monitor {
+                name: "default".into(),
+                width: 1920, height: 1080, x: 0, y: 0,
+                scale: 1.0, focused: true,
+            },
+        };
+        let scale = if manual { 1.0 } else { base.scale };
+        let (width, height) = if manual { base.logical_size() } else { (base.width, base.height) };
+        monitors = vec![Monitor {
+            width: args.screen_width.unwrap_or(width),
+            height: args.screen_height.unwrap_or(height),
+            x: args.screen_x_offset.unwrap_or(base.x),
+            y: args.screen_y_offset.unwrap_or(base.y),
+            scale,
+            focused: true,
+            ..base
+        }];
+    } else {
+        // Validate the requested name early
+        display::select(&monitors, args.screen.as_deref())?;
+    }
+
+    let mut layout = DesktopLayout::new(monitors, args.screen.as_deref(), args.span_desktop);
+    layout.switch_on_swipe = args.swipe_switch;
+    if let Some(path) = &args.screen_calibration {
+        layout.load_calibration(path)?;
+    }
+
+    let screen = layout.current_screen();
+    info!("Targeting {} ({}x{}@{},{}) of {} screen(s)",
+        layout.current_name(), screen.width, screen.height, screen.x_offset, screen.y_offset,
+        layout.monitors().len());
+    Ok(layout)
+}
+
+fn mapper_config(args: &MappingArgs) -> Result<MapperConfig> {
+    let curve = match args.curve {
+        CurveKind::Linear => TransferCurve::Linear,
+        CurveKind::Power => TransferCurve::Power(args.curve_param),
+        CurveKind::Sigmoid => TransferCurve::Sigmoid(args.curve_param),
+        CurveKind::Table => {
+            let spec = args.curve_table.as_deref()
+                .ok_or_else(|| crate::AirLinkError::CliError("--curve table needs --curve-table".into()))?;
+            TransferCurve::parse_table(spec)
+                .map_err(|e| crate::AirLinkError::CliError(format!("Invalid --curve-table: {}", e)))?
+        }
+    };
+
+    Ok(MapperConfig {
+        curve,
+        gain: (
+            args.gain_x.unwrap_or(args.sensitivity),
+            args.gain_y.unwrap_or(args.sensitivity),
+        ),
+        dead_zone: args.dead_zone,
+        mirror: true,
+    })
+}
+
+fn point_filter(profile: FilterProfile) -> Box<dyn PointFilter> {
+    match profile {
+        FilterProfile::Standard => Box::new(SmoothFilter::new(SmoothFilter::DEFAULT_ALPHA)),
+        FilterProfile::Tremor => Box::new(TremorFilter::new(TremorConfig::default())),
+    }
+}
+
+fn clutch_config(args: &ClutchArgs) -> ClutchConfig {
+    let pose = match args.clutch_pose {
+        ClutchPose::None => None,
+        ClutchPose::Fist => Some(HandPose::Fist),
+        ClutchPose::PalmAway => Some(HandPose::PalmAway),
+    };
+
+    ClutchConfig {
+        pose,
+        pose_hold: Duration::from_millis(args.clutch_hold_ms),
+        zone_dwell: (args.clutch_zone_dwell_ms > 0)
+            .then(|| Duration::from_millis(args.clutch_zone_dwell_ms)),
+    }
+}
+
+fn dwell_clicker(args: &DwellArgs) -> Option<DwellClicker> {
+    if !args.dwell_click {
+        return None;
+    }
+
+    let action = match args.dwell_action {
+        DwellMode::Left => DwellAction::Left,
+        DwellMode::Right => DwellAction::Right,
+        DwellMode::Double => DwellAction::Double,
+        DwellMode::Drag => DwellAction::Drag,
+    };
+
+    let config = DwellConfig {
+        radius: args.dwell_radius,
+        dwell: Duration::from_millis(args.dwell_ms),
+        refractory: Duration::from_millis(args.dwell_refractory_ms),
+    };
+
+    Some(DwellClicker::new(config, action))
+}
+
+fn idle_scheduler(args: &PowerArgs) -> Option<IdleScheduler> {
+    if args.no_idle {
+        return None;
+    }
+
+    Some(IdleScheduler::new(IdleConfig {
+        after: Duration::from_millis(args.idle_after_ms),
+        interval: Duration::from_millis(args.idle_interval_ms),
+        threshold: args.motion_threshold,
+    }))
+}
+
+/// A second instance or an unwritable runtime dir only costs the socket, not the session.
+fn control_server(args: &ControlArgs) -> Option<ControlServer> {
+    if args.no_control {
+        return None;
+    }
+
+    let path = args.control_socket.as_ref().map_or_else(control::default_socket_path, PathBuf::from);
+    match ControlServer::bind(&path) {
+        Ok(server) => {
+            info!("Control socket: {}", server.path().display());
+            Some(server)
+        }
+        Err(e) => {
+            warn!("Control socket unavailable ({}), `air-link ctl` won't reach this instance", e);
+            None
+        }
+    }
+}
+
+fn pointer_device(args: &OutputArgs) -> Result<MouseManager> {
+    if args.no_mouse {
+        info!("System cursor left alone (--no-mouse)");
+        return Ok(MouseManager::detached());
+    }
+    if args.gamepad || args.touch || args.pen {
+        return Ok(MouseManager::detached());
+    }
+    if let (Some(target), Some(key)) = (&args.remote, &args.remote_key) {
+        return Ok(MouseManager::remote(RemoteSender::connect(target, key, transport_kind(args.remote_transport))?));
+    }
+    MouseManager::new()
+}
+
+fn gamepad(args: &OutputArgs) -> Result<Option<GamepadManager>> {
+    if !args.gamepad {
+        return Ok(None);
+    }
+    let config = match &args.gamepad_map {
+        Some(path) => GamepadConfig::load(path)?,
+        None => GamepadConfig::default(),
+    };
+    info!("🎮 Hand drives the virtual gamepad; `air-link ctl recenter` retakes the neutral point");
+    Ok(Some(GamepadManager::new(config)?))
+}
+
+fn touch_device(args: &OutputArgs, screen: ScreenConfig) -> Result<Option<TouchManager>> {
+    if !args.touch {
+        return Ok(None);
+    }
+    let test = match args.touch_test {
+        TouchTrigger::Pinch => TouchTest::Pinch,
+        TouchTrigger::Depth if args.touch_depth > 0.0 => TouchTest::Depth(args.touch_depth),
+        TouchTrigger::Depth => return Err(crate::AirLinkError::CliError("--touch-depth must be positive".into())),
+    };
+    info!("👆 Touchscreen spans {}x{}; map \"Air-Link Virtual Touchscreen\" to that output", screen.width, screen.height);
+    Ok(Some(TouchManager::new(test, (screen.width, screen.height))?))
+}
+
+fn pen_device(args: &OutputArgs, screen: ScreenConfig) -> Result<Option<PenManager>> {
+    if !args.pen {
+        return Ok(None);
+    }
+    let source = match args.pen_pressure {
+        PenPressure::Pinch => PressureSource::Pinch,
+        PenPressure::Depth if args.pen_depth_range > 0.0 => PressureSource::Depth(args.pen_depth_range),
+        PenPressure::Depth => return Err(crate::AirLinkError::CliError("--pen-depth-range must be positive".into())),
+    };
+    info!("🖊️  Pen tablet spans {}x{}; map \"Air-Link Virtual Pen\" to that output", screen.width, screen.height);
+    Ok(Some(PenManager::new(source, (screen.width, screen.height))?))
+}
+
+fn transport_kind(transport: RemoteTransport) -> Transport {
+    match transport {
+        RemoteTransport::Udp => Transport::Udp,
+        RemoteTransport::Tcp => Transport::Tcp,
+    }
+}
+
+fn osc_sink(args: &OutputArgs) -> Result<Option<OscSink>> {
+    let Some(target) = &args.osc else { return Ok(None) };
+    Ok(Some(OscSink::new(target, AddressTemplate::parse(&args.osc_address)?)?))
+}
+
+fn landmark_stream(args: &StreamArgs) -> Result<Option<StreamServer>> {
+    if args.stream_ws.is_none() && args.stream_udp.is_empty() {
+        return Ok(None);
+    }
+
+    let format = match args.stream_format {
+        StreamFormat::Json => WireFormat::Json,
+        StreamFormat::Binary => WireFormat::Binary,
+    };
+    let mut server = StreamServer::new(format);
+    if let Some(addr) = &args.stream_ws {
+        server = server.listen_websocket(addr)?;
+    }
+    for target in &args.stream_udp {
+        server = server.send_udp(target)?;
+    }
+    Ok(Some(server))
+}
+
+fn predictor(args: &PredictArgs) -> Option<Predictor> {
+    args.predict.then(|| Predictor::new(PredictorConfig {
+        horizon: Duration::from_millis(args.predict_horizon_ms),
+        acceleration: args.predict_acceleration,
+        max_offset: args.predict_max_offset,
+    }))
+}
+
+/// Raised on the first Ctrl-C, which then no longer kills the process, so a
+/// loop can stop and save its work.
+fn ctrl_c_flag() -> Arc<AtomicBool> {
+    let flag = Arc::new(AtomicBool::new(false));
+    let writer = Arc::clone(&flag);
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            info!("⏹️  Stopping...");
+            writer.store(true, Ordering::Relaxed);
+        }
+    });
+    flag
+}
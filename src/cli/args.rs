@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "air-link")]
@@ -12,42 +12,592 @@ pub struct Cli {
 pub enum Commands {
     /// Start the tracking loop (CLI mode)
     Run {
-        /// Camera device index
-        #[arg(short, long, default_value_t = 0)]
-        camera_id: u32,
+        #[command(flatten)]
+        camera: CameraArgs,
 
-        /// Cursor sensitivity multiplier
-        #[arg(short, long, default_value_t = 1.5)]
-        sensitivity: f32,
+        #[command(flatten)]
+        tracking: TrackingArgs,
 
-        /// Tracking algorithm to use
-        #[arg(short, long, value_enum, default_value_t = TrackingMode::Ai)]
-        mode: TrackingMode,
+        /// Pointer smoothing profile
+        #[arg(long, value_enum, default_value_t = FilterProfile::Standard)]
+        filter: FilterProfile,
 
         // --- Screen Configuration ---
-        #[arg(long, default_value_t = 1920)]
-        screen_width: i32,
-        #[arg(long, default_value_t = 1080)]
-        screen_height: i32,
-        #[arg(long, default_value_t = 0)]
-        screen_x_offset: i32,
-        #[arg(long, default_value_t = 0)]
-        screen_y_offset: i32,
+        #[command(flatten)]
+        screen: ScreenArgs,
+
+        #[command(flatten)]
+        mapping: MappingArgs,
+
+        #[command(flatten)]
+        clutch: ClutchArgs,
+
+        #[command(flatten)]
+        dwell: DwellArgs,
+
+        #[command(flatten)]
+        power: PowerArgs,
+
+        #[command(flatten)]
+        predict: PredictArgs,
+
+        #[command(flatten)]
+        control: ControlArgs,
+
+        #[command(flatten)]
+        stream: StreamArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
     },
     /// Start with Graphical User Interface
     Gui {
-        /// Camera device index
-        #[arg(short, long, default_value_t = 0)]
-        camera_id: u32,
+        #[command(flatten)]
+        camera: CameraArgs,
+
+        #[command(flatten)]
+        tracking: TrackingArgs,
+
+        /// Pointer smoothing profile
+        #[arg(long, value_enum, default_value_t = FilterProfile::Standard)]
+        filter: FilterProfile,
+
+        #[command(flatten)]
+        screen: ScreenArgs,
+
+        #[command(flatten)]
+        mapping: MappingArgs,
+
+        #[command(flatten)]
+        clutch: ClutchArgs,
+
+        #[command(flatten)]
+        dwell: DwellArgs,
+
+        #[command(flatten)]
+        power: PowerArgs,
+
+        #[command(flatten)]
+        predict: PredictArgs,
+
+        #[command(flatten)]
+        control: ControlArgs,
+
+        #[command(flatten)]
+        stream: StreamArgs,
+
+        #[command(flatten)]
+        output: OutputArgs,
     },
-    /// Helper to find your camera ID
-    ListCameras,
+    /// Helper to find your camera ID, with every supported mode
+    ListCameras {
+        /// Print machine-readable JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show detected monitors and their geometry
+    ListScreens {
+        /// JSON dump from `hyprctl -j monitors` or `wlr-randr --json` ("-" for stdin)
+        #[arg(long)]
+        screen_geometry: Option<String>,
+    },
+    /// Run hand detection without any output device and write per-frame landmark records
+    Dump {
+        #[command(flatten)]
+        camera: CameraArgs,
+
+        /// Video file (decoded with ffmpeg), image or image folder instead of the camera
+        #[arg(long)]
+        input: Option<String>,
+
+        /// Hand landmark model; a `.toml` descriptor next to it is picked up
+        #[arg(long, default_value = "assets/hand_landmark.onnx")]
+        hand_model: String,
+
+        /// Record format
+        #[arg(long, value_enum, default_value_t = RecordFormat::Jsonl)]
+        output_format: RecordFormat,
+
+        /// File to write; stdout when omitted or "-"
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Stop after this many frames (a camera otherwise runs until interrupted)
+        #[arg(long)]
+        max_frames: Option<u64>,
+    },
+    /// Save camera frames with the detector's landmarks as a COCO-keypoint dataset
+    CaptureDataset {
+        #[command(flatten)]
+        camera: CameraArgs,
+
+        /// Hand landmark model whose landmarks become the pseudo-labels
+        #[arg(long, default_value = "assets/hand_landmark.onnx")]
+        hand_model: String,
+
+        /// Dataset folder; gets images/ and annotations.json
+        #[arg(short, long)]
+        output: String,
+
+        /// Also save a frame this often (otherwise only on Space/Enter in the window)
+        #[arg(long)]
+        interval_ms: Option<u64>,
+
+        /// Session name for the dataset info (defaults to the folder name)
+        #[arg(long)]
+        session: Option<String>,
+
+        /// Free-form notes on lighting, camera placement and so on
+        #[arg(long)]
+        notes: Option<String>,
+
+        /// No window: capture on the interval until --max-frames or Ctrl-C
+        #[arg(long, requires = "interval_ms")]
+        headless: bool,
+
+        /// Stop after saving this many frames (headless)
+        #[arg(long)]
+        max_frames: Option<u64>,
+    },
+    /// Measure hand detection accuracy and speed on a labelled image set
+    Eval {
+        /// COCO keypoint file (e.g. from capture-dataset) or simple JSON label file
+        labels: String,
+
+        /// Image folder (default: images/ next to a COCO file, else the label file's folder)
+        #[arg(long)]
+        images: Option<String>,
+
+        /// Hand landmark model to evaluate
+        #[arg(long, default_value = "assets/hand_landmark.onnx")]
+        hand_model: String,
+
+        /// PCK thresholds, as fractions of the labelled hand's bounding box
+        #[arg(long, value_delimiter = ',', default_values_t = [0.05, 0.1, 0.2])]
+        pck: Vec<f32>,
+
+        /// Write the full report as JSON
+        #[arg(long)]
+        report: Option<String>,
+
+        /// Previous JSON report; fails when a metric got worse
+        #[arg(long)]
+        baseline: Option<String>,
+    },
+    /// Own the local pointer and apply events sent by `run --remote` on another machine
+    ServeInput {
+        /// Address to listen on; loopback only unless given, e.g. 0.0.0.0:7878 for other machines
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        listen: String,
+
+        /// Shared key; must match the sender's --remote-key
+        #[arg(long, env = "AIR_LINK_KEY", hide_env_values = true)]
+        key: String,
+
+        #[arg(long, value_enum, default_value_t = RemoteTransport::Udp)]
+        transport: RemoteTransport,
+    },
+    /// Send a command to a running instance, e.g. `ctl pause` or `ctl set-sensitivity gain=2`
+    Ctl {
+        /// Control socket of the running instance
+        #[arg(long)]
+        socket: Option<String>,
+
+        /// status, pause, resume, toggle, set-profile, set-sensitivity, calibrate or recenter
+        method: String,
+
+        /// Parameters as key=value; values are read as JSON, else as text
+        params: Vec<String>,
+    },
+}
+
+/// Capture device selection shared by `run` and `gui`
+#[derive(Args, Clone, Debug)]
+pub struct CameraArgs {
+    /// Camera device index
+    #[arg(short, long, default_value_t = 0)]
+    pub camera_id: u32,
+
+    /// Camera to open by (part of) its name, bus info or serial; overrides --camera-id
+    #[arg(long)]
+    pub camera: Option<String>,
+
+    /// Capture resolution, e.g. 1280x720 (closest supported mode is used)
+    #[arg(long)]
+    pub resolution: Option<String>,
+
+    /// Capture frame rate (closest supported rate is used)
+    #[arg(long)]
+    pub fps: Option<u32>,
+
+    /// Capture pixel format; must be supported by the camera
+    #[arg(long, value_enum)]
+    pub format: Option<CaptureFormat>,
+
+    /// If the camera disappears and its index does not come back, switch to a
+    /// camera whose name, bus info or serial contains this text
+    #[arg(long)]
+    pub camera_fallback: Option<String>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum RecordFormat {
+    /// One JSON object per frame and line
+    Jsonl,
+    /// One row per frame, landmarks as x0,y0,z0 ... x20,y20,z20
+    Csv,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum CaptureFormat {
+    /// Compressed; lowest USB bandwidth, needs decoding
+    Mjpeg,
+    /// Packed 4:2:2, common for uncompressed low resolutions
+    Yuyv,
+    Nv12,
+    /// 8-bit grayscale (IR cameras)
+    Gray,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
 pub enum TrackingMode {
-    /// Fastest, tracks a specific color
+    /// Color blob tracking (not implemented yet)
     Color,
     /// Slower, uses AI model
     Ai,
+    /// Head pointer: nose tip from a face landmark model
+    Face,
+}
+
+/// Tracker selection shared by `run` and `gui`
+#[derive(Args, Clone, Debug)]
+pub struct TrackingArgs {
+    /// Tracking algorithm to use
+    #[arg(short, long, value_enum, default_value_t = TrackingMode::Ai)]
+    pub mode: TrackingMode,
+
+    /// Hand landmark model for `--mode ai`; a `.toml` descriptor next to it is picked up
+    #[arg(long, default_value = "assets/hand_landmark.onnx")]
+    pub hand_model: String,
+
+    /// Face landmark model for `--mode face`
+    #[arg(long, default_value = "assets/face_landmark.onnx")]
+    pub face_model: String,
+
+    /// Face gesture that clicks in `--mode face`
+    #[arg(long, value_enum, default_value_t = FaceGesture::Blink)]
+    pub face_click: FaceGesture,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum FaceGesture {
+    /// Long, deliberate blink with both eyes
+    Blink,
+    MouthOpen,
+}
+
+/// Target screen selection shared by `run` and `gui`
+#[derive(Args, Clone, Debug)]
+pub struct ScreenArgs {
+    /// Monitor to drive, by connector name (see `list-screens`)
+    #[arg(long)]
+    pub screen: Option<String>,
+
+    /// JSON dump from `hyprctl -j monitors` or `wlr-randr --json` ("-" for stdin);
+    /// without it a single active output is read from the DRM state in debugfs
+    #[arg(long)]
+    pub screen_geometry: Option<String>,
+
+    /// Map the active zone onto the whole virtual desktop instead of one monitor
+    #[arg(long, conflicts_with = "swipe_switch")]
+    pub span_desktop: bool,
+
+    /// A fast horizontal swipe jumps to the neighbouring monitor
+    #[arg(long)]
+    pub swipe_switch: bool,
+
+    /// Per-monitor calibration JSON: {"DP-1": {"gain_x": 1.2, "zone_min": 0.25, ...}}
+    #[arg(long)]
+    pub screen_calibration: Option<String>,
+
+    // Manual geometry, overrides discovery when given
+    #[arg(long)]
+    pub screen_width: Option<i32>,
+    #[arg(long)]
+    pub screen_height: Option<i32>,
+    #[arg(long)]
+    pub screen_x_offset: Option<i32>,
+    #[arg(long)]
+    pub screen_y_offset: Option<i32>,
+}
+
+/// Pointer mapping (transfer curve, gain, dead zone) shared by `run` and `gui`
+#[derive(Args, Clone, Debug)]
+pub struct MappingArgs {
+    /// Cursor gain around the active-zone center. The default 1.5 spans the
+    /// screen with the middle two thirds of the zone; 1.0 maps the whole zone.
+    /// This used to scale the absolute cursor position, so retune older values
+    #[arg(short, long, default_value_t = 1.5)]
+    pub sensitivity: f32,
+
+    /// Horizontal gain, overrides --sensitivity for X
+    #[arg(long)]
+    pub gain_x: Option<f32>,
+
+    /// Vertical gain, overrides --sensitivity for Y
+    #[arg(long)]
+    pub gain_y: Option<f32>,
+
+    /// Transfer function from hand offset to cursor offset
+    #[arg(long, value_enum, default_value_t = CurveKind::Linear)]
+    pub curve: CurveKind,
+
+    /// Exponent for `power`, steepness for `sigmoid`
+    #[arg(long, default_value_t = 2.0)]
+    pub curve_param: f32,
+
+    /// Points for `table`, e.g. "0.2:0.1,0.6:0.5" (0:0 and 1:1 implied)
+    #[arg(long)]
+    pub curve_table: Option<String>,
+
+    /// Rest dead zone as a fraction of the active zone
+    #[arg(long, default_value_t = 0.005)]
+    pub dead_zone: f32,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum CurveKind {
+    Linear,
+    /// Precise near the center, fast at the edges
+    Power,
+    /// Fast through the center, gentle at the edges
+    Sigmoid,
+    /// Custom piecewise-linear table (--curve-table)
+    Table,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum FilterProfile {
+    /// EMA smoothing, responsive
+    Standard,
+    /// Accessibility: suppresses 4-12 Hz tremor with a rest dead zone
+    Tremor,
+}
+
+/// Engage/disengage ("clutch") options shared by `run` and `gui`
+#[derive(Args, Clone, Debug)]
+pub struct ClutchArgs {
+    /// Hand pose that toggles pointer control
+    #[arg(long, value_enum, default_value_t = ClutchPose::Fist)]
+    pub clutch_pose: ClutchPose,
+
+    /// How long the clutch pose must be held (ms)
+    #[arg(long, default_value_t = 300)]
+    pub clutch_hold_ms: u64,
+
+    /// Disengage after the hand dwells outside the active zone this long (ms, 0 = off)
+    #[arg(long, default_value_t = 0)]
+    pub clutch_zone_dwell_ms: u64,
+}
+
+/// Dwell-click accessibility options shared by `run` and `gui`
+#[derive(Args, Clone, Debug)]
+pub struct DwellArgs {
+    /// Click by resting the cursor instead of pinching
+    #[arg(long)]
+    pub dwell_click: bool,
+
+    /// Initial dwell action (cycle with Tab in the GUI)
+    #[arg(long, value_enum, default_value_t = DwellMode::Left)]
+    pub dwell_action: DwellMode,
+
+    /// Resting time before a dwell fires (ms)
+    #[arg(long, default_value_t = 800)]
+    pub dwell_ms: u64,
+
+    /// Max cursor wander while resting (screen pixels)
+    #[arg(long, default_value_t = 30.0)]
+    pub dwell_radius: f32,
+
+    /// Quiet time after a dwell fires (ms)
+    #[arg(long, default_value_t = 1000)]
+    pub dwell_refractory_ms: u64,
+}
+
+/// Latency compensation shared by `run` and `gui`
+#[derive(Args, Clone, Debug)]
+pub struct PredictArgs {
+    /// Extrapolate the pointer by the measured capture-to-cursor latency
+    #[arg(long)]
+    pub predict: bool,
+
+    /// Never predict further ahead than this (ms)
+    #[arg(long, default_value_t = 80)]
+    pub predict_horizon_ms: u64,
+
+    /// Also extrapolate acceleration (snappier, less stable)
+    #[arg(long)]
+    pub predict_acceleration: bool,
+
+    /// Largest correction as a fraction of the camera frame
+    #[arg(long, default_value_t = 0.05)]
+    pub predict_max_offset: f32,
+}
+
+/// Runtime control socket shared by `run` and `gui`
+#[derive(Args, Clone, Debug)]
+pub struct ControlArgs {
+    /// Where to listen for `air-link ctl` (default: $XDG_RUNTIME_DIR/air-link.sock)
+    #[arg(long)]
+    pub control_socket: Option<String>,
+
+    /// Don't open the control socket
+    #[arg(long, conflicts_with = "control_socket")]
+    pub no_control: bool,
+}
+
+/// Where the pointer goes, shared by `run` and `gui`
+#[derive(Args, Clone, Debug)]
+pub struct OutputArgs {
+    /// Don't move the system cursor (no uinput needed); use with --osc or --stream-*
+    #[arg(long, conflicts_with = "remote")]
+    pub no_mouse: bool,
+
+    /// Drive a virtual gamepad (sticks and buttons) instead of the cursor
+    #[arg(long, conflicts_with = "remote")]
+    pub gamepad: bool,
+
+    /// TOML mapping table for --gamepad (axis sources, buttons, range, dead zone)
+    #[arg(long, requires = "gamepad")]
+    pub gamepad_map: Option<String>,
+
+    /// Act as a multi-touch screen on the target monitor instead of moving the cursor
+    #[arg(long, conflicts_with_all = ["remote", "gamepad"])]
+    pub touch: bool,
+
+    /// What puts a finger on the virtual screen
+    #[arg(long, value_enum, default_value_t = TouchTrigger::Pinch)]
+    pub touch_test: TouchTrigger,
+
+    /// For --touch-test depth: how much closer than the wrist a fingertip must be (frame widths)
+    #[arg(long, default_value_t = 0.08)]
+    pub touch_depth: f32,
+
+    /// Act as a pen tablet on the target monitor: hover with the hand, pinch to draw
+    #[arg(long, conflicts_with_all = ["remote", "gamepad", "touch"])]
+    pub pen: bool,
+
+    /// Where pen pressure comes from while pinching
+    #[arg(long, value_enum, default_value_t = PenPressure::Pinch)]
+    pub pen_pressure: PenPressure,
+
+    /// For --pen-pressure depth: push toward the camera (frame widths) for full pressure
+    #[arg(long, default_value_t = 0.1)]
+    pub pen_depth_range: f32,
+
+    /// Drive the cursor of another machine running `air-link serve-input` at host:port
+    #[arg(long, requires = "remote_key")]
+    pub remote: Option<String>,
+
+    /// Shared key for --remote
+    #[arg(long, env = "AIR_LINK_KEY", hide_env_values = true)]
+    pub remote_key: Option<String>,
+
+    #[arg(long, value_enum, default_value_t = RemoteTransport::Udp)]
+    pub remote_transport: RemoteTransport,
+
+    /// Send landmarks, pinch, pose and gesture events as OSC to host:port
+    #[arg(long)]
+    pub osc: Option<String>,
+
+    /// OSC address pattern; {name} is e.g. `pointer` or `landmark/8`, {hand} is left/right/unknown
+    #[arg(long, default_value = "/air-link/{name}")]
+    pub osc_address: String,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum PenPressure {
+    /// Squeeze the pinch tighter for more pressure
+    Pinch,
+    /// Push the pinching hand toward the camera for more pressure
+    Depth,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum TouchTrigger {
+    /// Each pinching hand is one finger on the screen (two hands pinch-zoom)
+    Pinch,
+    /// Index and middle tips touch when pushed toward the camera (two-finger zoom with one hand)
+    Depth,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum RemoteTransport {
+    /// Lowest latency; lost packets are repaired by the next one
+    Udp,
+    /// For links that drop or reorder a lot
+    Tcp,
+}
+
+/// Landmark streaming to other local tools, shared by `run` and `gui`
+#[derive(Args, Clone, Debug)]
+pub struct StreamArgs {
+    /// Serve landmarks, pointer and gesture events over WebSocket, e.g. 127.0.0.1:9001
+    #[arg(long)]
+    pub stream_ws: Option<String>,
+
+    /// Send the same messages as UDP datagrams to this address (repeatable)
+    #[arg(long)]
+    pub stream_udp: Vec<String>,
+
+    /// Message encoding for both transports
+    #[arg(long, value_enum, default_value_t = StreamFormat::Json)]
+    pub stream_format: StreamFormat,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum StreamFormat {
+    /// One JSON object per message
+    Json,
+    /// Compact little-endian layout (see README)
+    Binary,
+}
+
+/// Idle power saving shared by `run` and `gui`
+#[derive(Args, Clone, Debug)]
+pub struct PowerArgs {
+    /// Keep full-rate tracking even when nobody is in view
+    #[arg(long)]
+    pub no_idle: bool,
+
+    /// Time without a hand before dropping to idle (ms)
+    #[arg(long, default_value_t = 10_000)]
+    pub idle_after_ms: u64,
+
+    /// Time between motion checks while idle (ms)
+    #[arg(long, default_value_t = 200)]
+    pub idle_interval_ms: u64,
+
+    /// Mean brightness change (0..1) of a 32x32 thumbnail between checks that counts as motion
+    #[arg(long, default_value_t = 0.02)]
+    pub motion_threshold: f32,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum DwellMode {
+    Left,
+    Right,
+    Double,
+    /// First dwell presses, second dwell releases
+    Drag,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
+pub enum ClutchPose {
+    /// Pose toggling disabled
+    None,
+    /// Closed fist
+    Fist,
+    /// Back of the hand towards the camera
+    PalmAway,
 }
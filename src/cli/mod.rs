@@ -1,4 +1,9 @@
 pub mod args;
 pub mod actions;
 
-pub use args::{Cli, Commands, TrackingMode};
+pub use args::{
+    CameraArgs, CaptureFormat, Cli, ClutchArgs, ClutchPose, Commands, ControlArgs, CurveKind,
+    DwellArgs, DwellMode, FaceGesture, FilterProfile, MappingArgs, OutputArgs, PenPressure,
+    PowerArgs, PredictArgs, RecordFormat, RemoteTransport, ScreenArgs, StreamArgs, StreamFormat,
+    TouchTrigger, TrackingArgs, TrackingMode,
+};
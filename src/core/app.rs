@@ -1,122 +1,790 @@
-use crate::core::{CameraManager, vision::HandDetector, MouseManager};
-use crate::core::logic::SmoothFilter;
+use crate::core::{CameraManager, MouseManager};
+use crate::core::camera::{CameraConfig, CameraEvent};
+use crate::core::clutch::{Clutch, ClutchConfig, ClutchState};
+use crate::core::control::{self, ControlServer, RpcError, RpcResult};
+use crate::core::frame::Frame;
+use crate::core::gamepad::PadInput;
+use crate::core::dwell::{DwellAction, DwellClicker};
+use crate::core::gesture::{HandPose, Swipe, SwipeDetector};
+use crate::core::idle::{IdleEvent, IdleScheduler};
+use crate::core::input::{GamepadManager, PenManager, TouchManager};
+use crate::core::layout::{DesktopLayout, ZoneSweep};
+use crate::core::tracker::Tracker;
+use crate::core::logic::{PointFilter, SmoothFilter, TremorConfig, TremorFilter};
+use crate::core::osc::OscSink;
+use crate::core::predict::Predictor;
+use crate::core::stream::{Outbox, Sample, StreamEvent, StreamServer};
+use crate::core::mapping::{CoordinateMapper, MapperConfig};
+use crate::core::metrics::Metrics;
 use crate::Result;
-use tracing::{info, error};
-use std::time::Instant;
-
-pub struct ScreenConfig {
-    pub width: i32,
-    pub height: i32,
-    pub x_offset: i32,
-    pub y_offset: i32,
+use serde_json::{json, Value};
+use tracing::info;
+use std::io::BufRead;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+pub use crate::core::mapping::ScreenConfig;
+
+/// What the pipeline saw on the last processed frame.
+#[derive(Copy, Clone, Debug)]
+pub struct TrackingStatus {
+    pub hand_present: bool,
+    pub pose: HandPose,
+    pub clutch: ClutchState,
+    /// Index fingertip in frame-normalized coordinates.
+    pub pointer: Option<(f32, f32)>,
+    /// Dwell-click progress (0.0..=1.0), when dwell clicking is enabled.
+    pub dwell_progress: Option<f32>,
+    /// Tracking is paused until motion shows up.
+    pub idle: bool,
+    /// Tracking is paused from the control socket.
+    pub paused: bool,
+    /// Dequeue time and sequence number of the frame this describes.
+    pub timestamp: Instant,
+    pub sequence: u64,
 }
 
 pub struct AppContext {
     pub camera: CameraManager,
-    pub detector: HandDetector,
+    pub tracker: Box<dyn Tracker>,
     pub mouse: MouseManager,
-    pub filter: SmoothFilter,
-    pub sensitivity: f32,
-    pub screen_config: ScreenConfig,
+    pub filter: Box<dyn PointFilter>,
+    pub clutch: Clutch,
+    pub dwell: Option<DwellClicker>,
+    pub idle: Option<IdleScheduler>,
+    pub predictor: Option<Predictor>,
+    pub control: Option<ControlServer>,
+    pub stream: Option<StreamServer>,
+    pub osc: Option<OscSink>,
+    pub gamepad: Option<GamepadManager>,
+    pub touch: Option<TouchManager>,
+    pub pen: Option<PenManager>,
+    pub mapper: CoordinateMapper,
+    pub layout: DesktopLayout,
+    pub metrics: Metrics,
     pub is_clicking: bool,
+    /// Paused from the control socket: frames are captured but not tracked.
+    pub paused: bool,
+    last_frame: Option<Instant>,
+    /// Active-zone calibration in progress.
+    sweep: Option<ZoneSweep>,
+    /// Landmarks and events of the current frame, for the stream and OSC.
+    outbox: Outbox,
+    swipe: Option<SwipeDetector>,
+    /// Mapping flags before per-monitor calibration.
+    base_mapping: (MapperConfig, (f32, f32)),
 }
 
 impl AppContext {
-    pub fn new(camera_id: u32, sensitivity: f32, screen_config: ScreenConfig) -> Result<Self> {
+    /// Opens the camera. The pointer device starts detached; attach one with
+    /// [`AppContext::with_mouse`].
+    pub fn new(
+        camera_config: CameraConfig,
+        tracker: Box<dyn Tracker>,
+        mapper: CoordinateMapper,
+        layout: DesktopLayout,
+        clutch_config: ClutchConfig,
+        dwell: Option<DwellClicker>,
+        filter: Box<dyn PointFilter>,
+    ) -> Result<Self> {
         info!("Initializing Air-Link Core...");
-        let mut camera = CameraManager::new(camera_id)?;
+        let mut camera = CameraManager::new(camera_config)?;
         camera.start()?;
-        
-        let detector = HandDetector::new("assets/hand_landmark.onnx")?;
-        let mouse = MouseManager::new()?;
-        let filter = SmoothFilter::new(0.2); // Smooth but responsive
-        
-        Ok(Self { 
-            camera, 
-            detector, 
-            mouse, 
+
+        let base_mapping = (mapper.config().clone(), mapper.zone());
+        let swipe = (layout.switch_on_swipe && layout.monitors().len() > 1).then(SwipeDetector::new);
+
+        let mut app = Self {
+            camera,
+            tracker,
+            mouse: MouseManager::detached(),
             filter,
-            sensitivity, 
-            screen_config,
+            clutch: Clutch::new(clutch_config),
+            dwell,
+            idle: None,
+            predictor: None,
+            control: None,
+            stream: None,
+            osc: None,
+            gamepad: None,
+            touch: None,
+            pen: None,
+            mapper,
+            layout,
+            metrics: Metrics::new(),
             is_clicking: false,
+            paused: false,
+            last_frame: None,
+            sweep: None,
+            outbox: Outbox::default(),
+            swipe,
+            base_mapping,
+        };
+        app.apply_layout();
+        Ok(app)
+    }
+
+    /// Pauses tracking when nobody is in front of the camera.
+    pub fn with_idle(mut self, idle: Option<IdleScheduler>) -> Self {
+        self.idle = idle;
+        self
+    }
+
+    /// Extrapolates the pointer by the measured pipeline latency.
+    pub fn with_predictor(mut self, predictor: Option<Predictor>) -> Self {
+        self.predictor = predictor;
+        self
+    }
+
+    /// Accepts runtime commands (see [`AppContext::control`]) from a socket.
+    pub fn with_control(mut self, control: Option<ControlServer>) -> Self {
+        self.control = control;
+        self
+    }
+
+    /// Drives the system cursor through `mouse`.
+    pub fn with_mouse(mut self, mouse: MouseManager) -> Self {
+        self.mouse = mouse;
+        self
+    }
+
+    /// Publishes landmarks, pointer and gesture events of every frame.
+    pub fn with_stream(mut self, stream: Option<StreamServer>) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    /// Sends landmarks, pinch, pose and gesture events of every frame as OSC.
+    pub fn with_osc(mut self, osc: Option<OscSink>) -> Self {
+        self.osc = osc;
+        self
+    }
+
+    /// Drives a virtual gamepad from the hand, usually with the mouse detached.
+    pub fn with_gamepad(mut self, gamepad: Option<GamepadManager>) -> Self {
+        self.gamepad = gamepad;
+        self
+    }
+
+    /// Turns touching fingertips into contacts on a virtual touchscreen.
+    pub fn with_touch(mut self, touch: Option<TouchManager>) -> Self {
+        self.touch = touch;
+        self
+    }
+
+    /// Draws with a virtual pen: pinch to touch, pressure from the pinch or its depth.
+    pub fn with_pen(mut self, pen: Option<PenManager>) -> Self {
+        self.pen = pen;
+        self
+    }
+
+    /// Whether anything consumes the per-frame outbox.
+    fn publishing(&self) -> bool {
+        self.stream.is_some() || self.osc.is_some()
+    }
+
+    /// Queues `event` for the stream and OSC, if either is running.
+    fn emit(&mut self, event: StreamEvent) {
+        if self.publishing() {
+            self.outbox.events.push(event);
+        }
+    }
+
+    /// Forgets pointer history so the next sample is taken as-is.
+    fn reset_motion(&mut self) {
+        self.filter.reset();
+        self.mapper.reset();
+        if let Some(predictor) = &mut self.predictor {
+            predictor.reset();
+        }
+        self.last_frame = None;
+    }
+
+    /// Points the mapper at the layout's current target, with its calibration.
+    fn apply_layout(&mut self) {
+        let (base, base_zone) = &self.base_mapping;
+        let (config, zone) = match self.layout.calibration() {
+            Some(calibration) => calibration.apply(base, *base_zone),
+            None => (base.clone(), *base_zone),
+        };
+        self.mapper.reconfigure(config, zone);
+        self.mapper.set_screen(self.layout.current_screen());
+    }
+
+    /// Moves the mapped region to the next (or previous) monitor.
+    pub fn switch_screen(&mut self, forward: bool) {
+        if self.layout.step(forward) {
+            self.apply_layout();
+            info!("🖥️  Now targeting screen {}", self.layout.current_name());
+        }
+    }
+
+    /// Hotkey entry point: flips pointer control on or off.
+    pub fn toggle_clutch(&mut self) -> ClutchState {
+        let state = self.clutch.toggle();
+        self.on_clutch_change(state);
+        state
+    }
+
+    fn on_clutch_change(&mut self, state: ClutchState) {
+        match state {
+            ClutchState::Engaged => {
+                info!("🤚 Pointer control ENGAGED");
+                self.emit(StreamEvent::Engaged);
+            }
+            ClutchState::Disengaged => {
+                info!("✊ Pointer control DISENGAGED");
+                self.emit(StreamEvent::Disengaged);
+                self.let_go();
+            }
+        }
+    }
+
+    /// Releases every button and forgets motion, so resuming never jumps or drags.
+    fn let_go(&mut self) {
+        if let Err(e) = self.mouse.reset() {
+            println!("❌ MOUSE ERROR: {}", e);
+        }
+        self.hand_outputs_lost();
+        self.reset_motion();
+        if let Some(swipe) = &mut self.swipe {
+            swipe.reset();
+        }
+        if let Some(dwell) = &mut self.dwell {
+            dwell.reset();
+            dwell.end_drag();
+        }
+        self.is_clicking = false;
+    }
+
+    /// Centres the gamepad (letting its neutral point be retaken), lifts every
+    /// touch and takes the pen out of proximity.
+    fn hand_outputs_lost(&mut self) {
+        if let Some(Err(e)) = self.gamepad.as_mut().map(GamepadManager::hand_lost) {
+            println!("❌ GAMEPAD ERROR: {}", e);
+        }
+        if let Some(Err(e)) = self.touch.as_mut().map(TouchManager::lift_all) {
+            println!("❌ TOUCH ERROR: {}", e);
+        }
+        if let Some(Err(e)) = self.pen.as_mut().map(PenManager::leave) {
+            println!("❌ PEN ERROR: {}", e);
+        }
+    }
+
+    /// Takes the gamepad's neutral point from the next tracked frame.
+    pub fn recenter_gamepad(&mut self) -> bool {
+        let Some(gamepad) = &mut self.gamepad else { return false };
+        gamepad.mapper.recenter();
+        info!("🎮 Gamepad sticks recentred");
+        true
+    }
+
+    /// Stops (or restarts) tracking without closing the camera.
+    pub fn set_paused(&mut self, paused: bool) {
+        if paused == self.paused {
+            return;
+        }
+        self.paused = paused;
+        if paused {
+            info!("⏸️  Tracking PAUSED");
+            self.emit(StreamEvent::Paused);
+            self.let_go();
+        } else {
+            info!("▶️  Tracking RESUMED");
+            self.emit(StreamEvent::Resumed);
+        }
+    }
+
+    /// Answers pending control socket calls; call once per loop iteration.
+    pub fn poll_control(&mut self) {
+        let Some(server) = self.control.take() else { return };
+        server.poll(|method, params| self.control(method, params));
+        self.control = Some(server);
+    }
+
+    /// Runtime command dispatch for the control socket.
+    pub fn control(&mut self, method: &str, params: &Value) -> RpcResult {
+        match method {
+            "status" => Ok(self.status_json()),
+            "pause" => {
+                self.set_paused(true);
+                Ok(json!({ "paused": true }))
+            }
+            "resume" => {
+                self.set_paused(false);
+                Ok(json!({ "paused": false }))
+            }
+            "toggle" => {
+                let state = self.toggle_clutch();
+                Ok(json!({ "clutch": format!("{:?}", state).to_lowercase() }))
+            }
+            "set_profile" => self.set_profile(params),
+            "set_sensitivity" => self.set_sensitivity(params),
+            "calibrate" => self.start_calibration(params),
+            "recenter" => {
+                if !self.recenter_gamepad() {
+                    return Err(RpcError::new(control::SERVER_ERROR, "No gamepad attached (start with --gamepad)"));
+                }
+                Ok(json!({ "recentered": true }))
+            }
+            _ => Err(RpcError::new(control::METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+        }
+    }
+
+    fn status_json(&self) -> Value {
+        let (config, zone) = (self.mapper.config(), self.mapper.zone());
+        let ms = |d: Option<Duration>| d.map(|d| d.as_secs_f32() * 1000.0);
+        json!({
+            "paused": self.paused,
+            "mouse": self.mouse.is_attached(),
+            "gamepad": self.gamepad.is_some(),
+            "touch": self.touch.is_some(),
+            "pen": self.pen.is_some(),
+            "clutch": format!("{:?}", self.clutch.state()).to_lowercase(),
+            "idle": self.idle.as_ref().is_some_and(|idle| idle.is_idle()),
+            "calibrating": self.sweep.is_some(),
+            "screen": self.layout.current_name(),
+            "profile": self.filter.name(),
+            "gain": [config.gain.0, config.gain.1],
+            "dead_zone": config.dead_zone,
+            "zone": [zone.0, zone.1],
+            "dwell": self.dwell.as_ref().map(|d| format!("{:?}", d.action()).to_lowercase()),
+            "metrics": {
+                "fps": self.metrics.fps(),
+                "frames": self.metrics.frames,
+                "camera_errors": self.metrics.camera_errors,
+                "camera_losses": self.metrics.camera_losses,
+                "reconnects": self.metrics.reconnects,
+                "latency_ms": ms(self.metrics.latency),
+                "idle_entries": self.metrics.idle_entries,
+                "idle_cpu": self.metrics.idle_cpu,
+                "wake_latency_ms": ms(self.metrics.last_wake_latency),
+            },
+        })
+    }
+
+    /// `{profile, alpha?}` or `{profile: "tremor", q?, dead_zone?, dead_zone_speed?}`.
+    fn set_profile(&mut self, params: &Value) -> RpcResult {
+        let filter: Box<dyn PointFilter> = match params.get("profile").and_then(Value::as_str) {
+            Some("standard") => {
+                let alpha = control::number(params, "alpha")?.unwrap_or(SmoothFilter::DEFAULT_ALPHA);
+                if alpha <= 0.0 || alpha > 1.0 {
+                    return Err(RpcError::invalid_params("alpha must be in (0, 1]"));
+                }
+                Box::new(SmoothFilter::new(alpha))
+            }
+            Some("tremor") => {
+                let defaults = TremorConfig::default();
+                let config = TremorConfig {
+                    q: control::number(params, "q")?.unwrap_or(defaults.q),
+                    dead_zone: control::number(params, "dead_zone")?.unwrap_or(defaults.dead_zone),
+                    dead_zone_speed: control::number(params, "dead_zone_speed")?.unwrap_or(defaults.dead_zone_speed),
+                    ..defaults
+                };
+                if config.q <= 0.0 || config.dead_zone < 0.0 || config.dead_zone_speed <= 0.0 {
+                    return Err(RpcError::invalid_params("q and dead_zone_speed must be positive, dead_zone not negative"));
+                }
+                Box::new(TremorFilter::new(config))
+            }
+            _ => return Err(RpcError::invalid_params("profile must be \"standard\" or \"tremor\"")),
+        };
+        self.filter = filter;
+        self.reset_motion();
+        info!("🎛️  Filter profile: {}", self.filter.name());
+        Ok(json!({ "profile": self.filter.name() }))
+    }
+
+    /// `{gain?, gain_x?, gain_y?, dead_zone?}`; per-monitor calibration still applies on top.
+    fn set_sensitivity(&mut self, params: &Value) -> RpcResult {
+        let gain = control::number(params, "gain")?;
+        let gain_x = control::number(params, "gain_x")?.or(gain);
+        let gain_y = control::number(params, "gain_y")?.or(gain);
+        let dead_zone = control::number(params, "dead_zone")?;
+        if [gain_x, gain_y].into_iter().flatten().any(|g| g <= 0.0) {
+            return Err(RpcError::invalid_params("gain must be positive"));
+        }
+        if dead_zone.is_some_and(|d| !(0.0..0.5).contains(&d)) {
+            return Err(RpcError::invalid_params("dead_zone must be in [0, 0.5)"));
+        }
+
+        let base = &mut self.base_mapping.0;
+        base.gain = (gain_x.unwrap_or(base.gain.0), gain_y.unwrap_or(base.gain.1));
+        base.dead_zone = dead_zone.unwrap_or(base.dead_zone);
+        self.apply_layout();
+
+        let config = self.mapper.config();
+        info!("🎛️  Gain {:.2}x{:.2}, dead zone {:.3}", config.gain.0, config.gain.1, config.dead_zone);
+        Ok(json!({ "gain": [config.gain.0, config.gain.1], "dead_zone": config.dead_zone }))
+    }
+
+    /// `{seconds?}`: learns the active zone of the current screen from a hand sweep.
+    fn start_calibration(&mut self, params: &Value) -> RpcResult {
+        let seconds = control::number(params, "seconds")?.unwrap_or(3.0);
+        if !(0.5..=30.0).contains(&seconds) {
+            return Err(RpcError::invalid_params("seconds must be between 0.5 and 30"));
+        }
+        self.sweep = Some(ZoneSweep::new(Instant::now() + Duration::from_secs_f32(seconds)));
+        println!("📐 Calibrating {}: sweep your hand across the whole range for {:.1}s", self.layout.current_name(), seconds);
+        Ok(json!({ "calibrating": true, "seconds": seconds, "screen": self.layout.current_name() }))
+    }
+
+    /// Applies the calibration sweep once its time is up.
+    fn finish_calibration(&mut self, now: Instant) {
+        let Some(sweep) = self.sweep.take_if(|sweep| now >= sweep.until) else { return };
+        match sweep.zone() {
+            Some(zone) => {
+                self.layout.set_zone(zone);
+                self.apply_layout();
+                println!("📐 Active zone for {}: {:.2}..{:.2}", self.layout.current_name(), zone.0, zone.1);
+            }
+            None => println!("📐 Calibration failed: the hand barely moved, keeping the old zone"),
+        }
+    }
+
+    /// Runs tracking, clutch and pointer logic on a single frame, unless the
+    /// idle scheduler only wants a motion check, and publishes the result.
+    pub fn process_frame(&mut self, frame: &Frame) -> Result<TrackingStatus> {
+        let status = self.step(frame);
+        if let (true, Ok(status)) = (self.publishing(), &status) {
+            let sample = Sample {
+                frame: status.sequence,
+                timestamp: status.timestamp,
+                pose: status.pose,
+                pointer: status.pointer,
+                engaged: status.clutch == ClutchState::Engaged,
+                idle: status.idle,
+                paused: status.paused,
+                outbox: &self.outbox,
+            };
+            if let Some(stream) = &mut self.stream {
+                stream.publish(&sample);
+            }
+            if let Some(osc) = &mut self.osc {
+                osc.send(&sample);
+            }
+        }
+        self.outbox.clear();
+        status
+    }
+
+    fn step(&mut self, frame: &Frame) -> Result<TrackingStatus> {
+        let now = frame.timestamp;
+        self.finish_calibration(now);
+        if self.paused {
+            return Ok(TrackingStatus { paused: true, ..self.no_hand(frame) });
+        }
+        if let Some(idle) = &mut self.idle {
+            let was_idle = idle.is_idle();
+            if !idle.admit(frame, now)? {
+                self.metrics.sample_idle_cpu(now);
+                return Ok(TrackingStatus { idle: true, ..self.no_hand(frame) });
+            }
+            if was_idle {
+                info!("👀 Motion, back to full rate");
+                self.metrics.record_wake(now);
+            }
+        }
+
+        let status = self.track_frame(frame)?;
+
+        match self.idle.as_mut().and_then(|idle| idle.observe(status.hand_present, now)) {
+            Some(IdleEvent::Sleep) => {
+                info!("💤 No hand in view, idling until motion");
+                self.metrics.record_idle(now);
+            }
+            Some(IdleEvent::Resumed { latency }) => self.metrics.record_wake_latency(latency),
+            None => {}
+        }
+        Ok(status)
+    }
+
+    /// Status for `frame` when nothing was tracked.
+    fn no_hand(&self, frame: &Frame) -> TrackingStatus {
+        TrackingStatus {
+            hand_present: false,
+            pose: HandPose::Unknown,
+            clutch: self.clutch.state(),
+            pointer: None,
+            dwell_progress: None,
+            idle: false,
+            paused: false,
+            timestamp: frame.timestamp,
+            sequence: frame.sequence,
+        }
+    }
+
+    fn track_frame(&mut self, frame: &Frame) -> Result<TrackingStatus> {
+        let Some(tracked) = self.tracker.track(frame)? else {
+            if let Some(dwell) = &mut self.dwell {
+                dwell.reset();
+            }
+            self.hand_outputs_lost();
+            self.last_frame = None;
+            if let Some(predictor) = &mut self.predictor {
+                predictor.reset();
+            }
+            return Ok(self.no_hand(frame));
+        };
+
+        let (x, y) = tracked.pointer;
+        let pose = tracked.pose;
+        // The gamepad, touchscreen and pen read landmarks from the outbox too
+        let keep_hand = self.publishing() || self.gamepad.is_some() || self.touch.is_some() || self.pen.is_some();
+        if let (true, Some(hand)) = (keep_hand, tracked.hand) {
+            self.outbox.landmarks = hand.landmarks;
+            self.outbox.handedness = hand.handedness;
+        }
+        let other_hands = tracked.other_hands;
+        // Every timer below runs on frame time, so behaviour doesn't depend on the frame rate
+        let now = tracked.timestamp;
+
+        let in_zone = self.mapper.in_zone(x, y);
+        if let Some(sweep) = &mut self.sweep {
+            sweep.observe((x, y));
+        }
+
+        if let Some(state) = self.clutch.update(pose, in_zone, now) {
+            self.on_clutch_change(state);
+        }
+
+        // Freeze while disengaged or while the clutch pose is being formed
+        if !self.clutch.is_engaged() || self.clutch.pose_active(pose) {
+            if let Some(Err(e)) = self.gamepad.as_mut().map(GamepadManager::release) {
+                println!("❌ GAMEPAD ERROR: {}", e);
+            }
+            if let Some(Err(e)) = self.touch.as_mut().map(TouchManager::lift_all) {
+                println!("❌ TOUCH ERROR: {}", e);
+            }
+            if let Some(Err(e)) = self.pen.as_mut().map(PenManager::leave) {
+                println!("❌ PEN ERROR: {}", e);
+            }
+            return Ok(TrackingStatus {
+                hand_present: true,
+                pose,
+                pointer: Some((x, y)),
+                ..self.no_hand(frame)
+            });
+        }
+
+        // Swipe: jump the mapped region to the neighbouring monitor
+        if let Some(swipe) = self.swipe.as_mut().and_then(|s| s.update(x, now)) {
+            self.emit(StreamEvent::Swipe(swipe));
+            self.switch_screen(swipe == Swipe::Right);
+        }
+
+        // 1. Smooth the raw AI coordinates
+        let dt = self.last_frame.map_or(0.0, |t| now.duration_since(t).as_secs_f32());
+        self.last_frame = Some(now);
+        let (smooth_x, smooth_y) = self.filter.apply(x, y, dt);
+
+        // 2. Lead the hand by the capture-to-injection delay of recent frames
+        let (smooth_x, smooth_y) = match (&mut self.predictor, self.metrics.latency) {
+            (Some(predictor), Some(latency)) => predictor.predict((smooth_x, smooth_y), now, latency),
+            _ => (smooth_x, smooth_y),
+        };
+
+        // 3. Active zone, transfer curve & gain onto the target screen
+        let (screen_x, screen_y) = self.mapper.map_to_screen(smooth_x, smooth_y);
+
+        // 4. Move Mouse (MouseManager turns absolute targets into deltas)
+        if let Err(e) = self.mouse.move_to(screen_x, screen_y) {
+            println!("❌ MOUSE ERROR: {}", e);
+        }
+        self.outbox.cursor = self.publishing().then_some((screen_x, screen_y));
+
+        // 4b. Gamepad: sticks from the hand's offset to its neutral point, buttons from poses and pinches
+        if let Some(gamepad) = &mut self.gamepad {
+            let input = PadInput { pointer: (smooth_x, smooth_y), click: tracked.click, pose, landmarks: &self.outbox.landmarks };
+            if let Err(e) = gamepad.update(&input) {
+                println!("❌ GAMEPAD ERROR: {}", e);
+            }
+        }
+
+        // 4c. Touch: touching fingertips become contacts on the target screen
+        if let Some(touch) = &mut self.touch {
+            let hands: Vec<_> = std::iter::once(self.outbox.landmarks.as_slice())
+                .chain(other_hands.iter().map(|hand| hand.landmarks.as_slice()))
+                .collect();
+            let contacts: Vec<_> = touch.tracker.update(&hands).into_iter()
+                .map(|contact| contact.map(|p| screen_local(&self.mapper, p)))
+                .collect();
+            if let Err(e) = touch.send(&contacts) {
+                println!("❌ TOUCH ERROR: {}", e);
+            }
+        }
+
+        // 4d. Pen: hover over the target screen, pinch to draw
+        if let Some(pen) = &mut self.pen {
+            let sample = pen.tracker.update(&self.outbox.landmarks).map(|s| s.map(|p| screen_local(&self.mapper, p)));
+            if let Err(e) = pen.send(sample) {
+                println!("❌ PEN ERROR: {}", e);
+            }
+        }
+        self.metrics.record_latency(Instant::now().saturating_duration_since(now));
+
+        // 5. Gesture: Pinch (or the tracker's own gesture) to Click; dwell
+        // clicking replaces it, so one intent never fires two actions
+        if tracked.click && self.dwell.is_none() {
+            if !self.is_clicking {
+                println!("🎯 GESTURE: CLICK!");
+                let _ = self.mouse.click_left();
+                self.is_clicking = true;
+                self.emit(StreamEvent::ClickDown);
+            }
+        } else if self.is_clicking {
+            self.is_clicking = false;
+            self.emit(StreamEvent::ClickUp);
+        }
+
+        // 6. Dwell Click: resting the cursor fires the selected action
+        let mut dwell_progress = None;
+        if let Some(dwell) = &mut self.dwell {
+            if let Some(action) = dwell.update((screen_x as f32, screen_y as f32), now) {
+                println!("⏳ DWELL: {:?}", action);
+                if self.stream.is_some() || self.osc.is_some() {
+                    self.outbox.events.push(StreamEvent::Dwell(action));
+                }
+                let res = match action {
+                    DwellAction::Left => self.mouse.click_left(),
+                    DwellAction::Right => self.mouse.click_right(),
+                    DwellAction::Double => self.mouse.double_click_left(),
+                    DwellAction::Drag if dwell.is_dragging() => self.mouse.press_left(),
+                    DwellAction::Drag => self.mouse.release_left(),
+                };
+                if let Err(e) = res {
+                    println!("❌ MOUSE ERROR: {}", e);
+                }
+            }
+            dwell_progress = Some(dwell.progress(now));
+        }
+
+        Ok(TrackingStatus {
+            hand_present: true,
+            pose,
+            pointer: Some((x, y)),
+            dwell_progress,
+            ..self.no_hand(frame)
         })
     }
 
+    /// Switches the dwell action (left → right → double → drag), releasing any drag in progress.
+    pub fn cycle_dwell_action(&mut self) -> Option<DwellAction> {
+        let dwell = self.dwell.as_mut()?;
+        let action = dwell.cycle_action();
+        if self.mouse.is_left_down()
+            && let Err(e) = self.mouse.release_left()
+        {
+            println!("❌ MOUSE ERROR: {}", e);
+        }
+        info!("Dwell action: {:?}", action);
+        Some(action)
+    }
+
+    /// Grabs the next camera frame, feeding errors and reconnect events into the metrics.
+    pub fn capture_frame(&mut self) -> Result<Frame> {
+        let frame = self.camera.capture_frame();
+        match &frame {
+            Ok(frame) => self.metrics.record_frame(frame.timestamp),
+            Err(_) if self.camera.is_streaming() => self.metrics.record_camera_error(),
+            Err(_) => {}
+        }
+
+        for event in self.camera.drain_events() {
+            match &event {
+                CameraEvent::Lost { device, reason } => {
+                    println!("🔌 Camera '{}' lost ({}), reconnecting...", device, reason);
+                    // Don't leave a drag hanging while blind
+                    if self.mouse.is_left_down() {
+                        let _ = self.mouse.release_left();
+                    }
+                    if let Some(dwell) = &mut self.dwell {
+                        dwell.end_drag();
+                    }
+                    if let Some(gamepad) = &mut self.gamepad {
+                        let _ = gamepad.release();
+                    }
+                    if let Some(touch) = &mut self.touch {
+                        let _ = touch.lift_all();
+                    }
+                    if let Some(pen) = &mut self.pen {
+                        let _ = pen.leave();
+                    }
+                    self.is_clicking = false;
+                }
+                CameraEvent::Reconnected { device, downtime, .. } => {
+                    println!("🎥 Camera '{}' back after {:.1}s", device, downtime.as_secs_f32());
+                }
+            }
+            self.metrics.record_camera_event(&event);
+
+            // The hand may have moved anywhere while we were blind
+            self.reset_motion();
+        }
+
+        frame
+    }
+
     pub fn run_loop(&mut self) -> Result<()> {
         println!("🚀 AIR-LINK TURBO MODE ACTIVE!");
-        
+        println!("⌨️  Press Enter to engage/disengage pointer control.");
+
+        let hotkey = spawn_enter_hotkey();
         let mut last_time = Instant::now();
-        let mut frame_count = 0;
 
         loop {
-            frame_count += 1;
             if last_time.elapsed().as_secs() >= 1 {
-                println!("Heartbeat - FPS: {}", frame_count);
-                frame_count = 0;
+                println!("Heartbeat - {} | Pointer: {:?}", self.metrics.summary(), self.clutch.state());
                 last_time = Instant::now();
             }
 
-            match self.camera.capture_frame() {
+            if hotkey.swap(false, Ordering::Relaxed) {
+                self.toggle_clutch();
+            }
+            self.poll_control();
+
+            match self.capture_frame() {
                 Ok(frame) => {
-                    match self.detector.detect(&frame) {
-                        Ok(Some(hand)) => {
-                            let (x, y) = hand.index_tip;
-                            let (tx, ty) = hand.thumb_tip;
-
-                            // 1. Smooth the raw AI coordinates
-                            let (smooth_x, smooth_y) = self.filter.filter(x, y);
-
-                            // 2. ACTIVE ZONE LOGIC (Crop & Zoom)
-                            // Use the central 50% of the camera view as the full screen area
-                            let min_z = 0.2; 
-                            let max_z = 0.8;
-                            let z_size = max_z - min_z;
-
-                            let nx = ((smooth_x - min_z) / z_size).clamp(0.0, 1.0);
-                            let ny = ((smooth_y - min_z) / z_size).clamp(0.0, 1.0);
-
-                            // 3. Mirror & Scale to Screen
-                            let mirrored_x = 1.0 - nx; 
-                            
-                            let screen_x = self.screen_config.x_offset + 
-                                           (mirrored_x * self.screen_config.width as f32) as i32;
-                            
-                            let screen_y = self.screen_config.y_offset + 
-                                           (ny * self.screen_config.height as f32) as i32;
-
-                            // 4. Move Mouse with Sensitivity Boost
-                            // We multiply the final coordinates to amplify the delta in MouseManager
-                            let boost = self.sensitivity * 1.5;
-                            let adj_x = (screen_x as f32 * boost) as i32;
-                            let adj_y = (screen_y as f32 * boost) as i32;
-
-                            if let Err(e) = self.mouse.move_to(adj_x, adj_y) {
-                                println!("❌ MOUSE ERROR: {}", e);
-                            }
-
-                            // 5. Gesture: Pinch to Click
-                            let dist_sq = (x - tx).powi(2) + (y - ty).powi(2);
-                            let threshold_sq = 0.003;
-
-                            if dist_sq < threshold_sq {
-                                if !self.is_clicking {
-                                    println!("🎯 GESTURE: CLICK!");
-                                    let _ = self.mouse.click_left();
-                                    self.is_clicking = true;
-                                }
-                            } else {
-                                self.is_clicking = false;
-                            }
-                        },
-                        Ok(None) => {},
-                        Err(e) => println!("⚠️ AI Error: {}", e),
+                    if let Err(e) = self.process_frame(&frame) {
+                        println!("⚠️ AI Error: {}", e);
+                    }
+                    // Idle: nothing to do until the next motion check
+                    if let Some(wait) = self.idle.as_ref().and_then(|idle| idle.next_check(Instant::now())) {
+                        std::thread::sleep(wait);
                     }
+                    self.camera.recycle(frame);
                 }
                 Err(e) => {
-                    println!("🎥 Camera Error: {}", e);
+                    // While reconnecting the camera manager reports its own progress
+                    if self.camera.is_streaming() {
+                        println!("🎥 Camera Error: {}", e);
+                    }
                     std::thread::sleep(std::time::Duration::from_millis(100));
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// `point` projected onto the target screen, relative to its top-left, for
+/// absolute devices that span that one screen.
+fn screen_local(mapper: &CoordinateMapper, (x, y): (f32, f32)) -> (i32, i32) {
+    let screen = mapper.screen();
+    let (sx, sy) = mapper.project(x, y);
+    (sx - screen.x_offset, sy - screen.y_offset)
+}
+
+/// Terminal hotkey: every Enter press on stdin raises the returned flag.
+fn spawn_enter_hotkey() -> Arc<AtomicBool> {
+    let flag = Arc::new(AtomicBool::new(false));
+    let writer = Arc::clone(&flag);
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            if line.is_err() {
+                break;
+            }
+            writer.store(true, Ordering::Relaxed);
+        }
+    });
+    flag
+}
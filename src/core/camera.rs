@@ -1,45 +1,465 @@
 use nokhwa::pixel_format::RgbFormat;
-use nokhwa::utils::{CameraIndex, RequestedFormat, RequestedFormatType, CameraFormat, Resolution, FrameFormat};
-use nokhwa::Camera;
+use nokhwa::utils::{ApiBackend, CameraIndex, RequestedFormat, RequestedFormatType, CameraFormat, Resolution, FrameFormat};
+use nokhwa::{Camera, FormatDecoder, NokhwaError, query};
+use crate::core::frame::{Frame, PixelFormat};
 use crate::Result;
-use image::DynamicImage;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Capture mode used for anything not requested explicitly; cheap on legacy CPUs.
+const DEFAULT_RESOLUTION: (u32, u32) = (640, 480);
+const DEFAULT_FPS: u32 = 30;
+
+/// Which device to open, in which mode, and what to do when it disappears.
+#[derive(Clone, Debug, Default)]
+pub struct CameraConfig {
+    pub index: u32,
+    /// Pick the device by (part of) its name instead of `index`, which moves around on docks.
+    pub name: Option<String>,
+    pub resolution: Option<(u32, u32)>,
+    pub fps: Option<u32>,
+    pub format: Option<FrameFormat>,
+    /// When the camera cannot be re-opened, use one whose name, bus or serial contains this.
+    pub fallback: Option<String>,
+}
+
+/// A capture device and every mode it advertises.
+#[derive(Clone, Debug)]
+pub struct DeviceInfo {
+    pub index: u32,
+    pub name: String,
+    pub description: String,
+    /// Bus info on V4L2, e.g. "usb-0000:00:14.0-2".
+    pub bus: String,
+    /// Sorted by format, then largest resolution and frame rate first.
+    pub modes: Vec<CameraFormat>,
+}
+
+/// Every camera with its supported modes. Devices that are busy are listed without modes.
+pub fn list_devices() -> Result<Vec<DeviceInfo>> {
+    let devices = query(ApiBackend::Auto)
+        .map_err(|e| crate::AirLinkError::CoreError(format!("Query failed: {}", e)))?;
+
+    let mut list = Vec::new();
+    for dev in devices {
+        let CameraIndex::Index(index) = *dev.index() else { continue };
+
+        let requested = RequestedFormat::new::<RgbFormat>(RequestedFormatType::None);
+        let mut modes = match Camera::new(CameraIndex::Index(index), requested)
+            .and_then(|mut camera| camera.compatible_camera_formats())
+        {
+            Ok(modes) => modes,
+            Err(e) => {
+                warn!("Cannot query modes of camera {}: {}", index, e);
+                Vec::new()
+            }
+        };
+        modes.sort_by_key(|m| (m.format(), std::cmp::Reverse((m.resolution(), m.frame_rate()))));
+        modes.dedup();
+
+        list.push(DeviceInfo {
+            index,
+            name: dev.human_name(),
+            description: dev.description().to_string(),
+            bus: dev.misc(),
+            modes,
+        });
+    }
+    Ok(list)
+}
+
+/// Best advertised mode for the request. A requested format must match exactly;
+/// then the closest resolution wins, then the closest frame rate, then MJPEG
+/// (the lightest on USB bandwidth).
+pub fn choose_mode(modes: &[CameraFormat], config: &CameraConfig) -> Option<CameraFormat> {
+    let (width, height) = config.resolution.unwrap_or(DEFAULT_RESOLUTION);
+    let fps = config.fps.unwrap_or(DEFAULT_FPS);
+
+    modes
+        .iter()
+        .filter(|m| config.format.is_none_or(|f| m.format() == f))
+        .filter(|m| RgbFormat::FORMATS.contains(&m.format()))
+        .min_by_key(|m| {
+            let dw = m.width() as i64 - width as i64;
+            let dh = m.height() as i64 - height as i64;
+            let dfps = (m.frame_rate() as i64 - fps as i64).abs();
+            (dw * dw + dh * dh, dfps, m.format() != FrameFormat::MJPEG)
+        })
+        .copied()
+}
+
+fn pixel_format(format: FrameFormat) -> PixelFormat {
+    match format {
+        FrameFormat::MJPEG => PixelFormat::Mjpeg,
+        FrameFormat::YUYV => PixelFormat::Yuyv,
+        FrameFormat::NV12 => PixelFormat::Nv12,
+        FrameFormat::GRAY => PixelFormat::Gray,
+        FrameFormat::RAWRGB => PixelFormat::Rgb,
+        FrameFormat::RAWBGR => PixelFormat::Bgr,
+    }
+}
+
+/// Device index for a name pattern: an exact (case-insensitive) name match wins,
+/// otherwise the first camera whose name, description, bus info or sysfs serial contains it.
+fn find_device(pattern: &str) -> Option<u32> {
+    let pattern = pattern.to_ascii_lowercase();
+    let devices = query(ApiBackend::Auto).ok()?;
+
+    let candidates: Vec<(u32, String, String)> = devices
+        .iter()
+        .filter_map(|dev| {
+            let CameraIndex::Index(index) = *dev.index() else { return None };
+            let serial = std::fs::read_to_string(format!("/sys/class/video4linux/video{}/device/../serial", index))
+                .unwrap_or_default();
+            let haystack = format!("{} {} {} {}", dev.human_name(), dev.description(), dev.misc(), serial.trim());
+            Some((index, dev.human_name().to_ascii_lowercase(), haystack.to_ascii_lowercase()))
+        })
+        .collect();
+
+    candidates
+        .iter()
+        .find(|(_, name, _)| *name == pattern)
+        .or_else(|| candidates.iter().find(|(_, _, haystack)| haystack.contains(&pattern)))
+        .map(|(index, _, _)| *index)
+}
+
+/// Why a frame could not be delivered.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CameraFault {
+    /// Corrupt or short frame; the stream itself is fine.
+    Transient,
+    /// The device is gone (unplugged, ENODEV, stalled); the stream has to be re-opened.
+    DeviceLost,
+}
+
+#[derive(Clone, Debug)]
+pub enum CameraEvent {
+    Lost { device: String, reason: String },
+    Reconnected { device: String, downtime: Duration, attempts: u32 },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CameraStatus {
+    Streaming,
+    /// Waiting to retry; `attempt` re-opens have failed so far.
+    Reconnecting { attempt: u32 },
+}
+
+/// Exponential retry delay, doubling from `initial` up to `max`.
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    next: Duration,
+    attempts: u32,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self { initial, max, next: initial, attempts: 0 }
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        self.attempts += 1;
+        delay
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn reset(&mut self) {
+        self.next = self.initial;
+        self.attempts = 0;
+    }
+}
+
+/// Capture errors that mean the device node itself went away.
+fn is_device_gone(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    ["no such device", "os error 19", "enodev", "disconnected", "broken pipe"]
+        .iter()
+        .any(|needle| message.contains(needle))
+}
 
 pub struct CameraManager {
-    camera: Camera,
+    config: CameraConfig,
+    camera: Option<Camera>,
+    /// Device actually streaming; differs from the config after a fallback.
+    active_index: u32,
+    device_name: String,
+    consecutive_failures: u32,
+    lost_since: Option<Instant>,
+    next_attempt: Instant,
+    backoff: Backoff,
+    events: Vec<CameraEvent>,
+    /// Frames handed out so far; keeps counting across reconnects.
+    sequence: u64,
+    /// Buffer the next frame is copied into, handed back through `recycle`.
+    spare: Vec<u8>,
 }
 
 impl CameraManager {
-    pub fn new(index: u32) -> Result<Self> {
-        let index = CameraIndex::Index(index);
-        
-        // Correct way to request 640x480 for performance on legacy CPUs
-        let format = CameraFormat::new(
-            Resolution::new(640, 480),
-            FrameFormat::MJPEG, 
-            30
-        );
-        
-        let requested = RequestedFormat::new::<RgbFormat>(RequestedFormatType::Closest(format));
+    /// A stream that keeps failing this many frames in a row (~1 s) is treated as lost.
+    const MAX_FAILURES: u32 = 30;
 
-        let camera = Camera::new(index, requested)
-            .map_err(|e| crate::AirLinkError::CoreError(format!("Camera init failed: {}", e)))?;
+    pub fn new(config: CameraConfig) -> Result<Self> {
+        let index = match &config.name {
+            Some(name) => find_device(name).ok_or_else(|| {
+                crate::AirLinkError::CliError(format!("No camera matching '{}' (see `list-cameras`)", name))
+            })?,
+            None => config.index,
+        };
 
-        Ok(Self { camera })
+        let (active_index, camera) = match Self::open(index, &config) {
+            Ok(camera) => (index, camera),
+            Err(e) => match config.fallback.as_deref().and_then(find_device) {
+                Some(fallback) => {
+                    warn!("Camera {} unavailable ({}), using fallback {}", index, e, fallback);
+                    (fallback, Self::open(fallback, &config)?)
+                }
+                None => return Err(e),
+            },
+        };
+        let device_name = camera.info().human_name();
+
+        Ok(Self {
+            config,
+            camera: Some(camera),
+            active_index,
+            device_name,
+            consecutive_failures: 0,
+            lost_since: None,
+            next_attempt: Instant::now(),
+            backoff: Backoff::new(Duration::from_millis(250), Duration::from_secs(5)),
+            events: Vec::new(),
+            sequence: 0,
+            spare: Vec::new(),
+        })
+    }
+
+    fn open(index: u32, config: &CameraConfig) -> Result<Camera> {
+        let init_err = |e: NokhwaError| crate::AirLinkError::CoreError(format!("Camera init failed: {}", e));
+
+        let requested = RequestedFormat::new::<RgbFormat>(RequestedFormatType::None);
+        let mut camera = Camera::new(CameraIndex::Index(index), requested).map_err(init_err)?;
+
+        let modes = camera.compatible_camera_formats().unwrap_or_default();
+        let request = if modes.is_empty() {
+            // Backend cannot enumerate: ask for the closest match and hope
+            let (width, height) = config.resolution.unwrap_or(DEFAULT_RESOLUTION);
+            RequestedFormatType::Closest(CameraFormat::new(
+                Resolution::new(width, height),
+                config.format.unwrap_or(FrameFormat::MJPEG),
+                config.fps.unwrap_or(DEFAULT_FPS),
+            ))
+        } else {
+            let mode = choose_mode(&modes, config).ok_or_else(|| {
+                crate::AirLinkError::CliError(format!(
+                    "Camera {} has no {} mode (see `list-cameras`)",
+                    index,
+                    config.format.map_or("decodable".to_string(), |f| f.to_string()),
+                ))
+            })?;
+            RequestedFormatType::Exact(mode)
+        };
+
+        let mode = camera.set_camera_requset(RequestedFormat::new::<RgbFormat>(request)).map_err(init_err)?;
+        info!("Camera {} '{}': {}x{} {} @ {} fps",
+            index, camera.info().human_name(), mode.width(), mode.height(), mode.format(), mode.frame_rate());
+        Ok(camera)
     }
 
     pub fn start(&mut self) -> Result<()> {
-        self.camera.open_stream()
-            .map_err(|e| crate::AirLinkError::CoreError(format!("Stream open failed: {}", e)))?;
+        if let Some(camera) = &mut self.camera {
+            camera.open_stream()
+                .map_err(|e| crate::AirLinkError::CoreError(format!("Stream open failed: {}", e)))?;
+        }
         Ok(())
     }
 
-    pub fn capture_frame(&mut self) -> Result<DynamicImage> {
-        let frame = self.camera.frame()
-            .map_err(|e| crate::AirLinkError::CoreError(format!("Capture failed: {}", e)))?;
-        
-        let decoded = frame.decode_image::<RgbFormat>()
-            .map_err(|e| crate::AirLinkError::CoreError(format!("Decode failed: {}", e)))?;
+    pub fn status(&self) -> CameraStatus {
+        match self.camera {
+            Some(_) => CameraStatus::Streaming,
+            None => CameraStatus::Reconnecting { attempt: self.backoff.attempts() },
+        }
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        self.camera.is_some()
+    }
+
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
+    /// Lost / reconnected notifications since the last call.
+    pub fn drain_events(&mut self) -> Vec<CameraEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Next frame in the camera's native format, or an error. While the device
+    /// is lost this retries the stream with backoff instead of blocking.
+    pub fn capture_frame(&mut self) -> Result<Frame> {
+        if self.camera.is_none() {
+            self.reconnect()?;
+        }
+        let Some(camera) = self.camera.as_mut() else {
+            return Err(crate::AirLinkError::CoreError("Camera unavailable".into()));
+        };
+
+        // Raw driver buffer: decoding is left to whoever needs pixels. nokhwa drops
+        // the V4L2 buffer timestamp, so the frame is stamped as it is dequeued.
+        let mode = camera.camera_format();
+        let spare = &mut self.spare;
+        let result = camera.frame_raw().map(|raw| {
+            let mut data = std::mem::take(spare);
+            data.clear();
+            data.extend_from_slice(&raw);
+            Frame {
+                timestamp: Instant::now(),
+                sequence: self.sequence,
+                data,
+                width: mode.width(),
+                height: mode.height(),
+                format: pixel_format(mode.format()),
+            }
+        });
+        match result {
+            Ok(frame) => {
+                self.consecutive_failures = 0;
+                self.sequence += 1;
+                Ok(frame)
+            }
+            Err(e) => {
+                self.consecutive_failures += 1;
+                if self.classify(&e) == CameraFault::DeviceLost {
+                    self.mark_lost(e.to_string());
+                }
+                Err(crate::AirLinkError::CoreError(format!("Capture failed: {}", e)))
+            }
+        }
+    }
+
+    /// Gives a finished frame's buffer back, so the next capture copies into
+    /// it instead of allocating a new one.
+    pub fn recycle(&mut self, frame: Frame) {
+        self.spare = frame.data;
+    }
+
+    pub fn classify(&self, error: &NokhwaError) -> CameraFault {
+        let device_present = Path::new(&format!("/dev/video{}", self.active_index)).exists();
+        match error {
+            _ if !device_present => CameraFault::DeviceLost,
+            _ if self.consecutive_failures >= Self::MAX_FAILURES => CameraFault::DeviceLost,
+            NokhwaError::ProcessFrameError { .. } => CameraFault::Transient,
+            NokhwaError::ReadFrameError(msg) | NokhwaError::GeneralError(msg) if is_device_gone(msg) => {
+                CameraFault::DeviceLost
+            }
+            _ => CameraFault::Transient,
+        }
+    }
+
+    fn mark_lost(&mut self, reason: String) {
+        warn!("Camera '{}' lost: {}", self.device_name, reason);
+        if let Some(mut camera) = self.camera.take() {
+            let _ = camera.stop_stream();
+        }
+        self.consecutive_failures = 0;
+        self.lost_since = Some(Instant::now());
+        // Retry straight away: a replugged webcam usually re-enumerates within a second
+        self.backoff.reset();
+        self.next_attempt = Instant::now();
+        self.events.push(CameraEvent::Lost { device: self.device_name.clone(), reason });
+    }
+
+    /// One re-open attempt if the backoff allows it: the configured camera first, then the fallback.
+    fn reconnect(&mut self) -> Result<()> {
+        let now = Instant::now();
+        if now < self.next_attempt {
+            return Err(crate::AirLinkError::CoreError(format!(
+                "Camera reconnecting, next attempt in {} ms",
+                (self.next_attempt - now).as_millis()
+            )));
+        }
+
+        // Indices shift on re-plug, so a camera picked by name is looked up again
+        let primary = match &self.config.name {
+            Some(name) => find_device(name),
+            None => Some(self.config.index),
+        };
+        let candidates = [primary, self.config.fallback.as_deref().and_then(find_device)];
+        for index in candidates.into_iter().flatten() {
+            let Ok(mut camera) = Self::open(index, &self.config) else { continue };
+            if camera.open_stream().is_err() {
+                continue;
+            }
+
+            let attempts = self.backoff.attempts() + 1;
+            let downtime = self.lost_since.take().map_or(Duration::ZERO, |t| t.elapsed());
+            self.device_name = camera.info().human_name();
+            self.active_index = index;
+            self.camera = Some(camera);
+            self.backoff.reset();
+
+            info!("Camera '{}' reconnected after {:.1}s", self.device_name, downtime.as_secs_f32());
+            self.events.push(CameraEvent::Reconnected { device: self.device_name.clone(), downtime, attempts });
+            return Ok(());
+        }
+
+        self.next_attempt = now + self.backoff.next_delay();
+        Err(crate::AirLinkError::CoreError(format!(
+            "Camera reconnect attempt {} failed", self.backoff.attempts()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_and_caps() {
+        let mut backoff = Backoff::new(Duration::from_millis(250), Duration::from_secs(1));
+        let delays: Vec<u128> = (0..4).map(|_| backoff.next_delay().as_millis()).collect();
+        assert_eq!(delays, [250, 500, 1000, 1000]);
+        assert_eq!(backoff.attempts(), 4);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_choose_mode() {
+        let modes = [
+            CameraFormat::new_from(1920, 1080, FrameFormat::MJPEG, 30),
+            CameraFormat::new_from(640, 480, FrameFormat::YUYV, 30),
+            CameraFormat::new_from(640, 480, FrameFormat::MJPEG, 30),
+            CameraFormat::new_from(640, 480, FrameFormat::MJPEG, 60),
+            CameraFormat::new_from(1280, 720, FrameFormat::YUYV, 10),
+        ];
+
+        // Defaults: 640x480 @ 30, MJPEG preferred
+        let config = CameraConfig::default();
+        assert_eq!(choose_mode(&modes, &config), Some(modes[2]));
 
-        Ok(DynamicImage::ImageRgb8(decoded))
+        let config = CameraConfig { fps: Some(60), ..Default::default() };
+        assert_eq!(choose_mode(&modes, &config), Some(modes[3]));
+
+        // Closest resolution in the requested format, whatever its frame rate
+        let config = CameraConfig { resolution: Some((1280, 720)), format: Some(FrameFormat::YUYV), ..Default::default() };
+        assert_eq!(choose_mode(&modes, &config), Some(modes[4]));
+
+        let config = CameraConfig { format: Some(FrameFormat::NV12), ..Default::default() };
+        assert_eq!(choose_mode(&modes, &config), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_device_loss_messages() {
+        assert!(is_device_gone("Failed to dequeue buffer: No such device (os error 19)"));
+        assert!(!is_device_gone("Timed out waiting for frame"));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::core::gesture::HandPose;
+use std::time::{Duration, Instant};
+
+/// Whether hand motion currently drives the pointer.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ClutchState {
+    Engaged,
+    Disengaged,
+}
+
+pub struct ClutchConfig {
+    /// Pose that toggles pointer control, `None` disables pose toggling.
+    pub pose: Option<HandPose>,
+    /// How long the pose must be held before it toggles.
+    pub pose_hold: Duration,
+    /// Disengage after the hand stays outside the active zone this long.
+    pub zone_dwell: Option<Duration>,
+}
+
+impl Default for ClutchConfig {
+    fn default() -> Self {
+        Self {
+            pose: Some(HandPose::Fist),
+            pose_hold: Duration::from_millis(300),
+            zone_dwell: None,
+        }
+    }
+}
+
+/// Engage/disengage state machine, like lifting a physical mouse off the desk.
+pub struct Clutch {
+    config: ClutchConfig,
+    state: ClutchState,
+    pose_since: Option<Instant>,
+    pose_latched: bool,
+    outside_since: Option<Instant>,
+    released_by_zone: bool,
+}
+
+impl Clutch {
+    pub fn new(config: ClutchConfig) -> Self {
+        Self {
+            config,
+            state: ClutchState::Engaged,
+            pose_since: None,
+            pose_latched: false,
+            outside_since: None,
+            released_by_zone: false,
+        }
+    }
+
+    pub fn state(&self) -> ClutchState {
+        self.state
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.state == ClutchState::Engaged
+    }
+
+    /// True while the clutch pose is being shown, so the caller can freeze the pointer.
+    pub fn pose_active(&self, pose: HandPose) -> bool {
+        self.config.pose == Some(pose)
+    }
+
+    /// Manual toggle (hotkey). Returns the new state.
+    pub fn toggle(&mut self) -> ClutchState {
+        self.released_by_zone = false;
+        self.state = match self.state {
+            ClutchState::Engaged => ClutchState::Disengaged,
+            ClutchState::Disengaged => ClutchState::Engaged,
+        };
+        self.state
+    }
+
+    /// Feed one tracked frame. Returns the new state when it changes.
+    pub fn update(&mut self, pose: HandPose, in_zone: bool, now: Instant) -> Option<ClutchState> {
+        let before = self.state;
+
+        // 1. Pose toggle: hold to fire once, then release before it can fire again
+        if self.pose_active(pose) {
+            let since = *self.pose_since.get_or_insert(now);
+            if !self.pose_latched && now.duration_since(since) >= self.config.pose_hold {
+                self.pose_latched = true;
+                self.toggle();
+            }
+        } else {
+            self.pose_since = None;
+            self.pose_latched = false;
+        }
+
+        // 2. Zone dwell: park outside the zone to let go, come back in to grab again
+        if let Some(dwell) = self.config.zone_dwell {
+            if in_zone {
+                self.outside_since = None;
+                if self.released_by_zone {
+                    self.released_by_zone = false;
+                    self.state = ClutchState::Engaged;
+                }
+            } else {
+                let since = *self.outside_since.get_or_insert(now);
+                if self.is_engaged() && now.duration_since(since) >= dwell {
+                    self.released_by_zone = true;
+                    self.state = ClutchState::Disengaged;
+                }
+            }
+        }
+
+        (self.state != before).then_some(self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clutch(pose: Option<HandPose>, zone_dwell: Option<Duration>) -> Clutch {
+        Clutch::new(ClutchConfig { pose, pose_hold: Duration::from_millis(300), zone_dwell })
+    }
+
+    #[test]
+    fn test_pose_hold_toggles_once_per_hold() {
+        let mut clutch = clutch(Some(HandPose::Fist), None);
+        let t0 = Instant::now();
+        let ms = |n| t0 + Duration::from_millis(n);
+
+        assert_eq!(clutch.update(HandPose::Fist, true, t0), None);
+        assert_eq!(clutch.update(HandPose::Fist, true, ms(299)), None);
+        assert_eq!(clutch.update(HandPose::Fist, true, ms(300)), Some(ClutchState::Disengaged));
+        // Latched: holding on does not toggle back
+        assert_eq!(clutch.update(HandPose::Fist, true, ms(2000)), None);
+
+        // Releasing unlatches; a short fist does nothing, a full hold toggles again
+        assert_eq!(clutch.update(HandPose::Open, true, ms(2100)), None);
+        assert_eq!(clutch.update(HandPose::Fist, true, ms(2200)), None);
+        assert_eq!(clutch.update(HandPose::Open, true, ms(2300)), None);
+        assert_eq!(clutch.update(HandPose::Fist, true, ms(2400)), None);
+        assert_eq!(clutch.update(HandPose::Fist, true, ms(2700)), Some(ClutchState::Engaged));
+
+        assert_eq!(clutch.toggle(), ClutchState::Disengaged);
+        assert!(!clutch.is_engaged());
+    }
+
+    #[test]
+    fn test_zone_dwell_releases_and_regrabs() {
+        let mut clutch = clutch(None, Some(Duration::from_millis(500)));
+        let t0 = Instant::now();
+        let ms = |n| t0 + Duration::from_millis(n);
+
+        // A fist does nothing with pose toggling off
+        assert_eq!(clutch.update(HandPose::Fist, true, ms(1000)), None);
+        assert_eq!(clutch.update(HandPose::Open, false, ms(1000)), None);
+        assert_eq!(clutch.update(HandPose::Open, false, ms(1499)), None);
+        assert_eq!(clutch.update(HandPose::Open, false, ms(1500)), Some(ClutchState::Disengaged));
+        assert_eq!(clutch.update(HandPose::Open, true, ms(1600)), Some(ClutchState::Engaged));
+
+        // A short trip outside resets the dwell
+        assert_eq!(clutch.update(HandPose::Open, false, ms(2000)), None);
+        assert_eq!(clutch.update(HandPose::Open, true, ms(2400)), None);
+        assert_eq!(clutch.update(HandPose::Open, false, ms(2600)), None);
+        assert_eq!(clutch.update(HandPose::Open, false, ms(3000)), None);
+
+        // Disengaged by hand: coming back into the zone does not re-engage
+        let mut clutch = self::clutch(None, Some(Duration::from_millis(500)));
+        clutch.toggle();
+        assert_eq!(clutch.update(HandPose::Open, false, ms(0)), None);
+        assert_eq!(clutch.update(HandPose::Open, false, ms(1000)), None);
+        assert_eq!(clutch.update(HandPose::Open, true, ms(1100)), None);
+        assert_eq!(clutch.state(), ClutchState::Disengaged);
+    }
+}
@@ -0,0 +1,302 @@
+use crate::Result;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+/// JSON-RPC 2.0 error codes.
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const SERVER_ERROR: i64 = -32000;
+
+/// How long a connection waits for the pipeline loop to answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+pub type RpcResult = std::result::Result<Value, RpcError>;
+
+/// Optional number parameter.
+pub fn number(params: &Value, key: &str) -> std::result::Result<Option<f32>, RpcError> {
+    match params.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v.as_f64().map(|f| Some(f as f32))
+            .ok_or_else(|| RpcError::invalid_params(format!("'{}' must be a number", key))),
+    }
+}
+
+/// One decoded call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    /// `None` for a notification, which is run but never answered.
+    pub id: Option<Value>,
+    pub method: String,
+    /// Always an object (empty when omitted).
+    pub params: Value,
+}
+
+/// Decodes one line; on failure returns the error response to send back,
+/// or `None` when the line was a notification.
+pub fn parse_request(line: &str) -> std::result::Result<Request, Option<Value>> {
+    let json: Value = serde_json::from_str(line)
+        .map_err(|e| Some(response(&Value::Null, Err(RpcError::new(PARSE_ERROR, e.to_string())))))?;
+    let id = json.get("id").cloned();
+    let invalid = |msg: &str| id.as_ref().map(|id| response(id, Err(RpcError::new(INVALID_REQUEST, msg))));
+
+    if json.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return Err(invalid("jsonrpc must be \"2.0\""));
+    }
+    let method = json.get("method").and_then(Value::as_str).ok_or_else(|| invalid("method must be a string"))?;
+    let params = match json.get("params") {
+        None | Some(Value::Null) => json!({}),
+        Some(p @ Value::Object(_)) => p.clone(),
+        Some(_) => return Err(invalid("params must be an object")),
+    };
+    Ok(Request { id, method: method.to_string(), params })
+}
+
+pub fn response(id: &Value, result: RpcResult) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": e.code, "message": e.message } }),
+    }
+}
+
+/// `$XDG_RUNTIME_DIR/air-link.sock`, or `/tmp/air-link-<uid>.sock`.
+pub fn default_socket_path() -> PathBuf {
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty()) {
+        return Path::new(&dir).join("air-link.sock");
+    }
+    // /proc/self belongs to the process's own uid; USER can be unset (cron, services)
+    let user = fs::metadata("/proc/self").map(|m| m.uid().to_string())
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_else(|_| "default".to_string());
+    PathBuf::from(format!("/tmp/air-link-{}.sock", user))
+}
+
+/// Control socket of a running instance. Connections are served on their own
+/// threads; calls are queued for the pipeline loop, which answers them in
+/// [`ControlServer::poll`] between frames.
+pub struct ControlServer {
+    path: PathBuf,
+    calls: Receiver<(Request, Sender<Value>)>,
+}
+
+impl ControlServer {
+    pub fn bind(path: &Path) -> Result<Self> {
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                return Err(crate::AirLinkError::CoreError(format!(
+                    "Another instance is listening on {}", path.display()
+                )));
+            }
+            // Left behind by a crash
+            fs::remove_file(path)?;
+        }
+        let listener = bind_private(path)?;
+
+        let (tx, calls) = mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let tx = tx.clone();
+                std::thread::spawn(move || serve(stream, tx));
+            }
+        });
+        Ok(Self { path: path.to_path_buf(), calls })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Answers every queued call with `handler(method, params)`.
+    pub fn poll(&self, mut handler: impl FnMut(&str, &Value) -> RpcResult) {
+        while let Ok((request, reply)) = self.calls.try_recv() {
+            let result = handler(&request.method, &request.params);
+            if let Some(id) = &request.id {
+                let _ = reply.send(response(id, result));
+            }
+        }
+    }
+}
+
+/// Binds in a fresh 0700 directory next to `path`, restricts the socket to
+/// 0600 and only then moves it into place, so it is never reachable with the
+/// umask's looser mode.
+fn bind_private(path: &Path) -> Result<UnixListener> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let staging = path.with_file_name(format!(".{}.{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&staging);
+    fs::DirBuilder::new().mode(0o700).create(&staging)?;
+    let bound = (|| {
+        let temp = staging.join("sock");
+        let listener = UnixListener::bind(&temp)?;
+        fs::set_permissions(&temp, fs::Permissions::from_mode(0o600))?;
+        fs::rename(&temp, path)?;
+        Ok(listener)
+    })();
+    let _ = fs::remove_dir_all(&staging);
+    bound
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn serve(stream: UnixStream, calls: Sender<(Request, Sender<Value>)>) {
+    let Ok(mut writer) = stream.try_clone() else { return };
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match parse_request(&line) {
+            Ok(request) => {
+                let id = request.id.clone();
+                let (tx, rx) = mpsc::channel();
+                if calls.send((request, tx)).is_err() {
+                    break;
+                }
+                let Some(id) = id else { continue };
+                rx.recv_timeout(REPLY_TIMEOUT).unwrap_or_else(|_| {
+                    response(&id, Err(RpcError::new(SERVER_ERROR, "Pipeline did not answer")))
+                })
+            }
+            Err(Some(error)) => error,
+            Err(None) => continue,
+        };
+        if writeln!(writer, "{}", reply).is_err() {
+            break;
+        }
+    }
+}
+
+/// Client side: one call, returning the result or the server's error message.
+pub fn call(path: &Path, method: &str, params: Value) -> Result<Value> {
+    let stream = UnixStream::connect(path).map_err(|e| {
+        crate::AirLinkError::CliError(format!("Cannot reach air-link at {} ({}); is it running?", path.display(), e))
+    })?;
+    stream.set_read_timeout(Some(REPLY_TIMEOUT * 2))?;
+
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    writeln!(&stream, "{}", request)?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let reply: Value = serde_json::from_str(&line)?;
+    match reply.get("error") {
+        Some(error) => Err(crate::AirLinkError::CliError(format!(
+            "{} ({})",
+            error.get("message").and_then(Value::as_str).unwrap_or("unknown error"),
+            error.get("code").and_then(Value::as_i64).unwrap_or(0)
+        ))),
+        None => Ok(reply.get("result").cloned().unwrap_or(Value::Null)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_validation() {
+        let ok = parse_request(r#"{"jsonrpc":"2.0","id":7,"method":"status"}"#).unwrap();
+        assert_eq!((ok.id, ok.method.as_str(), ok.params), (Some(json!(7)), "status", json!({})));
+
+        let code = |line: &str| parse_request(line).unwrap_err().unwrap()["error"]["code"].as_i64().unwrap();
+        assert_eq!(code("{nope"), PARSE_ERROR);
+        assert_eq!(code(r#"{"id":1,"method":"status"}"#), INVALID_REQUEST);
+        assert_eq!(code(r#"{"jsonrpc":"2.0","id":1,"method":"pause","params":[1]}"#), INVALID_REQUEST);
+        // Without an id nothing is sent back, not even an error
+        assert_eq!(parse_request(r#"{"jsonrpc":"2.0","method":"pause","params":[1]}"#), Err(None));
+    }
+
+    #[test]
+    fn test_round_trip_over_socket() {
+        let path = std::env::temp_dir().join(format!("air-link-test-{}.sock", std::process::id()));
+        let server = ControlServer::bind(&path).unwrap();
+        assert!(ControlServer::bind(&path).is_err(), "second instance on the same socket");
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        // Stand-in for the pipeline loop
+        let client = std::thread::spawn({
+            let path = path.clone();
+            move || {
+                let gain = call(&path, "set_sensitivity", json!({ "gain": 1.5 })).unwrap();
+                let unknown = call(&path, "explode", json!({}));
+                (gain, unknown)
+            }
+        });
+        while !client.is_finished() {
+            server.poll(|method, params| match method {
+                "set_sensitivity" => Ok(json!({ "gain": params["gain"] })),
+                _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+            });
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let (gain, unknown) = client.join().unwrap();
+        assert_eq!(gain, json!({ "gain": 1.5 }));
+        assert!(unknown.unwrap_err().to_string().contains("Unknown method 'explode'"));
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_notifications_get_no_reply() {
+        let path = std::env::temp_dir().join(format!("air-link-notify-{}.sock", std::process::id()));
+        let server = ControlServer::bind(&path).unwrap();
+
+        let client = std::thread::spawn({
+            let path = path.clone();
+            move || {
+                let stream = UnixStream::connect(&path).unwrap();
+                stream.set_read_timeout(Some(REPLY_TIMEOUT * 2)).unwrap();
+                writeln!(&stream, r#"{{"jsonrpc":"2.0","method":"pause"}}"#).unwrap();
+                writeln!(&stream, r#"{{"jsonrpc":"2.0","method":"explode"}}"#).unwrap();
+                writeln!(&stream, r#"{{"jsonrpc":"2.0","id":2,"method":"status"}}"#).unwrap();
+                let mut line = String::new();
+                BufReader::new(&stream).read_line(&mut line).unwrap();
+                line
+            }
+        });
+        let mut handled = Vec::new();
+        while !client.is_finished() {
+            server.poll(|method, _| {
+                handled.push(method.to_string());
+                match method {
+                    "explode" => Err(RpcError::new(METHOD_NOT_FOUND, "Unknown method")),
+                    _ => Ok(json!(method)),
+                }
+            });
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        // The first line back answers the call, not either notification
+        let reply: Value = serde_json::from_str(&client.join().unwrap()).unwrap();
+        assert_eq!((&reply["id"], &reply["result"]), (&json!(2), &json!("status")));
+        assert_eq!(handled, ["pause", "explode", "status"]);
+    }
+}
@@ -0,0 +1,391 @@
+use crate::core::camera::CameraConfig;
+use crate::core::frame::Frame;
+use crate::core::vision::{Detection, HandDetector, HandResult, Handedness};
+use crate::core::CameraManager;
+use crate::Result;
+use image::DynamicImage;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// COCO keypoint names, in landmark order.
+pub const KEYPOINTS: [&str; 21] = [
+    "wrist",
+    "thumb_cmc", "thumb_mcp", "thumb_ip", "thumb_tip",
+    "index_mcp", "index_pip", "index_dip", "index_tip",
+    "middle_mcp", "middle_pip", "middle_dip", "middle_tip",
+    "ring_mcp", "ring_pip", "ring_dip", "ring_tip",
+    "pinky_mcp", "pinky_pip", "pinky_dip", "pinky_tip",
+];
+
+/// Bones as COCO wants them: 1-based keypoint pairs.
+pub const SKELETON: [[usize; 2]; 20] = [
+    [1, 2], [2, 3], [3, 4], [4, 5],
+    [1, 6], [6, 7], [7, 8], [8, 9],
+    [1, 10], [10, 11], [11, 12], [12, 13],
+    [1, 14], [14, 15], [15, 16], [16, 17],
+    [1, 18], [18, 19], [19, 20], [20, 21],
+];
+
+/// The single COCO category.
+pub const HAND_CATEGORY: u64 = 1;
+pub const ANNOTATIONS_FILE: &str = "annotations.json";
+pub const IMAGES_DIR: &str = "images";
+
+/// Describes the session in the COCO `info` block.
+#[derive(Clone, Debug, Default)]
+pub struct SessionInfo {
+    pub name: String,
+    pub notes: Option<String>,
+    pub camera: String,
+    pub model: String,
+    /// How frames were picked, e.g. "key" or "interval 500 ms".
+    pub trigger: String,
+}
+
+/// `2024-05-01T12:00:00Z`, without pulling in a date crate.
+pub fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, rest / 3600, rest / 60 % 60, rest % 60)
+}
+
+/// COCO `keypoints` (pixel x, y, visibility) and `bbox` of a detected hand.
+/// Landmarks outside the frame are unlabeled (`0, 0, 0`), as COCO expects.
+pub fn coco_keypoints(landmarks: &[(f32, f32, f32)], width: u32, height: u32) -> (Vec<f32>, Option<[f32; 4]>) {
+    let (w, h) = (width as f32, height as f32);
+    let mut keypoints = Vec::with_capacity(KEYPOINTS.len() * 3);
+    let (mut min, mut max) = ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN));
+    for i in 0..KEYPOINTS.len() {
+        match landmarks.get(i).filter(|p| (0.0..=1.0).contains(&p.0) && (0.0..=1.0).contains(&p.1)) {
+            Some(&(x, y, _)) => {
+                let (px, py) = (x * w, y * h);
+                keypoints.extend([px, py, 2.0]);
+                min = (min.0.min(px), min.1.min(py));
+                max = (max.0.max(px), max.1.max(py));
+            }
+            None => keypoints.extend([0.0; 3]),
+        }
+    }
+    let bbox = (min.0 <= max.0).then_some([min.0, min.1, max.0 - min.0, max.1 - min.1]);
+    (keypoints, bbox)
+}
+
+/// One captured frame: its COCO image entry and the pseudo-label, if a hand was found.
+struct Sample {
+    image: Value,
+    annotation: Option<Value>,
+}
+
+/// Saves frames and detector landmarks as a COCO-keypoint dataset:
+/// `images/000001.png ...` plus `annotations.json`. Images are written as they
+/// come; the JSON every [`DatasetWriter::SAVE_EVERY`] changes, on
+/// [`DatasetWriter::flush`] and on drop, so a crash loses at most the labels
+/// of the last few frames.
+pub struct DatasetWriter {
+    dir: PathBuf,
+    info: SessionInfo,
+    created: SystemTime,
+    samples: Vec<Sample>,
+    /// Changes since the JSON was last written.
+    unsaved: usize,
+}
+
+impl DatasetWriter {
+    pub const SAVE_EVERY: usize = 20;
+
+    /// Refuses a folder that already holds a dataset rather than overwrite it.
+    pub fn create(dir: &Path, info: SessionInfo) -> Result<Self> {
+        if dir.join(ANNOTATIONS_FILE).exists() {
+            return Err(crate::AirLinkError::CliError(format!(
+                "{} already holds a dataset; pick a new folder", dir.display()
+            )));
+        }
+        fs::create_dir_all(dir.join(IMAGES_DIR))?;
+        let writer = Self { dir: dir.to_path_buf(), info, created: SystemTime::now(), samples: Vec::new(), unsaved: 0 };
+        writer.save()?;
+        Ok(writer)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Frames flagged as bad detections.
+    pub fn flagged(&self) -> usize {
+        self.samples.iter().filter(|s| s.image["bad_detection"] == true).count()
+    }
+
+    /// Saves `image` with its detection and returns the new image id.
+    pub fn add(&mut self, image: &DynamicImage, time: f64, presence: Option<f32>, hand: Option<&HandResult>) -> Result<u64> {
+        let id = self.samples.len() as u64 + 1;
+        let file_name = format!("{:06}.png", id);
+        image.save(self.dir.join(IMAGES_DIR).join(&file_name))
+            .map_err(|e| crate::AirLinkError::CoreError(format!("Cannot save {}: {}", file_name, e)))?;
+
+        let (width, height) = (image.width(), image.height());
+        let annotation = hand.map(|hand| {
+            let (keypoints, bbox) = coco_keypoints(&hand.landmarks, width, height);
+            let bbox = bbox.unwrap_or_default();
+            json!({
+                "id": id,
+                "image_id": id,
+                "category_id": HAND_CATEGORY,
+                "keypoints": keypoints,
+                "num_keypoints": keypoints.chunks(3).filter(|k| k[2] > 0.0).count(),
+                "bbox": bbox,
+                "area": bbox[2] * bbox[3],
+                "iscrowd": 0,
+                "score": presence,
+                "handedness": hand.handedness.map(|h| match h {
+                    Handedness::Left => "left",
+                    Handedness::Right => "right",
+                }),
+                "keypoints_z": hand.landmarks.iter().map(|p| p.2).collect::<Vec<_>>(),
+            })
+        });
+        let image = json!({
+            "id": id,
+            "file_name": file_name,
+            "width": width,
+            "height": height,
+            "time": time,
+            "presence": presence,
+            "bad_detection": false,
+        });
+        self.samples.push(Sample { image, annotation });
+        self.changed()?;
+        Ok(id)
+    }
+
+    /// Flips the bad-detection flag of the latest frame; its id and new flag.
+    pub fn toggle_last(&mut self) -> Result<Option<(u64, bool)>> {
+        let Some(last) = self.samples.last_mut() else { return Ok(None) };
+        let bad = last.image["bad_detection"] != true;
+        last.image["bad_detection"] = Value::Bool(bad);
+        let id = last.image["id"].as_u64().unwrap_or_default();
+        self.changed()?;
+        Ok(Some((id, bad)))
+    }
+
+    pub fn to_coco(&self) -> Value {
+        let info = &self.info;
+        json!({
+            "info": {
+                "description": info.name,
+                "version": "1.0",
+                "contributor": "air-link capture-dataset",
+                "date_created": utc_timestamp(self.created),
+                "air_link": {
+                    "camera": info.camera,
+                    "model": info.model,
+                    "trigger": info.trigger,
+                    "notes": info.notes,
+                    "frames": self.len(),
+                    "hands": self.samples.iter().filter(|s| s.annotation.is_some()).count(),
+                    "flagged": self.flagged(),
+                    "pseudo_labels": true,
+                },
+            },
+            "licenses": [],
+            "categories": [{
+                "id": HAND_CATEGORY,
+                "name": "hand",
+                "supercategory": "person",
+                "keypoints": KEYPOINTS,
+                "skeleton": SKELETON,
+            }],
+            "images": self.samples.iter().map(|s| &s.image).collect::<Vec<_>>(),
+            "annotations": self.samples.iter().filter_map(|s| s.annotation.as_ref()).collect::<Vec<_>>(),
+        })
+    }
+
+    fn changed(&mut self) -> Result<()> {
+        self.unsaved += 1;
+        if self.unsaved >= Self::SAVE_EVERY {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the JSON if anything changed since it was last written.
+    pub fn flush(&mut self) -> Result<()> {
+        if self.unsaved > 0 {
+            self.save()?;
+            self.unsaved = 0;
+        }
+        Ok(())
+    }
+
+    /// Writes through a temporary file, so the JSON is never half-written.
+    fn save(&self) -> Result<()> {
+        let path = self.dir.join(ANNOTATIONS_FILE);
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_vec_pretty(&self.to_coco())?)?;
+        fs::rename(&temp, &path)?;
+        Ok(())
+    }
+}
+
+impl Drop for DatasetWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("Could not save {}: {}", self.dir.join(ANNOTATIONS_FILE).display(), e);
+        }
+    }
+}
+
+/// The latest frame of a [`DatasetSession`], for the preview.
+pub struct Preview {
+    pub frame: Frame,
+    pub detection: Detection,
+    /// Id of the saved image, when this frame was captured.
+    pub saved: Option<u64>,
+}
+
+/// Camera, detector and writer of `capture-dataset`, shared by the window
+/// and the headless mode.
+pub struct DatasetSession {
+    camera: CameraManager,
+    detector: HandDetector,
+    pub writer: DatasetWriter,
+    /// Captures on its own this often, unless paused.
+    interval: Option<Duration>,
+    pub auto: bool,
+    start: Instant,
+    last_capture: Option<Instant>,
+}
+
+impl DatasetSession {
+    pub fn new(camera: CameraConfig, hand_model: &str, dir: &Path, mut info: SessionInfo, interval: Option<Duration>) -> Result<Self> {
+        let mut camera = CameraManager::new(camera)?;
+        camera.start()?;
+        let detector = HandDetector::new(hand_model)?;
+        info.camera = camera.device_name().to_string();
+        info.model = hand_model.to_string();
+        info.trigger = match interval {
+            Some(interval) => format!("interval {} ms", interval.as_millis()),
+            None => "key".to_string(),
+        };
+        let writer = DatasetWriter::create(dir, info)?;
+        info!("🗂️  Capturing a dataset into {}", dir.display());
+        Ok(Self { camera, detector, writer, interval, auto: interval.is_some(), start: Instant::now(), last_capture: None })
+    }
+
+    /// Gives a shown preview frame back to the camera for the next capture.
+    pub fn recycle(&mut self, frame: Frame) {
+        self.camera.recycle(frame);
+    }
+
+    pub fn interval(&self) -> Option<Duration> {
+        self.interval
+    }
+
+    /// Reads and detects one frame, saving it when `capture` is set or the
+    /// interval is due. `None` while the camera is reconnecting.
+    pub fn step(&mut self, capture: bool) -> Result<Option<Preview>> {
+        let Ok(frame) = self.camera.capture_frame() else { return Ok(None) };
+        let detection = self.detector.infer(&frame)?;
+
+        let due = self.auto && self.interval.is_some_and(|interval| {
+            self.last_capture.is_none_or(|last| frame.timestamp.duration_since(last) >= interval)
+        });
+        let mut saved = None;
+        if capture || due {
+            let time = frame.timestamp.saturating_duration_since(self.start).as_secs_f64();
+            let image = frame.to_image()?;
+            saved = Some(self.writer.add(&image, time, detection.presence, detection.hand.as_ref())?);
+            self.last_capture = Some(frame.timestamp);
+        }
+        Ok(Some(Preview { frame, detection, saved }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::{hand_result, TempDir};
+
+    fn hand() -> HandResult {
+        let mut landmarks: Vec<_> = (0..21).map(|i| (0.125 + i as f32 / 64.0, 0.5, 0.0)).collect();
+        // The pinky tip just left the frame
+        landmarks[20] = (1.2, 0.5, 0.0);
+        hand_result(landmarks, Some(Handedness::Right))
+    }
+
+    #[test]
+    fn test_utc_timestamp() {
+        assert_eq!(utc_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(utc_timestamp(UNIX_EPOCH + Duration::from_secs(951_827_696)), "2000-02-29T12:34:56Z");
+    }
+
+    #[test]
+    fn test_coco_dataset() {
+        let dir = TempDir::new("dataset");
+        let info = SessionInfo { name: "desk".into(), trigger: "key".into(), ..Default::default() };
+        let mut writer = DatasetWriter::create(&dir, info.clone()).unwrap();
+        let image = DynamicImage::new_rgb8(128, 64);
+        assert_eq!(writer.add(&image, 0.0, Some(0.9), Some(&hand())).unwrap(), 1);
+        assert_eq!(writer.add(&image, 0.5, Some(0.1), None).unwrap(), 2);
+        assert_eq!(writer.toggle_last().unwrap(), Some((2, true)));
+        assert!(DatasetWriter::create(&dir, info).is_err());
+
+        // Buffered until flushed
+        let read = || -> Value { serde_json::from_slice(&fs::read(dir.join(ANNOTATIONS_FILE)).unwrap()).unwrap() };
+        assert_eq!(read()["images"], json!([]));
+        writer.flush().unwrap();
+        let coco = read();
+        assert_eq!(coco["images"].as_array().unwrap().len(), 2);
+        assert_eq!(coco["images"][1]["bad_detection"], true);
+        assert_eq!(coco["info"]["air_link"]["flagged"], 1);
+        assert!(dir.join(IMAGES_DIR).join("000002.png").exists());
+
+        // The negative frame has no annotation
+        let annotations = coco["annotations"].as_array().unwrap();
+        assert_eq!(annotations.len(), 1);
+        let hand = &annotations[0];
+        assert_eq!((hand["image_id"].clone(), hand["num_keypoints"].clone()), (json!(1), json!(20)));
+        let keypoints = hand["keypoints"].as_array().unwrap();
+        assert_eq!(keypoints[0..3], [json!(16.0), json!(32.0), json!(2.0)]);
+        assert_eq!(keypoints[60..63], [json!(0.0), json!(0.0), json!(0.0)]);
+        assert_eq!(hand["bbox"], json!([16.0, 32.0, 38.0, 0.0]));
+    }
+
+    #[test]
+    fn test_json_is_saved_periodically_and_on_drop() {
+        let dir = TempDir::new("dataset-save");
+        let mut writer = DatasetWriter::create(&dir, SessionInfo::default()).unwrap();
+        let image = DynamicImage::new_rgb8(8, 8);
+        let frames = || -> usize {
+            let coco: Value = serde_json::from_slice(&fs::read(dir.join(ANNOTATIONS_FILE)).unwrap()).unwrap();
+            coco["images"].as_array().unwrap().len()
+        };
+
+        for i in 0..DatasetWriter::SAVE_EVERY + 1 {
+            writer.add(&image, i as f64, None, None).unwrap();
+        }
+        assert_eq!(frames(), DatasetWriter::SAVE_EVERY);
+        drop(writer);
+        assert_eq!(frames(), DatasetWriter::SAVE_EVERY + 1);
+    }
+}
@@ -0,0 +1,322 @@
+use crate::core::mapping::ScreenConfig;
+use crate::Result;
+use serde_json::Value;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// One output of the desktop layout.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Monitor {
+    pub name: String,
+    /// Current mode in physical pixels, already rotated by the output transform.
+    pub width: i32,
+    pub height: i32,
+    /// Position in the compositor's logical layout.
+    pub x: i32,
+    pub y: i32,
+    pub scale: f32,
+    pub focused: bool,
+}
+
+impl Monitor {
+    /// Size in logical (layout) pixels, which is what pointer motion is measured in.
+    pub fn logical_size(&self) -> (i32, i32) {
+        let scale = if self.scale > 0.0 { self.scale } else { 1.0 };
+        (
+            (self.width as f32 / scale).round() as i32,
+            (self.height as f32 / scale).round() as i32,
+        )
+    }
+
+    pub fn screen_config(&self) -> ScreenConfig {
+        let (width, height) = self.logical_size();
+        ScreenConfig {
+            width,
+            height,
+            x_offset: self.x,
+            y_offset: self.y,
+        }
+    }
+}
+
+/// The active output from the kernel's DRM atomic state (`/sys/kernel/debug/dri/*/state`).
+///
+/// DRM knows each CRTC's current mode but not the compositor layout or scale,
+/// so this only succeeds for a single active output, placed at the origin at
+/// scale 1. Multi-monitor setups need a JSON dump instead.
+pub fn discover_drm() -> Result<Vec<Monitor>> {
+    discover_drm_in(Path::new("/sys/kernel/debug/dri"))
+}
+
+fn discover_drm_in(root: &Path) -> Result<Vec<Monitor>> {
+    let mut states: Vec<_> = fs::read_dir(root)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join("state"))
+        .filter(|path| path.exists())
+        .collect();
+    states.sort();
+
+    let mut monitors = Vec::new();
+    for path in states {
+        let state = fs::read_to_string(&path)?;
+        monitors.extend(parse_drm_state(&state));
+    }
+
+    if monitors.len() > 1 {
+        let names: Vec<&str> = monitors.iter().map(|m| m.name.as_str()).collect();
+        return Err(crate::AirLinkError::CoreError(format!(
+            "DRM reports {} active outputs ({}) but not how they are arranged; pass a geometry dump",
+            monitors.len(), names.join(", ")
+        )));
+    }
+    Ok(monitors)
+}
+
+/// Connectors bound to an active CRTC, sized by that CRTC's current mode.
+fn parse_drm_state(state: &str) -> Vec<Monitor> {
+    // crtc name -> active mode, and connector name -> crtc name
+    let mut modes = Vec::new();
+    let mut connectors = Vec::new();
+
+    let mut section = "";
+    let mut name = "";
+    let mut active = false;
+    for line in state.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            (section, name) = line.split_once("]: ")
+                .map_or(("", ""), |(kind, name)| (kind.split('[').next().unwrap_or(""), name.trim()));
+            active = false;
+            continue;
+        }
+
+        let line = line.trim();
+        match section {
+            "crtc" if line == "active=1" => active = true,
+            "crtc" if active => {
+                if let Some(mode) = line.strip_prefix("mode: ").and_then(parse_mode) {
+                    modes.push((name, mode));
+                }
+            }
+            "connector" => {
+                if let Some(crtc) = line.strip_prefix("crtc=").filter(|c| *c != "(null)") {
+                    connectors.push((name, crtc));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    connectors
+        .into_iter()
+        .filter_map(|(connector, crtc)| {
+            let &(_, (width, height)) = modes.iter().find(|(name, _)| *name == crtc)?;
+            Some(Monitor {
+                name: connector.to_string(),
+                width,
+                height,
+                x: 0,
+                y: 0,
+                scale: 1.0,
+                focused: true,
+            })
+        })
+        .collect()
+}
+
+/// `"1920x1080": 60 148500 1920 2008 2052 2200 1080 ...` -> (1920, 1080).
+///
+/// The fields after the name are refresh, clock, then the horizontal and
+/// vertical timings, each starting with the visible size.
+fn parse_mode(mode: &str) -> Option<(i32, i32)> {
+    let (_, timings) = mode.rsplit_once("\":")?;
+    let fields: Vec<i32> = timings.split_whitespace().take(7).map(str::parse).collect::<std::result::Result<_, _>>().ok()?;
+    match fields[..] {
+        [_, _, width, _, _, _, height] => Some((width, height)),
+        _ => None,
+    }
+}
+
+/// Reads a geometry dump from a file, or from stdin when `source` is "-".
+pub fn load_geometry(source: &str) -> Result<Vec<Monitor>> {
+    let text = if source == "-" {
+        let mut buf = String::new();
+        std::io::stdin().read_to_string(&mut buf)?;
+        buf
+    } else {
+        fs::read_to_string(source)?
+    };
+    parse_geometry_json(&text)
+}
+
+/// Parses `hyprctl -j monitors` or `wlr-randr --json` output.
+pub fn parse_geometry_json(text: &str) -> Result<Vec<Monitor>> {
+    let json: Value = serde_json::from_str(text)?;
+    let entries = json.as_array().ok_or_else(|| {
+        crate::AirLinkError::CoreError("Geometry dump must be a JSON array of monitors".into())
+    })?;
+
+    let mut monitors = Vec::new();
+    for entry in entries {
+        let monitor = if entry.get("modes").is_some() {
+            parse_wlr_randr(entry)
+        } else {
+            parse_hyprctl(entry)
+        };
+        match monitor {
+            Some(m) => monitors.push(m),
+            None => tracing::warn!("Skipping unrecognised monitor entry: {}", entry),
+        }
+    }
+
+    Ok(monitors)
+}
+
+fn int(v: &Value, key: &str) -> Option<i32> {
+    v.get(key)?.as_i64().map(|n| n as i32)
+}
+
+/// Odd Wayland transforms (90°/270°, flipped or not) swap width and height.
+fn rotate(width: i32, height: i32, quarter_turn: bool) -> (i32, i32) {
+    if quarter_turn { (height, width) } else { (width, height) }
+}
+
+fn parse_hyprctl(v: &Value) -> Option<Monitor> {
+    if v.get("disabled").and_then(Value::as_bool) == Some(true) {
+        return None;
+    }
+    let transform = int(v, "transform").unwrap_or(0);
+    let (width, height) = rotate(int(v, "width")?, int(v, "height")?, transform % 2 == 1);
+
+    Some(Monitor {
+        name: v.get("name")?.as_str()?.to_string(),
+        width,
+        height,
+        x: int(v, "x")?,
+        y: int(v, "y")?,
+        scale: v.get("scale").and_then(Value::as_f64).unwrap_or(1.0) as f32,
+        focused: v.get("focused").and_then(Value::as_bool).unwrap_or(false),
+    })
+}
+
+fn parse_wlr_randr(v: &Value) -> Option<Monitor> {
+    if v.get("enabled").and_then(Value::as_bool) == Some(false) {
+        return None;
+    }
+    let modes = v.get("modes")?.as_array()?;
+    let mode = modes
+        .iter()
+        .find(|m| m.get("current").and_then(Value::as_bool) == Some(true))
+        .or_else(|| modes.first())?;
+
+    let transform = v.get("transform").and_then(Value::as_str).unwrap_or("normal");
+    let quarter_turn = transform.ends_with("90") || transform.ends_with("270");
+    let (width, height) = rotate(int(mode, "width")?, int(mode, "height")?, quarter_turn);
+    let position = v.get("position")?;
+
+    Some(Monitor {
+        name: v.get("name")?.as_str()?.to_string(),
+        width,
+        height,
+        x: int(position, "x")?,
+        y: int(position, "y")?,
+        scale: v.get("scale").and_then(Value::as_f64).unwrap_or(1.0) as f32,
+        focused: false,
+    })
+}
+
+/// Picks `name` if given, otherwise the focused monitor, the one at the origin, or the first.
+pub fn select<'a>(monitors: &'a [Monitor], name: Option<&str>) -> Result<&'a Monitor> {
+    if let Some(name) = name {
+        return monitors.iter().find(|m| m.name == name).ok_or_else(|| {
+            let known: Vec<&str> = monitors.iter().map(|m| m.name.as_str()).collect();
+            crate::AirLinkError::CliError(format!("No screen named '{}' (found: {})", name, known.join(", ")))
+        });
+    }
+
+    monitors
+        .iter()
+        .find(|m| m.focused)
+        .or_else(|| monitors.iter().find(|m| m.x == 0 && m.y == 0))
+        .or_else(|| monitors.first())
+        .ok_or_else(|| crate::AirLinkError::CoreError("No connected screens found".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hyprctl_dump() {
+        let dump = r#"[
+            {"id": 0, "name": "eDP-1", "width": 2880, "height": 1800, "x": 0, "y": 0,
+             "scale": 2.0, "transform": 0, "focused": false},
+            {"id": 1, "name": "DP-2", "width": 1920, "height": 1080, "x": 1440, "y": 0,
+             "scale": 1.0, "transform": 1, "focused": true}
+        ]"#;
+        let monitors = parse_geometry_json(dump).unwrap();
+
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(monitors[0].logical_size(), (1440, 900));
+        // Rotated output: portrait geometry
+        assert_eq!((monitors[1].width, monitors[1].height), (1080, 1920));
+        assert_eq!(select(&monitors, None).unwrap().name, "DP-2");
+        assert_eq!(
+            select(&monitors, Some("DP-2")).unwrap().screen_config(),
+            ScreenConfig { width: 1080, height: 1920, x_offset: 1440, y_offset: 0 }
+        );
+        assert!(select(&monitors, Some("HDMI-A-9")).is_err());
+    }
+
+    #[test]
+    fn test_drm_state_uses_active_mode() {
+        // The panel prefers 2880x1800 but is running at 1920x1200; the
+        // connected HDMI output has no CRTC
+        let state = r#"plane[31]: plane-0
+	crtc=crtc-0
+	crtc-pos=1920x1200+0+0
+crtc[51]: crtc-0
+	enable=1
+	active=1
+	mode: "1920x1200": 60 154000 1920 1968 2000 2080 1200 1203 1209 1235 0x48 0x9
+crtc[72]: crtc-1
+	enable=0
+	active=0
+	mode: "": 0 0 0 0 0 0 0 0 0 0 0x0 0x0
+connector[95]: eDP-1
+	crtc=crtc-0
+connector[103]: HDMI-A-1
+	crtc=(null)
+"#;
+        let dir = crate::core::testing::TempDir::new("drm");
+        fs::create_dir_all(dir.join("0")).unwrap();
+        fs::write(dir.join("0/state"), state).unwrap();
+        fs::create_dir_all(dir.join("1")).unwrap();
+
+        let monitors = discover_drm_in(&dir).unwrap();
+        assert_eq!(monitors.len(), 1);
+        assert_eq!(monitors[0].name, "eDP-1");
+        assert_eq!((monitors[0].width, monitors[0].height, monitors[0].x, monitors[0].y), (1920, 1200, 0, 0));
+
+        // A second active output has no known position, so discovery gives up
+        fs::write(dir.join("1/state"), state.replace("eDP-1", "DP-1")).unwrap();
+        assert!(discover_drm_in(&dir).is_err());
+    }
+
+    #[test]
+    fn test_parse_wlr_randr_dump() {
+        let dump = r#"[
+            {"name": "HDMI-A-1", "enabled": true, "position": {"x": 1920, "y": 0},
+             "transform": "normal", "scale": 1.25,
+             "modes": [{"width": 3840, "height": 2160, "refresh": 60.0, "current": false},
+                       {"width": 2560, "height": 1440, "refresh": 60.0, "current": true}]},
+            {"name": "DP-1", "enabled": false, "position": {"x": 0, "y": 0},
+             "modes": [{"width": 1920, "height": 1080, "current": true}]}
+        ]"#;
+        let monitors = parse_geometry_json(dump).unwrap();
+
+        assert_eq!(monitors.len(), 1);
+        assert_eq!(monitors[0].logical_size(), (2048, 1152));
+        assert_eq!(monitors[0].x, 1920);
+    }
+}
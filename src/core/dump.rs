@@ -0,0 +1,153 @@
+use crate::core::gesture;
+use crate::core::stream;
+use crate::core::vision::{HandResult, Handedness};
+use crate::Result;
+use serde_json::{json, Value};
+use std::io::Write;
+
+/// Landmarks per record; CSV always has this many column triples.
+pub const JOINTS: usize = 21;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// One JSON object per line.
+    Jsonl,
+    /// Header row, then one row per frame with `x0,y0,z0 ... x20,y20,z20`.
+    Csv,
+}
+
+/// What the detector made of one frame.
+pub struct Record<'a> {
+    pub frame: u64,
+    /// Seconds from the first frame, when the source has a clock.
+    pub time: Option<f64>,
+    /// Image file name, for folder sources.
+    pub source: Option<&'a str>,
+    pub presence: Option<f32>,
+    pub hand: Option<&'a HandResult>,
+}
+
+fn handedness_name(handedness: Option<Handedness>) -> Option<&'static str> {
+    handedness.map(|h| match h {
+        Handedness::Left => "left",
+        Handedness::Right => "right",
+    })
+}
+
+pub fn encode_json(record: &Record) -> Value {
+    let hand = record.hand;
+    json!({
+        "frame": record.frame,
+        "t": record.time,
+        "source": record.source,
+        "presence": record.presence,
+        "hand": hand.is_some(),
+        "handedness": hand.and_then(|h| handedness_name(h.handedness)),
+        "pose": hand.map(|h| stream::pose_name(gesture::classify_pose(h))),
+        "landmarks": hand.map(|h| h.landmarks.iter().map(|&(x, y, z)| [x, y, z]).collect::<Vec<_>>()),
+    })
+}
+
+pub fn csv_header() -> String {
+    let mut columns = vec!["frame", "t", "source", "presence", "hand", "handedness", "pose"]
+        .into_iter().map(String::from).collect::<Vec<_>>();
+    for i in 0..JOINTS {
+        columns.extend(["x", "y", "z"].map(|axis| format!("{}{}", axis, i)));
+    }
+    columns.join(",")
+}
+
+/// Quotes a cell holding a separator, quote or line break.
+fn csv_cell(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Missing values are empty cells.
+pub fn encode_csv(record: &Record) -> String {
+    let hand = record.hand;
+    let mut cells = vec![
+        record.frame.to_string(),
+        record.time.map_or_else(String::new, |t| format!("{:.6}", t)),
+        record.source.map_or_else(String::new, csv_cell),
+        record.presence.map_or_else(String::new, |p| p.to_string()),
+        (hand.is_some() as u8).to_string(),
+        hand.and_then(|h| handedness_name(h.handedness)).unwrap_or_default().to_string(),
+        hand.map_or("", |h| stream::pose_name(gesture::classify_pose(h))).to_string(),
+    ];
+    for i in 0..JOINTS {
+        match hand.and_then(|h| h.landmarks.get(i)) {
+            Some(&(x, y, z)) => cells.extend([x, y, z].map(|v| v.to_string())),
+            None => cells.extend(std::iter::repeat_n(String::new(), 3)),
+        }
+    }
+    cells.join(",")
+}
+
+/// Writes records to a file or stdout, one line each.
+pub struct RecordWriter {
+    out: Box<dyn Write>,
+    format: DumpFormat,
+}
+
+impl RecordWriter {
+    /// Writes the CSV header right away.
+    pub fn new(mut out: Box<dyn Write>, format: DumpFormat) -> Result<Self> {
+        if format == DumpFormat::Csv {
+            writeln!(out, "{}", csv_header())?;
+        }
+        Ok(Self { out, format })
+    }
+
+    pub fn write(&mut self, record: &Record) -> Result<()> {
+        match self.format {
+            DumpFormat::Jsonl => writeln!(self.out, "{}", encode_json(record))?,
+            DumpFormat::Csv => writeln!(self.out, "{}", encode_csv(record))?,
+        }
+        // Live camera dumps are often piped into another tool
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::hand_result;
+
+    fn hand() -> HandResult {
+        hand_result((0..JOINTS).map(|i| (i as f32 / 100.0, 0.5, -0.25)).collect(), Some(Handedness::Left))
+    }
+
+    #[test]
+    fn test_json_record() {
+        let hand = hand();
+        let record = Record { frame: 3, time: Some(0.1), source: None, presence: Some(0.75), hand: Some(&hand) };
+        let json = encode_json(&record);
+        assert_eq!(json["handedness"], "left");
+        assert_eq!(json["landmarks"].as_array().unwrap().len(), JOINTS);
+        assert_eq!(json["landmarks"][8], json!([0.08f32, 0.5, -0.25]));
+        assert!(json["pose"].is_string());
+
+        let empty = encode_json(&Record { hand: None, presence: Some(0.25), ..record });
+        assert_eq!((empty["hand"].clone(), empty["landmarks"].clone(), empty["pose"].clone()), (json!(false), Value::Null, Value::Null));
+    }
+
+    #[test]
+    fn test_csv_rows_match_header() {
+        let columns = csv_header().split(',').count();
+        assert_eq!(columns, 7 + JOINTS * 3);
+
+        let hand = hand();
+        let row = encode_csv(&Record { frame: 0, time: None, source: Some("a,b.png"), presence: None, hand: Some(&hand) });
+        assert!(row.starts_with("0,,\"a,b.png\",,1,left,"));
+        assert!(row.ends_with(",0.2,0.5,-0.25"));
+
+        let row = encode_csv(&Record { frame: 1, time: Some(0.5), source: None, presence: Some(0.1), hand: None });
+        assert_eq!(row.split(',').count(), columns);
+        assert!(row.starts_with("1,0.500000,,0.1,0,,,"));
+    }
+}
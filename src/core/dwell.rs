@@ -0,0 +1,208 @@
+use std::time::{Duration, Instant};
+
+/// What a completed dwell does. Cycled from the GUI.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DwellAction {
+    Left,
+    Right,
+    Double,
+    Drag,
+}
+
+impl DwellAction {
+    pub fn next(self) -> Self {
+        match self {
+            DwellAction::Left => DwellAction::Right,
+            DwellAction::Right => DwellAction::Double,
+            DwellAction::Double => DwellAction::Drag,
+            DwellAction::Drag => DwellAction::Left,
+        }
+    }
+}
+
+pub struct DwellConfig {
+    /// Max cursor wander (screen pixels) that still counts as resting.
+    pub radius: f32,
+    /// Time the cursor must rest before the action fires.
+    pub dwell: Duration,
+    /// Quiet time after a fire before a new dwell can start.
+    pub refractory: Duration,
+}
+
+impl Default for DwellConfig {
+    fn default() -> Self {
+        Self {
+            radius: 30.0,
+            dwell: Duration::from_millis(800),
+            refractory: Duration::from_millis(1000),
+        }
+    }
+}
+
+/// Fires a click when the cursor rests inside a small circle long enough.
+pub struct DwellClicker {
+    config: DwellConfig,
+    action: DwellAction,
+    anchor: Option<(f32, f32)>,
+    since: Instant,
+    last_fire: Option<Instant>,
+    spent: bool,
+    /// A drag started by a dwell and not yet ended.
+    dragging: bool,
+}
+
+impl DwellClicker {
+    pub fn new(config: DwellConfig, action: DwellAction) -> Self {
+        Self {
+            config,
+            action,
+            anchor: None,
+            since: Instant::now(),
+            last_fire: None,
+            spent: false,
+            dragging: false,
+        }
+    }
+
+    pub fn action(&self) -> DwellAction {
+        self.action
+    }
+
+    pub fn set_action(&mut self, action: DwellAction) {
+        self.action = action;
+        self.dragging = false;
+        self.reset();
+    }
+
+    pub fn cycle_action(&mut self) -> DwellAction {
+        self.set_action(self.action.next());
+        self.action
+    }
+
+    /// True between the dwell that starts a drag and the one that ends it.
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Another click source released the button: the next drag dwell starts a new drag.
+    pub fn end_drag(&mut self) {
+        self.dragging = false;
+    }
+
+    /// Forget the current dwell (hand lost, pointer disengaged...).
+    pub fn reset(&mut self) {
+        self.anchor = None;
+        self.spent = false;
+    }
+
+    /// Feed the filtered cursor position. Returns the action when a dwell completes.
+    pub fn update(&mut self, pos: (f32, f32), now: Instant) -> Option<DwellAction> {
+        let moved = match self.anchor {
+            Some((ax, ay)) => (pos.0 - ax).powi(2) + (pos.1 - ay).powi(2) > self.config.radius.powi(2),
+            None => true,
+        };
+
+        if moved {
+            self.anchor = Some(pos);
+            self.since = now;
+            self.spent = false;
+            return None;
+        }
+
+        let cooling = self.last_fire
+            .is_some_and(|t| now.duration_since(t) < self.config.refractory);
+        if cooling {
+            // Dwell time only counts once the refractory period is over
+            self.since = now;
+            return None;
+        }
+
+        if !self.spent && now.duration_since(self.since) >= self.config.dwell {
+            self.spent = true;
+            self.last_fire = Some(now);
+            if self.action == DwellAction::Drag {
+                self.dragging = !self.dragging;
+            }
+            return Some(self.action);
+        }
+
+        None
+    }
+
+    /// 0.0..=1.0 progress of the current dwell, for the GUI ring.
+    pub fn progress(&self, now: Instant) -> f32 {
+        if self.anchor.is_none() || self.spent {
+            return 0.0;
+        }
+        let elapsed = now.duration_since(self.since).as_secs_f32();
+        (elapsed / self.config.dwell.as_secs_f32().max(f32::EPSILON)).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clicker(action: DwellAction) -> DwellClicker {
+        DwellClicker::new(DwellConfig::default(), action)
+    }
+
+    #[test]
+    fn test_dwell_fires_once_until_moved() {
+        let mut dwell = clicker(DwellAction::Left);
+        let t0 = Instant::now();
+        let ms = |n| t0 + Duration::from_millis(n);
+
+        assert_eq!(dwell.update((100.0, 100.0), t0), None);
+        // Small wander stays inside the radius
+        assert_eq!(dwell.update((110.0, 110.0), ms(400)), None);
+        assert!((dwell.progress(ms(400)) - 0.5).abs() < 1e-3);
+        assert_eq!(dwell.update((105.0, 100.0), ms(800)), Some(DwellAction::Left));
+
+        // Spent: resting on does not fire again and shows no progress
+        assert_eq!(dwell.update((100.0, 100.0), ms(5000)), None);
+        assert_eq!(dwell.progress(ms(5000)), 0.0);
+    }
+
+    #[test]
+    fn test_refractory_delays_the_next_dwell() {
+        let mut dwell = clicker(DwellAction::Left);
+        let t0 = Instant::now();
+        let ms = |n| t0 + Duration::from_millis(n);
+        dwell.update((100.0, 100.0), t0);
+        assert_eq!(dwell.update((100.0, 100.0), ms(800)), Some(DwellAction::Left));
+
+        // Moving on re-arms it, but dwell time only counts after the refractory second
+        assert_eq!(dwell.update((300.0, 100.0), ms(900)), None);
+        assert_eq!(dwell.update((300.0, 100.0), ms(1799)), None);
+        assert_eq!(dwell.update((300.0, 100.0), ms(2598)), None);
+        assert_eq!(dwell.update((300.0, 100.0), ms(2599)), Some(DwellAction::Left));
+    }
+
+    #[test]
+    fn test_drag_start_and_end() {
+        let mut dwell = clicker(DwellAction::Drag);
+        let t0 = Instant::now();
+        let ms = |n| t0 + Duration::from_millis(n);
+        dwell.update((100.0, 100.0), t0);
+        assert_eq!(dwell.update((100.0, 100.0), ms(800)), Some(DwellAction::Drag));
+        assert!(dwell.is_dragging());
+
+        // The drop: move, then dwell again
+        dwell.update((400.0, 100.0), ms(900));
+        assert_eq!(dwell.update((400.0, 100.0), ms(2600)), Some(DwellAction::Drag));
+        assert!(!dwell.is_dragging());
+
+        // A click from elsewhere ends the drag; the next dwell starts a new one
+        dwell.update((100.0, 100.0), ms(2700));
+        assert_eq!(dwell.update((100.0, 100.0), ms(4400)), Some(DwellAction::Drag));
+        dwell.end_drag();
+        dwell.update((400.0, 100.0), ms(4500));
+        assert_eq!(dwell.update((400.0, 100.0), ms(6200)), Some(DwellAction::Drag));
+        assert!(dwell.is_dragging());
+
+        // Switching action drops the drag
+        assert_eq!(dwell.cycle_action(), DwellAction::Left);
+        assert!(!dwell.is_dragging());
+    }
+}
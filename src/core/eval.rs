@@ -0,0 +1,330 @@
+use crate::core::dataset::{HAND_CATEGORY, IMAGES_DIR, KEYPOINTS};
+use crate::Result;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::info;
+
+/// A PCK, precision or recall this much lower than the baseline is a regression...
+const FRACTION_TOLERANCE: f64 = 0.01;
+/// ...as is a mean joint error this factor higher...
+const ERROR_TOLERANCE: f64 = 1.05;
+/// ...or a mean latency this factor higher (timing is noisy).
+const LATENCY_TOLERANCE: f64 = 1.2;
+
+/// One labelled image. Joints are frame-normalized; `None` for joints the
+/// labeller left out, `joints: None` for an image without a hand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    pub path: PathBuf,
+    pub joints: Option<Vec<Option<(f32, f32)>>>,
+}
+
+fn invalid(msg: String) -> crate::AirLinkError {
+    crate::AirLinkError::CoreError(format!("Labels: {}", msg))
+}
+
+/// Reads a COCO keypoint file (an object with `images` and `annotations`;
+/// images flagged `bad_detection` are skipped) or the simple format: `[{"image": "a.png", "landmarks": [[x, y], ...] or null}]`
+/// with frame-normalized points. Image paths are relative to `images`, which
+/// defaults to `images/` next to a COCO file and the label file's folder otherwise.
+pub fn load_labels(path: &Path, images: Option<&Path>) -> Result<Vec<Label>> {
+    let json: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    match &json {
+        Value::Object(coco) => {
+            let images = images.map_or_else(|| dir.join(IMAGES_DIR), Path::to_path_buf);
+            parse_coco(coco, &images)
+        }
+        Value::Array(entries) => parse_simple(entries, images.unwrap_or(dir)),
+        _ => Err(invalid("expected a COCO object or an array of images".into())),
+    }
+}
+
+fn parse_coco(coco: &Map<String, Value>, images: &Path) -> Result<Vec<Label>> {
+    let list = |key: &str| coco.get(key).and_then(Value::as_array).ok_or_else(|| invalid(format!("COCO file without `{}`", key)));
+    let annotations = list("annotations")?;
+    let mut labels = Vec::new();
+    let mut flagged = 0;
+    for image in list("images")? {
+        // Known-wrong pseudo-labels from capture-dataset would score the model against noise
+        if image.get("bad_detection").and_then(Value::as_bool) == Some(true) {
+            flagged += 1;
+            continue;
+        }
+        let field = |key: &str| image.get(key).and_then(Value::as_u64).ok_or_else(|| invalid(format!("image without `{}`", key)));
+        let (id, width, height) = (field("id")?, field("width")?, field("height")?);
+        let file_name = image.get("file_name").and_then(Value::as_str).ok_or_else(|| invalid(format!("image {} without `file_name`", id)))?;
+
+        // Single-hand detector: the first hand annotation is the label
+        let hand = annotations.iter().find(|a| {
+            a.get("image_id").and_then(Value::as_u64) == Some(id)
+                && a.get("category_id").and_then(Value::as_u64).is_none_or(|c| c == HAND_CATEGORY)
+        });
+        let joints = match hand {
+            Some(hand) => {
+                let keypoints = hand.get("keypoints").and_then(Value::as_array)
+                    .ok_or_else(|| invalid(format!("annotation for image {} without `keypoints`", id)))?;
+                let values: Vec<f64> = keypoints.iter().map(|v| v.as_f64().unwrap_or_default()).collect();
+                let (w, h) = (width.max(1) as f64, height.max(1) as f64);
+                Some(values.chunks_exact(3)
+                    .map(|k| (k[2] > 0.0).then_some(((k[0] / w) as f32, (k[1] / h) as f32)))
+                    .collect())
+            }
+            None => None,
+        };
+        labels.push(Label { path: images.join(file_name), joints });
+    }
+    if flagged > 0 {
+        info!("🎯 Skipping {} images flagged as bad detections", flagged);
+    }
+    Ok(labels)
+}
+
+fn parse_simple(entries: &[Value], images: &Path) -> Result<Vec<Label>> {
+    entries.iter().enumerate().map(|(i, entry)| {
+        let image = entry.get("image").and_then(Value::as_str).ok_or_else(|| invalid(format!("entry {} without `image`", i)))?;
+        let joints = match entry.get("landmarks") {
+            None | Some(Value::Null) => None,
+            Some(Value::Array(points)) => Some(points.iter().map(|p| match p {
+                Value::Null => Ok(None),
+                Value::Array(xy) if xy.len() >= 2 && xy[0].is_number() && xy[1].is_number() => {
+                    Ok(xy[0].as_f64().zip(xy[1].as_f64()).map(|(x, y)| (x as f32, y as f32)))
+                }
+                _ => Err(invalid(format!("{}: landmarks must be [x, y] pairs or null", image))),
+            }).collect::<Result<Vec<_>>>()?),
+            Some(_) => return Err(invalid(format!("{}: landmarks must be an array or null", image))),
+        };
+        Ok(Label { path: images.join(image), joints })
+    }).collect()
+}
+
+/// Accumulates detector results against labels, image by image.
+pub struct EvalStats {
+    thresholds: Vec<f32>,
+    /// Joints within each PCK threshold.
+    within: Vec<u64>,
+    /// Joints labelled and predicted.
+    scored: u64,
+    images: u64,
+    joint_error: [f64; KEYPOINTS.len()],
+    joint_count: [u64; KEYPOINTS.len()],
+    true_pos: u64,
+    false_pos: u64,
+    false_neg: u64,
+    true_neg: u64,
+    latencies: Vec<f64>,
+}
+
+impl EvalStats {
+    /// PCK thresholds are fractions of the labelled hand's bounding box side.
+    pub fn new(thresholds: &[f32]) -> Self {
+        Self {
+            thresholds: thresholds.to_vec(),
+            within: vec![0; thresholds.len()],
+            scored: 0,
+            images: 0,
+            joint_error: [0.0; KEYPOINTS.len()],
+            joint_count: [0; KEYPOINTS.len()],
+            true_pos: 0,
+            false_pos: 0,
+            false_neg: 0,
+            true_neg: 0,
+            latencies: Vec::new(),
+        }
+    }
+
+    /// One image of `size` pixels: its label, the detector's landmarks and how long it took.
+    pub fn add(&mut self, label: Option<&[Option<(f32, f32)>]>, predicted: Option<&[(f32, f32, f32)]>, size: (u32, u32), latency: Duration) {
+        self.images += 1;
+        self.latencies.push(latency.as_secs_f64() * 1000.0);
+        let (label, predicted) = match (label, predicted) {
+            (Some(label), Some(predicted)) => (label, predicted),
+            (Some(_), None) => {
+                self.false_neg += 1;
+                return;
+            }
+            (None, Some(_)) => {
+                self.false_pos += 1;
+                return;
+            }
+            (None, None) => {
+                self.true_neg += 1;
+                return;
+            }
+        };
+        self.true_pos += 1;
+
+        let (w, h) = (size.0 as f32, size.1 as f32);
+        let pixels: Vec<Option<(f32, f32)>> = label.iter().map(|p| p.map(|(x, y)| (x * w, y * h))).collect();
+        let labelled = pixels.iter().flatten();
+        let (min_x, max_x) = labelled.clone().fold((f32::MAX, f32::MIN), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+        let (min_y, max_y) = labelled.fold((f32::MAX, f32::MIN), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+        let box_size = (max_x - min_x).max(max_y - min_y).max(1.0);
+
+        for (joint, (truth, guess)) in pixels.iter().zip(predicted).enumerate().take(KEYPOINTS.len()) {
+            let Some((tx, ty)) = truth else { continue };
+            let error = ((guess.0 * w - tx).powi(2) + (guess.1 * h - ty).powi(2)).sqrt();
+            self.scored += 1;
+            self.joint_error[joint] += error as f64;
+            self.joint_count[joint] += 1;
+            for (within, threshold) in self.within.iter_mut().zip(&self.thresholds) {
+                *within += (error <= threshold * box_size) as u64;
+            }
+        }
+    }
+
+    /// The report as JSON, the format `--baseline` reads back.
+    pub fn report(&self) -> Value {
+        let ratio = |num: u64, den: u64| (den > 0).then(|| num as f64 / den as f64);
+        let pck: Map<String, Value> = self.thresholds.iter().zip(&self.within)
+            .map(|(k, &n)| (k.to_string(), json!(ratio(n, self.scored))))
+            .collect();
+        let per_joint: Map<String, Value> = KEYPOINTS.iter().enumerate()
+            .map(|(i, name)| (name.to_string(), json!((self.joint_count[i] > 0).then(|| self.joint_error[i] / self.joint_count[i] as f64))))
+            .collect();
+        let total: f64 = self.joint_error.iter().sum();
+
+        let mut latencies = self.latencies.clone();
+        latencies.sort_by(f64::total_cmp);
+        let percentile = |p: usize| latencies.get((latencies.len().max(1) - 1) * p / 100).copied();
+        let mean_latency = (!latencies.is_empty()).then(|| latencies.iter().sum::<f64>() / latencies.len() as f64);
+
+        json!({
+            "images": self.images,
+            // Images with both a labelled and a detected hand, which PCK and errors cover
+            "matched": self.true_pos,
+            "pck": pck,
+            "mean_error_px": (self.scored > 0).then(|| total / self.scored as f64),
+            "joint_error_px": per_joint,
+            "presence": {
+                "true_positive": self.true_pos,
+                "false_positive": self.false_pos,
+                "false_negative": self.false_neg,
+                "true_negative": self.true_neg,
+                "precision": ratio(self.true_pos, self.true_pos + self.false_pos),
+                "recall": ratio(self.true_pos, self.true_pos + self.false_neg),
+            },
+            "latency_ms": { "mean": mean_latency, "p50": percentile(50), "p95": percentile(95) },
+        })
+    }
+}
+
+/// Metrics that got worse than in `baseline`, one line each. Metrics
+/// missing from either report are skipped.
+pub fn regressions(report: &Value, baseline: &Value) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut lower_is_worse = |name: String, now: Option<f64>, before: Option<f64>| {
+        if let (Some(now), Some(before)) = (now, before) && now < before - FRACTION_TOLERANCE {
+            out.push(format!("{} fell from {:.3} to {:.3}", name, before, now));
+        }
+    };
+    if let Some(pck) = report["pck"].as_object() {
+        for (k, now) in pck {
+            lower_is_worse(format!("PCK@{}", k), now.as_f64(), baseline["pck"][k].as_f64());
+        }
+    }
+    for key in ["precision", "recall"] {
+        lower_is_worse(format!("presence {}", key), report["presence"][key].as_f64(), baseline["presence"][key].as_f64());
+    }
+
+    let higher = [
+        ("mean joint error (px)", &report["mean_error_px"], &baseline["mean_error_px"], ERROR_TOLERANCE),
+        ("mean latency (ms)", &report["latency_ms"]["mean"], &baseline["latency_ms"]["mean"], LATENCY_TOLERANCE),
+    ];
+    for (name, now, before, factor) in higher {
+        if let (Some(now), Some(before)) = (now.as_f64(), before.as_f64()) && now > before * factor {
+            out.push(format!("{} rose from {:.2} to {:.2}", name, before, now));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::{hand_result, TempDir};
+
+    fn hand(offset: f32) -> Vec<(f32, f32, f32)> {
+        (0..KEYPOINTS.len()).map(|i| (0.25 + i as f32 / 80.0 + offset, 0.5 + offset, 0.0)).collect()
+    }
+
+    fn label(hand: &[(f32, f32, f32)]) -> Vec<Option<(f32, f32)>> {
+        hand.iter().map(|p| Some((p.0, p.1))).collect()
+    }
+
+    #[test]
+    fn test_load_coco_and_simple_labels() {
+        let dir = TempDir::new("eval");
+        let coco = json!({
+            "images": [
+                {"id": 1, "file_name": "a.png", "width": 200, "height": 100},
+                {"id": 2, "file_name": "b.png", "width": 200, "height": 100},
+            ],
+            "annotations": [{"id": 7, "image_id": 1, "category_id": 1, "keypoints": [100, 50, 2, 0, 0, 0]}],
+        });
+        fs::write(dir.join("coco.json"), coco.to_string()).unwrap();
+        let labels = load_labels(&dir.join("coco.json"), None).unwrap();
+        assert_eq!(labels[0], Label { path: dir.join("images/a.png"), joints: Some(vec![Some((0.5, 0.5)), None]) });
+        assert_eq!(labels[1].joints, None);
+
+        fs::write(dir.join("simple.json"), r#"[{"image": "c.png", "landmarks": [[0.1, 0.2], null]}, {"image": "d.png"}]"#).unwrap();
+        let labels = load_labels(&dir.join("simple.json"), None).unwrap();
+        assert_eq!(labels[0], Label { path: dir.join("c.png"), joints: Some(vec![Some((0.1, 0.2)), None]) });
+        assert_eq!(labels[1].joints, None);
+
+        fs::write(dir.join("bad.json"), r#"[{"image": "e.png", "landmarks": 3}]"#).unwrap();
+        assert!(load_labels(&dir.join("bad.json"), None).is_err());
+    }
+
+    #[test]
+    fn test_flagged_captures_are_skipped() {
+        use crate::core::dataset::{DatasetWriter, SessionInfo, ANNOTATIONS_FILE};
+
+        let dir = TempDir::new("eval-flagged");
+        let mut writer = DatasetWriter::create(&dir, SessionInfo::default()).unwrap();
+        let image = image::DynamicImage::new_rgb8(64, 64);
+        let found = hand_result(hand(0.0), None);
+        writer.add(&image, 0.0, Some(0.9), Some(&found)).unwrap();
+        writer.add(&image, 0.5, Some(0.8), Some(&found)).unwrap();
+        writer.toggle_last().unwrap();
+        writer.add(&image, 1.0, Some(0.1), None).unwrap();
+        drop(writer);
+
+        let labels = load_labels(&dir.join(ANNOTATIONS_FILE), None).unwrap();
+        let paths: Vec<_> = labels.iter().map(|l| l.path.clone()).collect();
+        assert_eq!(paths, [dir.join("images/000001.png"), dir.join("images/000003.png")]);
+        assert!(labels[0].joints.is_some() && labels[1].joints.is_none());
+    }
+
+    #[test]
+    fn test_metrics_and_regressions() {
+        let mut stats = EvalStats::new(&[0.05, 0.2]);
+        let truth = hand(0.0);
+        let ms = Duration::from_millis;
+        // Spot on, then 10 px off on each axis with a 200 px wide hand box
+        stats.add(Some(&label(&truth)), Some(&truth), (800, 800), ms(4));
+        stats.add(Some(&label(&truth)), Some(&hand(10.0 / 800.0)), (800, 800), ms(6));
+        stats.add(Some(&label(&truth)), None, (800, 800), ms(5));
+        stats.add(None, Some(&truth), (800, 800), ms(5));
+        stats.add(None, None, (800, 800), ms(5));
+
+        let report = stats.report();
+        assert_eq!(report["images"], 5);
+        assert_eq!(report["pck"]["0.05"], 0.5);
+        assert_eq!(report["pck"]["0.2"], 1.0);
+        let error = report["mean_error_px"].as_f64().unwrap();
+        assert!((error - 50f64.sqrt()).abs() < 0.01, "{}", error);
+        assert_eq!((report["presence"]["precision"].clone(), report["presence"]["recall"].clone()), (json!(2.0 / 3.0), json!(2.0 / 3.0)));
+        assert_eq!(report["latency_ms"]["p50"], 5.0);
+
+        assert!(regressions(&report, &report).is_empty());
+        let mut baseline = report.clone();
+        baseline["pck"]["0.05"] = json!(0.9);
+        baseline["mean_error_px"] = json!(1.0);
+        let found = regressions(&report, &baseline);
+        assert_eq!(found.len(), 2, "{:?}", found);
+        assert!(found[0].starts_with("PCK@0.05"));
+    }
+}
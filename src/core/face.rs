@@ -0,0 +1,245 @@
+use ort::session::Session;
+use ort::value::TensorRef;
+use crate::core::frame::Frame;
+use crate::core::gesture::HandPose;
+use crate::core::model::{Bindings, ModelDescriptor};
+use crate::core::preprocess::TensorInput;
+use crate::core::tracker::{Tracker, TrackerOutput};
+use crate::core::transform::Affine;
+use crate::core::vision::open_model;
+use crate::Result;
+use std::time::{Duration, Instant};
+
+/// MediaPipe face mesh indices.
+const NOSE_TIP: usize = 1;
+const LEFT_EYE: [usize; 4] = [33, 133, 159, 145]; // corner, corner, top, bottom
+const RIGHT_EYE: [usize; 4] = [362, 263, 386, 374];
+const MOUTH: [usize; 4] = [61, 291, 13, 14];
+/// The next crop spans the last mesh's larger side times this.
+const ROI_SCALE: f32 = 1.5;
+
+/// Which face gesture acts as the click.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FaceClick {
+    /// Both eyes closed longer than a natural blink.
+    Blink,
+    MouthOpen,
+}
+
+pub struct FaceResult {
+    pub nose_tip: (f32, f32),
+    /// Eye aspect ratio (height / width), averaged over both eyes.
+    pub eye_openness: f32,
+    /// Mouth aspect ratio (height / width).
+    pub mouth_openness: f32,
+}
+
+/// Height over width of an eye or the mouth, from its two corners, top and bottom.
+pub fn aspect_ratio([c1, c2, top, bottom]: [(f32, f32); 4]) -> f32 {
+    let dist = |a: (f32, f32), b: (f32, f32)| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+    dist(top, bottom) / dist(c1, c2).max(f32::EPSILON)
+}
+
+/// Frame-to-model map cropping a square around the mesh points; see [`Affine::around`].
+pub fn face_roi(points: &[(f32, f32)], width: u32, height: u32) -> Option<Affine> {
+    Affine::around(points, width, height, ROI_SCALE)
+}
+
+/// Turns eye and mouth openness into the click state.
+pub struct FaceClicker {
+    click: FaceClick,
+    closed_since: Option<Instant>,
+}
+
+impl FaceClicker {
+    /// Eyes count as closed below this aspect ratio.
+    const EYE_CLOSED: f32 = 0.15;
+    /// Mouth counts as open above this aspect ratio.
+    const MOUTH_OPEN: f32 = 0.35;
+    /// Deliberate blinks are held longer than spontaneous ones (~150 ms).
+    const BLINK_HOLD: Duration = Duration::from_millis(400);
+
+    pub fn new(click: FaceClick) -> Self {
+        Self { click, closed_since: None }
+    }
+
+    /// True while the click gesture is held. `face` is `None` when the face was lost.
+    pub fn update(&mut self, face: Option<&FaceResult>, now: Instant) -> bool {
+        let Some(face) = face else {
+            self.closed_since = None;
+            return false;
+        };
+        match self.click {
+            FaceClick::MouthOpen => face.mouth_openness > Self::MOUTH_OPEN,
+            FaceClick::Blink if face.eye_openness < Self::EYE_CLOSED => {
+                let since = *self.closed_since.get_or_insert(now);
+                now.duration_since(since) >= Self::BLINK_HOLD
+            }
+            FaceClick::Blink => {
+                self.closed_since = None;
+                false
+            }
+        }
+    }
+}
+
+/// Head pointer: a face landmark ONNX model where the nose tip drives the cursor.
+pub struct FaceDetector {
+    session: Session,
+    model: ModelDescriptor,
+    bindings: Bindings,
+    input: TensorInput,
+    clicker: FaceClicker,
+    /// Crop around the last frame's face; the whole frame until one is found.
+    roi: Option<Affine>,
+}
+
+impl FaceDetector {
+    /// Mesh points the gestures read.
+    const JOINTS: usize = 468;
+
+    pub fn new(model_path: &str, click: FaceClick) -> Result<Self> {
+        let (session, model, bindings) = open_model(model_path, ModelDescriptor::face(), Self::JOINTS)?;
+        let input = TensorInput::new(model.size).with_format(model.layout, model.normalization);
+        Ok(Self { session, model, bindings, input, clicker: FaceClicker::new(click), roi: None })
+    }
+
+    pub fn detect(&mut self, frame: &Frame) -> Result<Option<FaceResult>> {
+        // Lost faces are searched for in the whole frame again
+        let transform = self.roi.take().unwrap_or_else(|| Affine::letterbox(frame.width, frame.height));
+        self.input.fill_with(frame, transform)?;
+        let tensor = TensorRef::from_array_view((self.input.shape(), self.input.data()))?;
+
+        let outputs = self.session.run(ort::inputs![self.bindings.input.as_str() => tensor])?;
+
+        // 1. Face presence, as a logit or a probability depending on the export
+        if let Some(presence) = self.bindings.presence {
+            let (_, score_data) = outputs[presence].try_extract_tensor::<f32>()?;
+            if self.model.probability(score_data[0]) < self.model.threshold {
+                return Ok(None);
+            }
+        }
+
+        // 2. Mesh landmarks, 468 points in the descriptor's units
+        let (_shape, data) = outputs[self.bindings.landmarks].try_extract_tensor::<f32>()
+            .map_err(|e| crate::AirLinkError::CoreError(format!("Output extraction failed: {}", e)))?;
+
+        if data.len() < self.model.count * self.model.stride { return Ok(None); }
+
+        // Model input -> frame, undoing the crop or letterbox
+        let to_frame = self.input.transform().inverse().unwrap_or_default();
+        let mesh: Vec<(f32, f32)> = (0..Self::JOINTS)
+            .map(|i| {
+                let (x, y, _) = self.model.landmark(data, i);
+                to_frame.apply((x, y))
+            })
+            .collect();
+        let (nx, ny) = mesh[NOSE_TIP];
+        if !(0.0..=1.0).contains(&nx) || !(0.0..=1.0).contains(&ny) {
+            return Ok(None);
+        }
+        self.roi = face_roi(&mesh, frame.width, frame.height);
+
+        // Ratios in pixels, so a non-square frame doesn't skew them
+        let (w, h) = (frame.width as f32, frame.height as f32);
+        let aspect = |indices: [usize; 4]| aspect_ratio(indices.map(|i| (mesh[i].0 * w, mesh[i].1 * h)));
+        Ok(Some(FaceResult {
+            nose_tip: (nx, ny),
+            eye_openness: (aspect(LEFT_EYE) + aspect(RIGHT_EYE)) / 2.0,
+            mouth_openness: aspect(MOUTH),
+        }))
+    }
+}
+
+impl Tracker for FaceDetector {
+    fn track(&mut self, frame: &Frame) -> Result<Option<TrackerOutput>> {
+        let face = self.detect(frame)?;
+        let click = self.clicker.update(face.as_ref(), frame.timestamp);
+        let Some(face) = face else { return Ok(None) };
+
+        Ok(Some(TrackerOutput {
+            pointer: face.nose_tip,
+            click,
+            pose: HandPose::Unknown,
+            hand: None,
+            other_hands: Vec::new(),
+            timestamp: frame.timestamp,
+            sequence: frame.sequence,
+        }))
+    }
+
+    /// The nose travels far less than a hand, so a small central window is the whole screen.
+    fn active_zone(&self) -> (f32, f32) {
+        (0.4, 0.6)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face(eye_openness: f32, mouth_openness: f32) -> FaceResult {
+        FaceResult { nose_tip: (0.5, 0.5), eye_openness, mouth_openness }
+    }
+
+    #[test]
+    fn test_aspect_ratio() {
+        // Eye 40 px wide and 10 px open, then one at an angle
+        assert!((aspect_ratio([(0.0, 0.0), (40.0, 0.0), (20.0, -5.0), (20.0, 5.0)]) - 0.25).abs() < 1e-6);
+        let tilted = [(0.0, 0.0), (30.0, 40.0), (10.0, 25.0), (20.0, 15.0)];
+        assert!((aspect_ratio(tilted) - (200f32.sqrt() / 50.0)).abs() < 1e-6);
+        // Collapsed corners don't divide by zero
+        assert!(aspect_ratio([(1.0, 1.0); 4]).is_finite());
+    }
+
+    #[test]
+    fn test_blink_must_be_held() {
+        let mut clicker = FaceClicker::new(FaceClick::Blink);
+        let t0 = Instant::now();
+        let ms = |n| t0 + Duration::from_millis(n);
+        let (open, closed) = (face(0.3, 0.1), face(0.05, 0.1));
+
+        // A natural blink never clicks
+        assert!(!clicker.update(Some(&closed), t0));
+        assert!(!clicker.update(Some(&closed), ms(150)));
+        assert!(!clicker.update(Some(&open), ms(200)));
+
+        // A deliberate one clicks after the hold and for as long as it lasts
+        assert!(!clicker.update(Some(&closed), ms(300)));
+        assert!(!clicker.update(Some(&closed), ms(699)));
+        assert!(clicker.update(Some(&closed), ms(700)));
+        assert!(clicker.update(Some(&closed), ms(900)));
+        assert!(!clicker.update(Some(&open), ms(950)));
+
+        // Losing the face restarts the hold
+        assert!(!clicker.update(Some(&closed), ms(1000)));
+        assert!(!clicker.update(None, ms(1200)));
+        assert!(!clicker.update(Some(&closed), ms(1450)));
+        assert!(clicker.update(Some(&closed), ms(1850)));
+    }
+
+    #[test]
+    fn test_mouth_click_is_immediate() {
+        let mut clicker = FaceClicker::new(FaceClick::MouthOpen);
+        let now = Instant::now();
+        assert!(clicker.update(Some(&face(0.05, 0.5)), now));
+        assert!(!clicker.update(Some(&face(0.05, 0.2)), now));
+    }
+
+    #[test]
+    fn test_roi_is_square_in_pixels() {
+        // A 64x48 px face in a 640x480 frame: a 96 px square around its center
+        let mesh = [(0.45, 0.45), (0.55, 0.55), (0.5, 0.5)];
+        let roi = face_roi(&mesh, 640, 480).unwrap();
+        let (x0, y0) = roi.inverse().unwrap().apply((0.0, 0.0));
+        let (x1, y1) = roi.inverse().unwrap().apply((1.0, 1.0));
+        assert!((((x1 - x0) * 640.0) - 96.0).abs() < 1e-3);
+        assert!((((y1 - y0) * 480.0) - 96.0).abs() < 1e-3);
+        assert!(((x0 + x1) / 2.0 - 0.5).abs() < 1e-6);
+        // The face fills two thirds of the input
+        assert!((roi.apply((0.45, 0.5)).0 - 1.0 / 6.0).abs() < 1e-5);
+
+        assert!(face_roi(&[], 640, 480).is_none());
+        assert!(face_roi(&[(0.5, 0.5)], 640, 480).is_none());
+    }
+}
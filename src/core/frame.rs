@@ -0,0 +1,176 @@
+use crate::Result;
+use image::{DynamicImage, GrayImage, RgbImage};
+use std::time::Instant;
+
+/// Byte layout of a captured buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Packed RGB, 3 bytes per pixel.
+    Rgb,
+    /// Packed BGR, 3 bytes per pixel.
+    Bgr,
+    /// 8-bit luma.
+    Gray,
+    /// Packed 4:2:2, `Y0 U Y1 V` per pixel pair.
+    Yuyv,
+    /// 4:2:0, a full Y plane followed by interleaved UV at half resolution.
+    Nv12,
+    /// One JPEG per frame.
+    Mjpeg,
+}
+
+/// One camera frame in the device's native layout. Trackers read it directly
+/// instead of going through an RGB image first.
+///
+/// Everything downstream (filters, gestures, FPS) times itself from
+/// `timestamp`, never from when it happens to run.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    /// Monotonic time the frame was dequeued from the driver. This is not the
+    /// sensor's capture time: nokhwa drops the V4L2 buffer timestamp, so the
+    /// stamp also includes however long the buffer waited in the queue.
+    pub timestamp: Instant,
+    /// Capture order, counting from 0 for the stream.
+    pub sequence: u64,
+}
+
+/// BT.601 limited-range YCbCr to RGB (0..=255, unclamped), as UVC cameras emit it.
+#[inline(always)]
+pub fn yuv_to_rgb(y: f32, u: f32, v: f32) -> [f32; 3] {
+    let c = 1.164 * (y - 16.0);
+    let (d, e) = (u - 128.0, v - 128.0);
+    [c + 1.596 * e, c - 0.391 * d - 0.813 * e, c + 2.018 * d]
+}
+
+impl Frame {
+    /// A frame captured now, first in its stream.
+    pub fn new(data: Vec<u8>, width: u32, height: u32, format: PixelFormat) -> Self {
+        Self { data, width, height, format, timestamp: Instant::now(), sequence: 0 }
+    }
+
+    pub fn from_image(img: &DynamicImage) -> Self {
+        let rgb = img.to_rgb8();
+        let (width, height) = rgb.dimensions();
+        Self::new(rgb.into_raw(), width, height, PixelFormat::Rgb)
+    }
+
+    /// Bytes a well-formed frame of this size and format holds (MJPEG is variable).
+    pub fn expected_len(&self) -> Option<usize> {
+        let pixels = self.width as usize * self.height as usize;
+        match self.format {
+            PixelFormat::Rgb | PixelFormat::Bgr => Some(pixels * 3),
+            PixelFormat::Gray => Some(pixels),
+            PixelFormat::Yuyv => Some(pixels * 2),
+            PixelFormat::Nv12 => Some(pixels * 3 / 2),
+            PixelFormat::Mjpeg => None,
+        }
+    }
+
+    /// Fails on truncated buffers, which some cameras deliver right after (re)connecting.
+    pub fn check(&self) -> Result<()> {
+        match self.expected_len() {
+            Some(len) if self.data.len() < len => Err(crate::AirLinkError::CoreError(format!(
+                "Short {:?} frame: {} of {} bytes", self.format, self.data.len(), len
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Decoded RGB copy, for display. The tracking path never needs this.
+    pub fn to_image(&self) -> Result<DynamicImage> {
+        self.check()?;
+        let (w, h) = (self.width as usize, self.height as usize);
+        let to_u8 = |c: f32| c.round().clamp(0.0, 255.0) as u8;
+
+        let rgb = match self.format {
+            PixelFormat::Mjpeg => {
+                return image::load_from_memory_with_format(&self.data, image::ImageFormat::Jpeg)
+                    .map_err(|e| crate::AirLinkError::CoreError(format!("MJPEG decode failed: {}", e)));
+            }
+            PixelFormat::Rgb => self.data[..w * h * 3].to_vec(),
+            PixelFormat::Bgr => self.data[..w * h * 3].chunks_exact(3).flat_map(|p| [p[2], p[1], p[0]]).collect(),
+            PixelFormat::Gray => {
+                let gray = GrayImage::from_raw(self.width, self.height, self.data[..w * h].to_vec());
+                return gray.map(DynamicImage::ImageLuma8).ok_or_else(|| {
+                    crate::AirLinkError::CoreError("Invalid gray frame".into())
+                });
+            }
+            PixelFormat::Yuyv => {
+                let mut out = Vec::with_capacity(w * h * 3);
+                for px in self.data[..w * h * 2].chunks_exact(4) {
+                    let (u, v) = (px[1] as f32, px[3] as f32);
+                    for y in [px[0], px[2]] {
+                        out.extend(yuv_to_rgb(y as f32, u, v).map(to_u8));
+                    }
+                }
+                out
+            }
+            PixelFormat::Nv12 => {
+                let (luma, chroma) = self.data.split_at(w * h);
+                let mut out = Vec::with_capacity(w * h * 3);
+                for row in 0..h {
+                    for col in 0..w {
+                        let uv = (row / 2) * w + (col & !1);
+                        let (u, v) = (chroma[uv] as f32, chroma[uv + 1] as f32);
+                        out.extend(yuv_to_rgb(luma[row * w + col] as f32, u, v).map(to_u8));
+                    }
+                }
+                out
+            }
+        };
+
+        RgbImage::from_raw(self.width, self.height, rgb)
+            .map(DynamicImage::ImageRgb8)
+            .ok_or_else(|| crate::AirLinkError::CoreError("Invalid RGB frame".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_image_keeps_size_and_pixels() {
+        let img = RgbImage::from_fn(3, 2, |x, y| image::Rgb([x as u8, y as u8, 7]));
+        let frame = Frame::from_image(&DynamicImage::ImageRgb8(img.clone()));
+
+        assert_eq!((frame.width, frame.height, frame.format, frame.sequence), (3, 2, PixelFormat::Rgb, 0));
+        assert_eq!(frame.data, img.into_raw());
+        assert_eq!(frame.to_image().unwrap().to_rgb8().get_pixel(2, 1), &image::Rgb([2, 1, 7]));
+    }
+
+    #[test]
+    fn test_expected_len_per_format() {
+        let len = |format| Frame::new(Vec::new(), 4, 2, format).expected_len();
+        assert_eq!(len(PixelFormat::Rgb), Some(24));
+        assert_eq!(len(PixelFormat::Bgr), Some(24));
+        assert_eq!(len(PixelFormat::Gray), Some(8));
+        assert_eq!(len(PixelFormat::Yuyv), Some(16));
+        assert_eq!(len(PixelFormat::Nv12), Some(12));
+        assert_eq!(len(PixelFormat::Mjpeg), None);
+    }
+
+    #[test]
+    fn test_short_frames_are_rejected() {
+        assert!(Frame::new(vec![0; 15], 4, 2, PixelFormat::Yuyv).check().is_err());
+        assert!(Frame::new(vec![0; 16], 4, 2, PixelFormat::Yuyv).check().is_ok());
+        assert!(Frame::new(vec![0; 11], 4, 2, PixelFormat::Nv12).to_image().is_err());
+        // No fixed size to check against
+        assert!(Frame::new(vec![0; 3], 4, 2, PixelFormat::Mjpeg).check().is_ok());
+    }
+
+    #[test]
+    fn test_to_image_converts_native_layouts() {
+        let bgr = Frame::new(vec![1, 2, 3], 1, 1, PixelFormat::Bgr).to_image().unwrap();
+        assert_eq!(bgr.to_rgb8().get_pixel(0, 0), &image::Rgb([3, 2, 1]));
+
+        // Limited-range white and black with neutral chroma
+        let yuyv = Frame::new(vec![235, 128, 16, 128], 2, 1, PixelFormat::Yuyv).to_image().unwrap().to_rgb8();
+        assert_eq!(yuyv.get_pixel(0, 0), &image::Rgb([255, 255, 255]));
+        assert_eq!(yuyv.get_pixel(1, 0), &image::Rgb([0, 0, 0]));
+    }
+}
@@ -0,0 +1,453 @@
+use crate::core::gesture::HandPose;
+use crate::core::landmarks::{self, distance, Pinch, INDEX_MCP, INDEX_TIP, MIDDLE_TIP, RING_TIP, THUMB_TIP};
+use crate::Result;
+use std::fs;
+use toml::{Table, Value};
+
+/// Stick travel of the virtual gamepad, as `ABS_*` min/max.
+pub const AXIS_MIN: i32 = -32768;
+pub const AXIS_MAX: i32 = 32767;
+
+/// Fingertip offset from the knuckle, in hand sizes, for full deflection.
+const FINGER_RANGE: f32 = 0.5;
+
+/// Stick axes, in `PadState::axes` order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    RX,
+    RY,
+}
+
+impl Axis {
+    pub const ALL: [Axis; 4] = [Axis::X, Axis::Y, Axis::RX, Axis::RY];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Axis::X => "x",
+            Axis::Y => "y",
+            Axis::RX => "rx",
+            Axis::RY => "ry",
+        }
+    }
+}
+
+/// Gamepad buttons, named after the `BTN_*` codes they send.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PadButton {
+    South,
+    East,
+    North,
+    West,
+    TL,
+    TR,
+    Select,
+    Start,
+    Mode,
+    ThumbL,
+    ThumbR,
+}
+
+impl PadButton {
+    pub const ALL: [PadButton; 11] = [
+        PadButton::South, PadButton::East, PadButton::North, PadButton::West, PadButton::TL, PadButton::TR,
+        PadButton::Select, PadButton::Start, PadButton::Mode, PadButton::ThumbL, PadButton::ThumbR,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PadButton::South => "south",
+            PadButton::East => "east",
+            PadButton::North => "north",
+            PadButton::West => "west",
+            PadButton::TL => "tl",
+            PadButton::TR => "tr",
+            PadButton::Select => "select",
+            PadButton::Start => "start",
+            PadButton::Mode => "mode",
+            PadButton::ThumbL => "thumbl",
+            PadButton::ThumbR => "thumbr",
+        }
+    }
+
+    fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// What drives a stick axis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AxisSource {
+    None,
+    /// Tracked point, relative to the neutral point.
+    HandX,
+    HandY,
+    /// Where the index finger points, relative to its direction at the neutral point.
+    FingerX,
+    FingerY,
+    /// Thumb-index closure, 0 (open) to full deflection (touching).
+    Pinch,
+}
+
+impl AxisSource {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "none" => AxisSource::None,
+            "hand_x" => AxisSource::HandX,
+            "hand_y" => AxisSource::HandY,
+            "finger_x" => AxisSource::FingerX,
+            "finger_y" => AxisSource::FingerY,
+            "pinch" => AxisSource::Pinch,
+            _ => return None,
+        })
+    }
+}
+
+/// What holds a button down.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ButtonSource {
+    /// The tracker's click gesture (thumb-index pinch, blink...).
+    Click,
+    /// Thumb on the middle or ring fingertip.
+    MiddlePinch,
+    RingPinch,
+    Pose(HandPose),
+}
+
+impl ButtonSource {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "click" | "pinch" => ButtonSource::Click,
+            "middle_pinch" => ButtonSource::MiddlePinch,
+            "ring_pinch" => ButtonSource::RingPinch,
+            "fist" => ButtonSource::Pose(HandPose::Fist),
+            "open" => ButtonSource::Pose(HandPose::Open),
+            "palm_away" => ButtonSource::Pose(HandPose::PalmAway),
+            _ => return None,
+        })
+    }
+}
+
+/// Mapping table of the virtual gamepad. Loaded from a TOML file; keys left
+/// out keep the defaults, a `[buttons]` table replaces the default buttons.
+///
+/// ```toml
+/// range = 0.15            # hand travel (fraction of the frame) for full deflection
+/// dead_zone = 0.1         # fraction of the stick radius that reads as centred
+/// mirror = true           # moving the hand to your right pushes the stick right
+/// recenter_on_entry = true
+///
+/// [axes]                  # hand_x, hand_y, finger_x, finger_y, pinch or none
+/// x = "hand_x"
+/// y = "hand_y"
+/// rx = "finger_x"
+/// ry = "finger_y"
+///
+/// [buttons]               # click, middle_pinch, ring_pinch, fist, open or palm_away
+/// south = "click"
+/// east = "middle_pinch"
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct GamepadConfig {
+    pub axes: [AxisSource; 4],
+    pub buttons: Vec<(PadButton, ButtonSource)>,
+    pub range: f32,
+    pub dead_zone: f32,
+    pub mirror: bool,
+    /// Take the neutral point from wherever the hand is when it (re)appears.
+    pub recenter_on_entry: bool,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self {
+            axes: [AxisSource::HandX, AxisSource::HandY, AxisSource::FingerX, AxisSource::FingerY],
+            // Fist is left out: it is the default clutch pose
+            buttons: vec![
+                (PadButton::South, ButtonSource::Click),
+                (PadButton::East, ButtonSource::MiddlePinch),
+                (PadButton::North, ButtonSource::RingPinch),
+                (PadButton::Start, ButtonSource::Pose(HandPose::PalmAway)),
+            ],
+            range: 0.15,
+            dead_zone: 0.1,
+            mirror: true,
+            recenter_on_entry: true,
+        }
+    }
+}
+
+fn invalid(msg: String) -> crate::AirLinkError {
+    crate::AirLinkError::CoreError(format!("Gamepad map: {}", msg))
+}
+
+impl GamepadConfig {
+    pub fn load(path: &str) -> Result<Self> {
+        Self::default().parse(&fs::read_to_string(path)?)
+    }
+
+    /// Overrides `self` with the keys set in a mapping TOML.
+    pub fn parse(mut self, text: &str) -> Result<Self> {
+        let table: Table = text.parse().map_err(|e| invalid(format!("{}", e)))?;
+        if let Some(key) = table.keys().find(|k| !["range", "dead_zone", "mirror", "recenter_on_entry", "axes", "buttons"].contains(&k.as_str())) {
+            return Err(invalid(format!("unknown key {}", key)));
+        }
+
+        if let Some(v) = table.get("range") {
+            self.range = float(v, "range")?;
+        }
+        if let Some(v) = table.get("dead_zone") {
+            self.dead_zone = float(v, "dead_zone")?;
+        }
+        if let Some(v) = table.get("mirror") {
+            self.mirror = v.as_bool().ok_or_else(|| invalid("mirror must be true or false".into()))?;
+        }
+        if let Some(v) = table.get("recenter_on_entry") {
+            self.recenter_on_entry = v.as_bool().ok_or_else(|| invalid("recenter_on_entry must be true or false".into()))?;
+        }
+        if self.range <= 0.0 {
+            return Err(invalid("range must be positive".into()));
+        }
+        if !(0.0..1.0).contains(&self.dead_zone) {
+            return Err(invalid("dead_zone must be in [0, 1)".into()));
+        }
+
+        if let Some(axes) = table.get("axes") {
+            let axes = axes.as_table().ok_or_else(|| invalid("[axes] must be a table".into()))?;
+            for (key, v) in axes {
+                let axis = Axis::ALL.iter().position(|a| a.name() == key)
+                    .ok_or_else(|| invalid(format!("unknown axis '{}' (x, y, rx or ry)", key)))?;
+                let name = string(v, &format!("axes.{}", key))?;
+                self.axes[axis] = AxisSource::parse(name)
+                    .ok_or_else(|| invalid(format!("unknown source '{}' for axes.{}", name, key)))?;
+            }
+        }
+
+        if let Some(buttons) = table.get("buttons") {
+            let buttons = buttons.as_table().ok_or_else(|| invalid("[buttons] must be a table".into()))?;
+            self.buttons = buttons.iter().map(|(key, v)| {
+                let button = PadButton::ALL.into_iter().find(|b| b.name() == key)
+                    .ok_or_else(|| invalid(format!("unknown button '{}'", key)))?;
+                let name = string(v, &format!("buttons.{}", key))?;
+                let source = ButtonSource::parse(name)
+                    .ok_or_else(|| invalid(format!("unknown source '{}' for buttons.{}", name, key)))?;
+                Ok((button, source))
+            }).collect::<Result<_>>()?;
+        }
+        Ok(self)
+    }
+}
+
+fn string<'a>(v: &'a Value, key: &str) -> Result<&'a str> {
+    v.as_str().ok_or_else(|| invalid(format!("{} must be a string", key)))
+}
+
+fn float(v: &Value, key: &str) -> Result<f32> {
+    v.as_float().or_else(|| v.as_integer().map(|i| i as f64)).map(|f| f as f32)
+        .ok_or_else(|| invalid(format!("{} must be a number", key)))
+}
+
+/// One tracked frame, as the gamepad sees it.
+pub struct PadInput<'a> {
+    /// Smoothed tracked point, frame-normalized.
+    pub pointer: (f32, f32),
+    pub click: bool,
+    pub pose: HandPose,
+    /// Hand landmarks, empty for trackers without hands.
+    pub landmarks: &'a [(f32, f32, f32)],
+}
+
+/// Stick positions (`Axis` order) and pressed buttons.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PadState {
+    pub axes: [i32; 4],
+    buttons: u16,
+}
+
+impl PadState {
+    pub fn pressed(&self, button: PadButton) -> bool {
+        self.buttons & button.bit() != 0
+    }
+
+    fn press(&mut self, button: PadButton) {
+        self.buttons |= button.bit();
+    }
+}
+
+/// Where the hand rested when the sticks were last centred.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Neutral {
+    hand: (f32, f32),
+    finger: Option<(f32, f32)>,
+}
+
+/// [`landmarks::hand_size`] of a hand with every finger this mapper reads.
+fn hand_size(lm: &[(f32, f32, f32)]) -> Option<f32> {
+    landmarks::hand_size(lm).filter(|_| lm.len() > RING_TIP)
+}
+
+/// Index knuckle to fingertip, in hand sizes.
+fn finger_direction(lm: &[(f32, f32, f32)]) -> Option<(f32, f32)> {
+    let size = hand_size(lm)?;
+    let (tip, knuckle) = (lm[INDEX_TIP], lm[INDEX_MCP]);
+    Some(((tip.0 - knuckle.0) / size, (tip.1 - knuckle.1) / size))
+}
+
+/// Thumb on `tip`; buttons read one frame at a time, so without hysteresis.
+fn pinched(lm: &[(f32, f32, f32)], tip: usize) -> bool {
+    hand_size(lm).is_some_and(|size| distance(lm[THUMB_TIP], lm[tip]) < Pinch::CLOSE * size)
+}
+
+/// Shrinks the dead zone to zero and rescales the rest of the stick's travel.
+fn dead_zone(stick: (f32, f32), dead_zone: f32) -> (f32, f32) {
+    let length = (stick.0 * stick.0 + stick.1 * stick.1).sqrt();
+    if length <= dead_zone {
+        return (0.0, 0.0);
+    }
+    let scale = ((length - dead_zone) / (1.0 - dead_zone)).min(1.0) / length;
+    (stick.0 * scale, stick.1 * scale)
+}
+
+fn to_axis(v: f32) -> i32 {
+    (v.clamp(-1.0, 1.0) * AXIS_MAX as f32).round() as i32
+}
+
+/// Turns tracked frames into gamepad states through a [`GamepadConfig`].
+pub struct GamepadMapper {
+    config: GamepadConfig,
+    neutral: Option<Neutral>,
+}
+
+impl GamepadMapper {
+    pub fn new(config: GamepadConfig) -> Self {
+        Self { config, neutral: None }
+    }
+
+    pub fn config(&self) -> &GamepadConfig {
+        &self.config
+    }
+
+    /// Takes the neutral point from the next frame.
+    pub fn recenter(&mut self) {
+        self.neutral = None;
+    }
+
+    /// The hand left (or control was released): recentre on its return, if configured.
+    pub fn hand_lost(&mut self) {
+        if self.config.recenter_on_entry {
+            self.neutral = None;
+        }
+    }
+
+    pub fn update(&mut self, input: &PadInput) -> PadState {
+        let finger = finger_direction(input.landmarks);
+        let neutral = *self.neutral.get_or_insert(Neutral { hand: input.pointer, finger });
+        // Hands seen only after the neutral point was taken centre their finger now
+        let finger_neutral = match (neutral.finger, finger) {
+            (None, Some(f)) => {
+                self.neutral = Some(Neutral { finger: Some(f), ..neutral });
+                Some(f)
+            }
+            (n, _) => n,
+        };
+
+        let flip = if self.config.mirror { -1.0 } else { 1.0 };
+        let finger_offset = finger.zip(finger_neutral).map(|(f, n)| ((f.0 - n.0) / FINGER_RANGE, (f.1 - n.1) / FINGER_RANGE));
+        let pinch = hand_size(input.landmarks).map(|size| {
+            1.0 - distance(input.landmarks[THUMB_TIP], input.landmarks[INDEX_TIP]) / size
+        });
+
+        let raw = self.config.axes.map(|source| match source {
+            AxisSource::None => 0.0,
+            AxisSource::HandX => flip * (input.pointer.0 - neutral.hand.0) / self.config.range,
+            AxisSource::HandY => (input.pointer.1 - neutral.hand.1) / self.config.range,
+            AxisSource::FingerX => finger_offset.map_or(0.0, |f| flip * f.0),
+            AxisSource::FingerY => finger_offset.map_or(0.0, |f| f.1),
+            AxisSource::Pinch => pinch.unwrap_or(0.0).clamp(0.0, 1.0),
+        });
+        let left = dead_zone((raw[0], raw[1]), self.config.dead_zone);
+        let right = dead_zone((raw[2], raw[3]), self.config.dead_zone);
+
+        let mut state = PadState { axes: [left.0, left.1, right.0, right.1].map(to_axis), buttons: 0 };
+        for &(button, source) in &self.config.buttons {
+            let down = match source {
+                ButtonSource::Click => input.click,
+                ButtonSource::MiddlePinch => pinched(input.landmarks, MIDDLE_TIP),
+                ButtonSource::RingPinch => pinched(input.landmarks, RING_TIP),
+                ButtonSource::Pose(pose) => input.pose == pose,
+            };
+            if down {
+                state.press(button);
+            }
+        }
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::test_hand;
+
+    fn input(pointer: (f32, f32), landmarks: &[(f32, f32, f32)]) -> PadInput<'_> {
+        PadInput { pointer, click: false, pose: HandPose::Open, landmarks }
+    }
+
+    #[test]
+    fn test_sticks_follow_the_neutral_point() {
+        let mut pad = GamepadMapper::new(GamepadConfig::default());
+        // First sighting is the neutral point
+        assert_eq!(pad.update(&input((0.5, 0.5), &[])).axes, [0; 4]);
+        // Inside the dead zone
+        assert_eq!(pad.update(&input((0.49, 0.5), &[])).axes, [0; 4]);
+
+        // Mirrored: a lower x is the user's right. Past the range the stick saturates.
+        let state = pad.update(&input((0.3, 0.5), &[]));
+        assert_eq!(state.axes, [AXIS_MAX, 0, 0, 0]);
+        // Halfway through the live part of the travel
+        let half = 0.15 * (0.1 + 0.9 * 0.5);
+        let state = pad.update(&input((0.5, 0.5 - half), &[]));
+        assert!((state.axes[1] + AXIS_MAX / 2).abs() < 50, "{:?}", state.axes);
+
+        // Recentring takes the next frame as the new rest position
+        pad.recenter();
+        assert_eq!(pad.update(&input((0.3, 0.5), &[])).axes, [0; 4]);
+        pad.hand_lost();
+        assert_eq!(pad.update(&input((0.7, 0.2), &[])).axes, [0; 4]);
+    }
+
+    #[test]
+    fn test_mapping_table() {
+        let config = GamepadConfig::default().parse(r#"
+            mirror = false
+            [axes]
+            x = "none"
+            rx = "pinch"
+            [buttons]
+            west = "click"
+            tr = "palm_away"
+        "#).unwrap();
+        assert_eq!(config.axes, [AxisSource::None, AxisSource::HandY, AxisSource::Pinch, AxisSource::FingerY]);
+        assert_eq!(config.buttons.len(), 2);
+
+        // Thumb and index tips touching
+        let mut hand = test_hand(0.45, 0.0);
+        hand[INDEX_MCP] = (0.45, 0.3, 0.0);
+        hand[MIDDLE_TIP] = (0.5, 0.1, 0.0);
+        hand[RING_TIP] = (0.55, 0.12, 0.0);
+
+        let mut pad = GamepadMapper::new(config);
+        let state = pad.update(&PadInput { click: true, ..input((0.5, 0.5), &hand) });
+        assert_eq!(state.axes[0], 0);
+        assert_eq!(state.axes[2], AXIS_MAX);
+        assert!(state.pressed(PadButton::West) && !state.pressed(PadButton::South) && !state.pressed(PadButton::TR));
+
+        let state = pad.update(&PadInput { pose: HandPose::PalmAway, ..input((0.5, 0.5), &hand) });
+        assert!(state.pressed(PadButton::TR) && !state.pressed(PadButton::West));
+
+        assert!(GamepadConfig::default().parse("[axes]\nz = \"hand_x\"").is_err());
+        assert!(GamepadConfig::default().parse("[buttons]\nsouth = \"wave\"").is_err());
+        assert!(GamepadConfig::default().parse("dead_zone = 1.5").is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::core::landmarks::{INDEX_MCP, PINKY_MCP, WRIST};
+use crate::core::vision::{HandResult, Handedness};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Coarse hand poses derived from the 21 landmarks.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HandPose {
+    Open,
+    Fist,
+    PalmAway,
+    Unknown,
+}
+
+/// (tip, pip) pairs for index, middle, ring and pinky.
+const FINGERS: [(usize, usize); 4] = [(8, 6), (12, 10), (16, 14), (20, 18)];
+
+fn dist_sq(a: (f32, f32, f32), b: (f32, f32, f32)) -> f32 {
+    (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)
+}
+
+pub fn classify_pose(hand: &HandResult) -> HandPose {
+    let lm = &hand.landmarks;
+    if lm.len() < 21 {
+        return HandPose::Unknown;
+    }
+
+    // A finger is curled when its tip sits closer to the wrist than its PIP joint
+    let curled = FINGERS
+        .iter()
+        .filter(|&&(tip, pip)| dist_sq(lm[tip], lm[WRIST]) < dist_sq(lm[pip], lm[WRIST]))
+        .count();
+    if curled >= 3 {
+        return HandPose::Fist;
+    }
+
+    // Winding of wrist -> index MCP -> pinky MCP flips when the hand turns around.
+    // The model labels hands as if the frame were mirrored, so a "Right" label on
+    // our raw frame is a left hand, whose palm faces the camera with a positive cross.
+    if let Some(handedness) = hand.handedness {
+        let (w, i, p) = (lm[WRIST], lm[INDEX_MCP], lm[PINKY_MCP]);
+        let cross = (i.0 - w.0) * (p.1 - w.1) - (i.1 - w.1) * (p.0 - w.0);
+        let palm_facing = match handedness {
+            Handedness::Right => cross > 0.0,
+            Handedness::Left => cross < 0.0,
+        };
+        if !palm_facing {
+            return HandPose::PalmAway;
+        }
+    }
+
+    HandPose::Open
+}
+
+/// Horizontal swipe direction, from the user's point of view.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Swipe {
+    Left,
+    Right,
+}
+
+/// Detects a fast horizontal flick of the tracked point.
+pub struct SwipeDetector {
+    history: VecDeque<(Instant, f32)>,
+    cooldown_until: Option<Instant>,
+}
+
+impl SwipeDetector {
+    /// Sweep across this fraction of the frame...
+    const DISTANCE: f32 = 0.3;
+    /// ...within this time to count as a swipe.
+    const WINDOW: Duration = Duration::from_millis(250);
+    const COOLDOWN: Duration = Duration::from_millis(800);
+
+    pub fn new() -> Self {
+        Self {
+            history: VecDeque::new(),
+            cooldown_until: None,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.history.clear();
+    }
+
+    /// Feed the frame-normalized x of the tracked point.
+    pub fn update(&mut self, x: f32, now: Instant) -> Option<Swipe> {
+        while self.history.front().is_some_and(|(t, _)| now.duration_since(*t) > Self::WINDOW) {
+            self.history.pop_front();
+        }
+        self.history.push_back((now, x));
+
+        if self.cooldown_until.is_some_and(|t| now < t) {
+            return None;
+        }
+
+        let (_, start) = *self.history.front()?;
+        let dx = x - start;
+        if dx.abs() < Self::DISTANCE {
+            return None;
+        }
+
+        self.history.clear();
+        self.cooldown_until = Some(now + Self::COOLDOWN);
+        // The camera is not mirrored: moving to the user's right lowers x
+        Some(if dx < 0.0 { Swipe::Right } else { Swipe::Left })
+    }
+}
+
+impl Default for SwipeDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wrist at the bottom, fingers pointing up. `index_x` places the index
+    /// MCP (the pinky MCP mirrors it), which sets which way the palm faces.
+    fn hand(curled: bool, index_x: f32, handedness: Option<Handedness>) -> HandResult {
+        let mut landmarks = vec![(0.5, 0.5, 0.0); 21];
+        landmarks[WRIST] = (0.5, 0.9, 0.0);
+        landmarks[INDEX_MCP] = (index_x, 0.7, 0.0);
+        landmarks[PINKY_MCP] = (1.0 - index_x, 0.7, 0.0);
+        for (tip, pip) in FINGERS {
+            landmarks[pip] = (0.5, 0.6, 0.0);
+            landmarks[tip] = (0.5, if curled { 0.75 } else { 0.4 }, 0.0);
+        }
+        HandResult { index_tip: (0.5, 0.4), thumb_tip: (0.4, 0.6), landmarks, handedness }
+    }
+
+    #[test]
+    fn test_classify_pose() {
+        assert_eq!(classify_pose(&hand(false, 0.4, None)), HandPose::Open);
+        assert_eq!(classify_pose(&hand(true, 0.4, None)), HandPose::Fist);
+
+        // A "Right" label faces the camera with index MCP on the left of the frame
+        assert_eq!(classify_pose(&hand(false, 0.4, Some(Handedness::Right))), HandPose::Open);
+        assert_eq!(classify_pose(&hand(false, 0.6, Some(Handedness::Right))), HandPose::PalmAway);
+        assert_eq!(classify_pose(&hand(false, 0.6, Some(Handedness::Left))), HandPose::Open);
+        assert_eq!(classify_pose(&hand(false, 0.4, Some(Handedness::Left))), HandPose::PalmAway);
+        // A fist wins over the palm direction
+        assert_eq!(classify_pose(&hand(true, 0.6, Some(Handedness::Right))), HandPose::Fist);
+
+        let mut partial = hand(false, 0.4, None);
+        partial.landmarks.truncate(20);
+        assert_eq!(classify_pose(&partial), HandPose::Unknown);
+    }
+
+    /// Feeds `(ms, x)` samples and returns every swipe with the time it fired.
+    fn swipes(detector: &mut SwipeDetector, start: Instant, samples: &[(u64, f32)]) -> Vec<(u64, Swipe)> {
+        samples
+            .iter()
+            .filter_map(|&(ms, x)| {
+                detector.update(x, start + Duration::from_millis(ms)).map(|swipe| (ms, swipe))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_swipe_direction() {
+        let start = Instant::now();
+        // Falling x is the user's right on an unmirrored camera
+        let mut detector = SwipeDetector::new();
+        assert_eq!(swipes(&mut detector, start, &[(0, 0.8), (50, 0.65), (100, 0.45)]), [(100, Swipe::Right)]);
+
+        let mut detector = SwipeDetector::new();
+        assert_eq!(swipes(&mut detector, start, &[(0, 0.2), (50, 0.35), (100, 0.55)]), [(100, Swipe::Left)]);
+    }
+
+    #[test]
+    fn test_swipe_needs_distance_and_speed() {
+        let start = Instant::now();
+
+        // Fast but short
+        let mut detector = SwipeDetector::new();
+        assert!(swipes(&mut detector, start, &[(0, 0.5), (50, 0.6), (100, 0.7)]).is_empty());
+
+        // Far but slow: 0.35 over 500 ms never covers 0.3 within one window
+        let mut detector = SwipeDetector::new();
+        let slow: Vec<_> = (0..=10).map(|i| (i * 50, 0.8 - 0.035 * i as f32)).collect();
+        assert!(swipes(&mut detector, start, &slow).is_empty());
+    }
+
+    #[test]
+    fn test_swipe_cooldown() {
+        let start = Instant::now();
+        let mut detector = SwipeDetector::new();
+        let samples = [
+            (0, 0.8), (100, 0.45),
+            // Swinging back inside the cooldown does not fire a Left swipe
+            (200, 0.6), (300, 0.8),
+            // After the cooldown a new flick fires again
+            (1000, 0.8), (1050, 0.6), (1100, 0.45),
+        ];
+        assert_eq!(swipes(&mut detector, start, &samples), [(100, Swipe::Right), (1100, Swipe::Right)]);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::core::frame::{Frame, PixelFormat};
+use crate::core::preprocess::decode_jpeg;
+use crate::Result;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug)]
+pub struct IdleConfig {
+    /// Time without a hand before dropping to idle.
+    pub after: Duration,
+    /// Time between motion checks while idle.
+    pub interval: Duration,
+    /// Mean brightness change (0..1) of the thumbnail cells between checks
+    /// that counts as motion.
+    pub threshold: f32,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            after: Duration::from_secs(10),
+            interval: Duration::from_millis(200),
+            threshold: 0.02,
+        }
+    }
+}
+
+/// Transitions worth reporting.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum IdleEvent {
+    /// No hand for `after`; full tracking paused.
+    Sleep,
+    /// First hand after waking, `latency` after the last still check.
+    Resumed { latency: Duration },
+}
+
+/// Drops the pipeline to a cheap motion check when nobody is in front of the
+/// camera. Each check compares a tiny thumbnail against the previous one;
+/// motion brings back full-rate tracking.
+pub struct IdleScheduler {
+    config: IdleConfig,
+    thumbnail: Vec<f32>,
+    /// Decoded MJPEG pixels, reused across checks.
+    scratch: Vec<u8>,
+    previous: Vec<f32>,
+    idle: bool,
+    /// Last hand (or wake-up) while active.
+    last_seen: Option<Instant>,
+    last_check: Option<Instant>,
+    /// Last idle check that saw no motion; the hand appeared after it.
+    last_still: Option<Instant>,
+    /// Set on wake until the first hand, for the latency report.
+    woke_from: Option<Instant>,
+}
+
+impl IdleScheduler {
+    const THUMBNAIL: u32 = 32;
+
+    pub fn new(config: IdleConfig) -> Self {
+        Self {
+            config,
+            thumbnail: Vec::new(),
+            scratch: Vec::new(),
+            previous: Vec::new(),
+            idle: false,
+            last_seen: None,
+            last_check: None,
+            last_still: None,
+            woke_from: None,
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// Whether `frame` should go through full tracking. While idle this is a
+    /// motion check at most once per `interval`; motion wakes the scheduler.
+    pub fn admit(&mut self, frame: &Frame, now: Instant) -> Result<bool> {
+        if !self.idle {
+            return Ok(true);
+        }
+        if self.last_check.is_some_and(|t| now.duration_since(t) < self.config.interval) {
+            return Ok(false);
+        }
+        self.last_check = Some(now);
+
+        box_thumbnail(frame, Self::THUMBNAIL as usize, &mut self.scratch, &mut self.thumbnail)?;
+        let current = &self.thumbnail;
+        // The first check after falling asleep has nothing to compare against
+        let motion = if self.previous.len() == current.len() {
+            current.iter().zip(&self.previous).map(|(a, b)| (a - b).abs()).sum::<f32>() / current.len() as f32
+        } else {
+            0.0
+        };
+        self.previous.clear();
+        self.previous.extend_from_slice(current);
+
+        if motion <= self.config.threshold {
+            self.last_still = Some(now);
+            return Ok(false);
+        }
+
+        self.idle = false;
+        self.last_seen = Some(now);
+        self.woke_from = Some(self.last_still.unwrap_or(now));
+        Ok(true)
+    }
+
+    /// Feeds back the tracking result of an admitted frame.
+    pub fn observe(&mut self, hand_present: bool, now: Instant) -> Option<IdleEvent> {
+        if hand_present {
+            self.last_seen = Some(now);
+            return self.woke_from.take().map(|from| IdleEvent::Resumed { latency: now.duration_since(from) });
+        }
+
+        let last_seen = *self.last_seen.get_or_insert(now);
+        if self.idle || now.duration_since(last_seen) < self.config.after {
+            return None;
+        }
+        self.idle = true;
+        self.previous.clear();
+        self.last_check = None;
+        self.last_still = None;
+        self.woke_from = None;
+        Some(IdleEvent::Sleep)
+    }
+
+    /// How long the loop can sleep before the next motion check; `None` while active.
+    pub fn next_check(&self, now: Instant) -> Option<Duration> {
+        if !self.idle {
+            return None;
+        }
+        let due = self.last_check.map_or(now, |t| t + self.config.interval);
+        Some(due.saturating_duration_since(now))
+    }
+}
+
+/// Mean luma (0..1) of each cell of a `size × size` grid stretched over the
+/// frame. Every pixel counts toward its cell, so small motion moves a mean
+/// instead of slipping between sample points, and sensor noise averages out.
+fn box_thumbnail(frame: &Frame, size: usize, scratch: &mut Vec<u8>, out: &mut Vec<f32>) -> Result<()> {
+    frame.check()?;
+    let (data, width, height, format) = match frame.format {
+        PixelFormat::Mjpeg => {
+            let (width, height, format) = decode_jpeg(&frame.data, scratch)?;
+            (&scratch[..], width, height, format)
+        }
+        format => (&frame.data[..], frame.width as usize, frame.height as usize, format),
+    };
+    if width == 0 || height == 0 {
+        return Err(crate::AirLinkError::CoreError("Empty frame".into()));
+    }
+    // Bytes per pixel and where the luma (or the channels to average) sits
+    let (stride, channels) = match format {
+        PixelFormat::Rgb | PixelFormat::Bgr => (3, 3),
+        PixelFormat::Yuyv => (2, 1),
+        PixelFormat::Gray | PixelFormat::Nv12 | PixelFormat::Mjpeg => (1, 1),
+    };
+
+    out.clear();
+    out.resize(size * size, 0.0);
+    let columns: Vec<usize> = (0..width).map(|x| x * size / width).collect();
+    for (y, row) in data[..width * height * stride].chunks_exact(width * stride).enumerate() {
+        let cells = &mut out[y * size / height * size..][..size];
+        for (pixel, &cell) in row.chunks_exact(stride).zip(&columns) {
+            cells[cell] += pixel[..channels].iter().map(|&v| v as f32).sum::<f32>();
+        }
+    }
+    // Cells differ by at most one pixel per axis, so each gets its own count
+    let count = |n: usize, i: usize| ((i + 1) * n).div_ceil(size) - (i * n).div_ceil(size);
+    for (i, v) in out.iter_mut().enumerate() {
+        let pixels = count(width, i % size) * count(height, i / size) * channels;
+        *v /= pixels.max(1) as f32 * 255.0;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::frame::PixelFormat;
+
+    fn gray(level: u8) -> Frame {
+        Frame::new(vec![level; 64 * 48], 64, 48, PixelFormat::Gray)
+    }
+
+    #[test]
+    fn test_sleeps_without_hand_and_wakes_on_motion() {
+        let config = IdleConfig { after: Duration::from_secs(2), interval: Duration::from_millis(200), threshold: 0.02 };
+        let mut idle = IdleScheduler::new(config);
+        let t0 = Instant::now();
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+
+        assert_eq!(idle.observe(true, at(0)), None);
+        assert_eq!(idle.observe(false, at(1000)), None);
+        assert_eq!(idle.observe(false, at(2000)), Some(IdleEvent::Sleep));
+        assert_eq!(idle.next_check(at(2000)), Some(Duration::ZERO));
+
+        // Only one frame per interval is compared with the previous check. The
+        // 90 between the two 50s is never looked at, so no motion is seen
+        assert!(!idle.admit(&gray(50), at(2000)).unwrap());
+        assert!(!idle.admit(&gray(90), at(2100)).unwrap(), "between checks");
+        assert_eq!(idle.next_check(at(2100)), Some(Duration::from_millis(100)));
+        assert!(!idle.admit(&gray(50), at(2200)).unwrap());
+
+        // Motion wakes it; latency runs from the last still check to the hand
+        assert!(idle.admit(&gray(90), at(2400)).unwrap());
+        assert!(!idle.is_idle());
+        assert!(idle.admit(&gray(90), at(2410)).unwrap());
+        assert_eq!(idle.observe(false, at(2410)), None);
+        assert_eq!(idle.observe(true, at(2500)), Some(IdleEvent::Resumed { latency: Duration::from_millis(300) }));
+    }
+
+    #[test]
+    fn test_thumbnail_averages_every_pixel() {
+        let (mut scratch, mut out) = (Vec::new(), Vec::new());
+        // Stripes finer than a cell: point samples would land on one phase or the other
+        let stripes = (0..96 * 32).map(|i| if i % 3 == 2 { 255 } else { 0 }).collect();
+        box_thumbnail(&Frame::new(stripes, 96, 32, PixelFormat::Gray), 32, &mut scratch, &mut out).unwrap();
+        assert!(out.iter().all(|&v| (v - 1.0 / 3.0).abs() < 1e-5), "{:?}", out);
+
+        // Uneven cells (100 over 32) are still true means
+        box_thumbnail(&Frame::new([200, 0, 0].repeat(100 * 50), 100, 50, PixelFormat::Rgb), 32, &mut scratch, &mut out).unwrap();
+        assert!(out.iter().all(|&v| (v - 200.0 / 3.0 / 255.0).abs() < 1e-5));
+    }
+}
@@ -1,44 +1,127 @@
 use uinput::event::controller::Controller::Mouse;
-use uinput::event::controller::Mouse::Left;
+use uinput::event::controller::Mouse as MouseButton;
 use uinput::event::relative::Relative::Position;
 use uinput::event::relative::Position::{X, Y};
 use uinput::event::Event::{Controller, Relative};
+use crate::core::gamepad::{self, Axis, GamepadConfig, GamepadMapper, PadButton, PadInput, PadState};
+use crate::core::pen::{self, PenSample, PenTracker, PressureSource};
+use crate::core::remote::RemoteSender;
+use crate::core::touch::{self, Contact, TouchTest, TouchTracker};
 use crate::Result;
+use std::time::Duration;
 use tracing::info;
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Button {
+    Left,
+    Right,
+}
+
+impl Button {
+    fn code(self) -> MouseButton {
+        match self {
+            Button::Left => MouseButton::Left,
+            Button::Right => MouseButton::Right,
+        }
+    }
+}
+
+/// One `MouseManager` call, as carried to a remote `serve-input`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PointerEvent {
+    /// Absolute screen target; the device side turns it into relative motion.
+    Move { x: i32, y: i32 },
+    Press(Button),
+    Release(Button),
+    Click(Button),
+    /// Release the buttons and start the next move fresh.
+    Reset,
+}
+
+enum Backend {
+    Uinput(uinput::Device),
+    /// Another machine's `serve-input` owns the device.
+    Remote(RemoteSender),
+    /// The pipeline runs, the system cursor stays put.
+    Detached,
+}
+
 pub struct MouseManager {
-    device: uinput::Device,
+    backend: Backend,
     last_x: i32,
     last_y: i32,
     is_initialized: bool,
+    left_down: bool,
 }
 
 impl MouseManager {
+    /// Pause between the two clicks of a double click.
+    pub const DOUBLE_CLICK_GAP: Duration = Duration::from_millis(40);
+
     pub fn new() -> Result<Self> {
         info!("Initializing Native uinput mouse...");
         
         let device = uinput::default()
-            .map_err(|e| crate::AirLinkError::CoreError(format!("uinput default failed: {}", e)))?
+            .map_err(uinput_err("default"))?
             .name("Air-Link Virtual Mouse")
-            .map_err(|e| crate::AirLinkError::CoreError(format!("uinput name failed: {}", e)))?
-            .event(Controller(Mouse(Left)))
-            .map_err(|e| crate::AirLinkError::CoreError(format!("uinput event failed: {}", e)))?
+            .map_err(uinput_err("name"))?
+            .event(Controller(Mouse(MouseButton::Left)))
+            .map_err(uinput_err("event"))?
+            .event(Controller(Mouse(MouseButton::Right)))
+            .map_err(uinput_err("event"))?
             .event(Relative(Position(X)))
-            .map_err(|e| crate::AirLinkError::CoreError(format!("uinput event failed: {}", e)))?
+            .map_err(uinput_err("event"))?
             .event(Relative(Position(Y)))
-            .map_err(|e| crate::AirLinkError::CoreError(format!("uinput event failed: {}", e)))?
+            .map_err(uinput_err("event"))?
             .create()
-            .map_err(|e| crate::AirLinkError::CoreError(format!("uinput create failed: {}", e)))?;
+            .map_err(uinput_err("create"))?;
+
+        Ok(Self::with_backend(Backend::Uinput(device)))
+    }
+
+    /// A mouse that tracks positions and buttons but sends nothing, for
+    /// running with other outputs (OSC, streaming) only. Needs no uinput access.
+    pub fn detached() -> Self {
+        Self::with_backend(Backend::Detached)
+    }
 
-        Ok(Self { 
-            device,
+    /// Forwards every call to a `serve-input` receiver on another machine.
+    pub fn remote(sender: RemoteSender) -> Self {
+        Self::with_backend(Backend::Remote(sender))
+    }
+
+    fn with_backend(backend: Backend) -> Self {
+        Self {
+            backend,
             last_x: 0,
             last_y: 0,
             is_initialized: false,
-        })
+            left_down: false,
+        }
+    }
+
+    pub fn is_attached(&self) -> bool {
+        !matches!(self.backend, Backend::Detached)
+    }
+
+    /// Replays one event, as received by `serve-input`.
+    pub fn apply(&mut self, event: PointerEvent) -> Result<()> {
+        match event {
+            PointerEvent::Move { x, y } => self.move_to(x, y),
+            PointerEvent::Press(Button::Left) => self.press_left(),
+            PointerEvent::Release(Button::Left) => self.release_left(),
+            PointerEvent::Press(button) => self.send_button(button, 1),
+            PointerEvent::Release(button) => self.send_button(button, 0),
+            PointerEvent::Click(button) => self.click(button),
+            PointerEvent::Reset => self.reset(),
+        }
     }
 
     pub fn move_to(&mut self, x: i32, y: i32) -> Result<()> {
+        if let Backend::Remote(sender) = &mut self.backend {
+            // Absolute, so a lost packet is repaired by the next one
+            return sender.send(PointerEvent::Move { x, y });
+        }
         if !self.is_initialized {
             self.last_x = x;
             self.last_y = y;
@@ -51,15 +134,17 @@ impl MouseManager {
         let dy = y - self.last_y;
 
         // Only move if there is a significant change to avoid jitter
-        if dx.abs() > 0 || dy.abs() > 0 {
+        if let Backend::Uinput(device) = &mut self.backend
+            && (dx.abs() > 0 || dy.abs() > 0)
+        {
             // Send relative movement to OS
-            self.device.send(Relative(Position(X)), dx)
+            device.send(Relative(Position(X)), dx)
                 .map_err(|e| crate::AirLinkError::CoreError(format!("uinput send X failed: {}", e)))?;
-            self.device.send(Relative(Position(Y)), dy)
+            device.send(Relative(Position(Y)), dy)
                 .map_err(|e| crate::AirLinkError::CoreError(format!("uinput send Y failed: {}", e)))?;
             
-            self.device.synchronize()
-                .map_err(|e| crate::AirLinkError::CoreError(format!("uinput sync failed: {}", e)))?;
+            device.synchronize()
+                .map_err(uinput_err("sync"))?;
         }
 
         self.last_x = x;
@@ -69,16 +154,345 @@ impl MouseManager {
     }
 
     pub fn click_left(&mut self) -> Result<()> {
-        self.device.send(Controller(Mouse(Left)), 1)
-            .map_err(|e| crate::AirLinkError::CoreError(format!("Click down failed: {}", e)))?;
-        self.device.synchronize()
-            .map_err(|e| crate::AirLinkError::CoreError(format!("Sync failed: {}", e)))?;
-            
-        self.device.send(Controller(Mouse(Left)), 0)
-            .map_err(|e| crate::AirLinkError::CoreError(format!("Click up failed: {}", e)))?;
-        self.device.synchronize()
+        self.click(Button::Left)
+    }
+
+    pub fn click_right(&mut self) -> Result<()> {
+        self.click(Button::Right)
+    }
+
+    /// Two clicks [`MouseManager::DOUBLE_CLICK_GAP`] apart; back to back,
+    /// many desktops drop the second or merge the two.
+    pub fn double_click_left(&mut self) -> Result<()> {
+        self.click(Button::Left)?;
+        std::thread::sleep(Self::DOUBLE_CLICK_GAP);
+        self.click(Button::Left)
+    }
+
+    /// Holds the left button down (start of a drag).
+    pub fn press_left(&mut self) -> Result<()> {
+        self.send_button(Button::Left, 1)?;
+        self.left_down = true;
+        Ok(())
+    }
+
+    /// Lets go of the left button (end of a drag).
+    pub fn release_left(&mut self) -> Result<()> {
+        self.send_button(Button::Left, 0)?;
+        self.left_down = false;
+        Ok(())
+    }
+
+    pub fn is_left_down(&self) -> bool {
+        self.left_down
+    }
+
+    fn click(&mut self, button: Button) -> Result<()> {
+        // A click's release ends any drag in progress
+        if button == Button::Left {
+            self.left_down = false;
+        }
+        if let Backend::Remote(sender) = &mut self.backend {
+            return sender.send(PointerEvent::Click(button));
+        }
+        self.send_button(button, 1)?;
+        self.send_button(button, 0)
+    }
+
+    fn send_button(&mut self, button: Button, value: i32) -> Result<()> {
+        let device = match &mut self.backend {
+            Backend::Uinput(device) => device,
+            Backend::Remote(sender) => {
+                let event = if value != 0 { PointerEvent::Press(button) } else { PointerEvent::Release(button) };
+                return sender.send(event);
+            }
+            Backend::Detached => return Ok(()),
+        };
+        device.send(Controller(Mouse(button.code())), value)
+            .map_err(|e| crate::AirLinkError::CoreError(format!("Button {:?} send failed: {}", button, e)))?;
+        device.synchronize()
             .map_err(|e| crate::AirLinkError::CoreError(format!("Sync failed: {}", e)))?;
-            
         Ok(())
     }
+
+    /// Releases buttons and forgets the last position, so the next move starts fresh.
+    pub fn reset(&mut self) -> Result<()> {
+        self.is_initialized = false;
+        if let Backend::Remote(sender) = &mut self.backend {
+            self.left_down = false;
+            return sender.send(PointerEvent::Reset);
+        }
+        self.release_left()
+    }
+}
+
+/// `map_err` adapter for uinput calls, e.g. `.map_err(uinput_err("create"))`.
+fn uinput_err(what: &'static str) -> impl Fn(uinput::Error) -> crate::AirLinkError + Copy {
+    move |e| crate::AirLinkError::CoreError(format!("uinput {} failed: {}", what, e))
+}
+
+fn pad_axis(axis: Axis) -> uinput::event::absolute::Position {
+    use uinput::event::absolute::Position as Abs;
+    match axis {
+        Axis::X => Abs::X,
+        Axis::Y => Abs::Y,
+        Axis::RX => Abs::RX,
+        Axis::RY => Abs::RY,
+    }
+}
+
+fn pad_button(button: PadButton) -> uinput::event::controller::GamePad {
+    use uinput::event::controller::GamePad;
+    match button {
+        PadButton::South => GamePad::South,
+        PadButton::East => GamePad::East,
+        PadButton::North => GamePad::North,
+        PadButton::West => GamePad::West,
+        PadButton::TL => GamePad::TL,
+        PadButton::TR => GamePad::TR,
+        PadButton::Select => GamePad::Select,
+        PadButton::Start => GamePad::Start,
+        PadButton::Mode => GamePad::Mode,
+        PadButton::ThumbL => GamePad::ThumbL,
+        PadButton::ThumbR => GamePad::ThumbR,
+    }
+}
+
+/// Virtual gamepad: two sticks (`ABS_X/Y`, `ABS_RX/RY`) and the `BTN_SOUTH`
+/// family, driven by the hand through a [`GamepadMapper`].
+pub struct GamepadManager {
+    device: uinput::Device,
+    pub mapper: GamepadMapper,
+    /// Last state sent, so only changes go out.
+    state: PadState,
+}
+
+impl GamepadManager {
+    pub fn new(config: GamepadConfig) -> Result<Self> {
+        info!("Initializing Native uinput gamepad...");
+
+        let mut builder = uinput::default().map_err(uinput_err("default"))?
+            .name("Air-Link Virtual Gamepad").map_err(uinput_err("name"))?;
+        for axis in Axis::ALL {
+            builder = builder.event(pad_axis(axis)).map_err(uinput_err("event"))?
+                .min(gamepad::AXIS_MIN).max(gamepad::AXIS_MAX).flat(0);
+        }
+        for button in PadButton::ALL {
+            builder = builder.event(pad_button(button)).map_err(uinput_err("event"))?;
+        }
+        let device = builder.create().map_err(uinput_err("create"))?;
+
+        Ok(Self { device, mapper: GamepadMapper::new(config), state: PadState::default() })
+    }
+
+    /// Maps one tracked frame and sends whatever changed.
+    pub fn update(&mut self, input: &PadInput) -> Result<()> {
+        let state = self.mapper.update(input);
+        self.send(state)
+    }
+
+    /// Centres the sticks and lets go of every button, keeping the neutral point.
+    pub fn release(&mut self) -> Result<()> {
+        self.send(PadState::default())
+    }
+
+    /// Releases everything; the neutral point is retaken when the hand comes back.
+    pub fn hand_lost(&mut self) -> Result<()> {
+        self.mapper.hand_lost();
+        self.release()
+    }
+
+    fn send(&mut self, state: PadState) -> Result<()> {
+        if state == self.state {
+            return Ok(());
+        }
+        let err = uinput_err("gamepad send");
+        for (i, axis) in Axis::ALL.into_iter().enumerate() {
+            if state.axes[i] != self.state.axes[i] {
+                self.device.send(pad_axis(axis), state.axes[i]).map_err(err)?;
+            }
+        }
+        for button in PadButton::ALL {
+            if state.pressed(button) != self.state.pressed(button) {
+                self.device.send(pad_button(button), state.pressed(button) as i32).map_err(err)?;
+            }
+        }
+        self.device.synchronize().map_err(err)?;
+        self.state = state;
+        Ok(())
+    }
+}
+
+/// Virtual touchscreen speaking multi-touch protocol B: one `ABS_MT_SLOT` per
+/// finger, `ABS_MT_TRACKING_ID` -1 on lift, plus `BTN_TOUCH` and `ABS_X/Y`
+/// for the first contact. The axes span one screen, so the compositor has to
+/// map the device onto that output.
+pub struct TouchManager {
+    device: uinput::Device,
+    pub tracker: TouchTracker,
+    /// What each slot last reported: (tracking ID, position).
+    down: [Option<(i32, (i32, i32))>; touch::MAX_SLOTS],
+}
+
+impl TouchManager {
+    /// `size` is the width and height of the screen the touches land on.
+    pub fn new(test: TouchTest, size: (i32, i32)) -> Result<Self> {
+        use uinput::event::absolute::Multi;
+        use uinput::event::absolute::Position as Abs;
+        use uinput::event::controller::Digi;
+
+        info!("Initializing Native uinput touchscreen ({}x{})...", size.0, size.1);
+        let (max_x, max_y) = ((size.0 - 1).max(1), (size.1 - 1).max(1));
+
+        let device = uinput::default().map_err(uinput_err("default"))?
+            .name("Air-Link Virtual Touchscreen").map_err(uinput_err("name"))?
+            .event(Digi::Touch).map_err(uinput_err("event"))?
+            .event(Abs::X).map_err(uinput_err("event"))?.min(0).max(max_x)
+            .event(Abs::Y).map_err(uinput_err("event"))?.min(0).max(max_y)
+            .event(Multi::Slot).map_err(uinput_err("event"))?.min(0).max(touch::MAX_SLOTS as i32 - 1)
+            .event(Multi::TrackingId).map_err(uinput_err("event"))?.min(0).max(touch::MAX_TRACKING_ID)
+            .event(Multi::PositionX).map_err(uinput_err("event"))?.min(0).max(max_x)
+            .event(Multi::PositionY).map_err(uinput_err("event"))?.min(0).max(max_y)
+            .create().map_err(uinput_err("create"))?;
+
+        Ok(Self { device, tracker: TouchTracker::new(test), down: [None; touch::MAX_SLOTS] })
+    }
+
+    /// Reports `contacts` (positions relative to the screen's top-left) and
+    /// lifts every slot not among them.
+    pub fn send(&mut self, contacts: &[Contact<(i32, i32)>]) -> Result<()> {
+        use uinput::event::absolute::Multi;
+        use uinput::event::absolute::Position as Abs;
+        use uinput::event::controller::Digi;
+
+        let mut next = [None; touch::MAX_SLOTS];
+        for contact in contacts {
+            next[contact.slot] = Some((contact.tracking_id, contact.position));
+        }
+        if next == self.down {
+            return Ok(());
+        }
+
+        let err = uinput_err("touch send");
+        for (slot, (before, after)) in self.down.iter().zip(&next).enumerate() {
+            if before == after {
+                continue;
+            }
+            self.device.send(Multi::Slot, slot as i32).map_err(err)?;
+            match (before, after) {
+                (_, None) => self.device.send(Multi::TrackingId, -1).map_err(err)?,
+                (Some((id, (x0, y0))), Some((new_id, (x, y)))) if id == new_id => {
+                    if x != x0 {
+                        self.device.send(Multi::PositionX, *x).map_err(err)?;
+                    }
+                    if y != y0 {
+                        self.device.send(Multi::PositionY, *y).map_err(err)?;
+                    }
+                }
+                (_, Some((id, (x, y)))) => {
+                    self.device.send(Multi::TrackingId, *id).map_err(err)?;
+                    self.device.send(Multi::PositionX, *x).map_err(err)?;
+                    self.device.send(Multi::PositionY, *y).map_err(err)?;
+                }
+            }
+        }
+
+        // Single-touch emulation follows the lowest slot
+        let was_down = self.down.iter().any(Option::is_some);
+        match next.iter().flatten().next() {
+            Some((_, (x, y))) => {
+                if !was_down {
+                    self.device.send(Digi::Touch, 1).map_err(err)?;
+                }
+                self.device.send(Abs::X, *x).map_err(err)?;
+                self.device.send(Abs::Y, *y).map_err(err)?;
+            }
+            None => self.device.send(Digi::Touch, 0).map_err(err)?,
+        }
+        self.device.synchronize().map_err(err)?;
+        self.down = next;
+        Ok(())
+    }
+
+    /// Lifts every finger, on the device and in the tracker.
+    pub fn lift_all(&mut self) -> Result<()> {
+        self.tracker.clear();
+        self.send(&[])
+    }
+}
+
+/// Virtual pen tablet (`BTN_TOOL_PEN`): proximity while a hand is in view,
+/// `BTN_TOUCH` and proportional `ABS_PRESSURE` while it pinches. Like the
+/// touchscreen, its axes span one screen.
+pub struct PenManager {
+    device: uinput::Device,
+    pub tracker: PenTracker,
+    last: Option<PenSample<(i32, i32)>>,
+}
+
+impl PenManager {
+    /// `size` is the width and height of the screen the pen draws on.
+    pub fn new(source: PressureSource, size: (i32, i32)) -> Result<Self> {
+        use uinput::event::absolute::Digi as AbsDigi;
+        use uinput::event::absolute::Position as Abs;
+        use uinput::event::controller::Digi;
+
+        info!("Initializing Native uinput pen tablet ({}x{})...", size.0, size.1);
+
+        let device = uinput::default().map_err(uinput_err("default"))?
+            .name("Air-Link Virtual Pen").map_err(uinput_err("name"))?
+            .event(Digi::Pen).map_err(uinput_err("event"))?
+            .event(Digi::Touch).map_err(uinput_err("event"))?
+            .event(Abs::X).map_err(uinput_err("event"))?.min(0).max((size.0 - 1).max(1))
+            .event(Abs::Y).map_err(uinput_err("event"))?.min(0).max((size.1 - 1).max(1))
+            .event(AbsDigi::Pressure).map_err(uinput_err("event"))?.min(0).max(pen::PRESSURE_MAX)
+            .create().map_err(uinput_err("create"))?;
+
+        Ok(Self { device, tracker: PenTracker::new(source), last: None })
+    }
+
+    /// Reports `sample` (position relative to the screen's top-left), or
+    /// takes the pen out of proximity for `None`.
+    pub fn send(&mut self, sample: Option<PenSample<(i32, i32)>>) -> Result<()> {
+        use uinput::event::absolute::Digi as AbsDigi;
+        use uinput::event::absolute::Position as Abs;
+        use uinput::event::controller::Digi;
+
+        if sample == self.last {
+            return Ok(());
+        }
+        let err = uinput_err("pen send");
+        let was_touching = self.last.is_some_and(|s| s.contact);
+
+        match sample {
+            Some(PenSample { position: (x, y), contact, pressure }) => {
+                if self.last.is_none() {
+                    self.device.send(Digi::Pen, 1).map_err(err)?;
+                }
+                self.device.send(Abs::X, x).map_err(err)?;
+                self.device.send(Abs::Y, y).map_err(err)?;
+                self.device.send(AbsDigi::Pressure, pressure).map_err(err)?;
+                if contact != was_touching {
+                    self.device.send(Digi::Touch, contact as i32).map_err(err)?;
+                }
+            }
+            None => {
+                // Lift before leaving proximity
+                if was_touching {
+                    self.device.send(AbsDigi::Pressure, 0).map_err(err)?;
+                    self.device.send(Digi::Touch, 0).map_err(err)?;
+                }
+                self.device.send(Digi::Pen, 0).map_err(err)?;
+            }
+        }
+        self.device.synchronize().map_err(err)?;
+        self.last = sample;
+        Ok(())
+    }
+
+    /// Takes the pen out of proximity, lifting it first if it was down.
+    pub fn leave(&mut self) -> Result<()> {
+        self.tracker.reset();
+        self.send(None)
+    }
 }
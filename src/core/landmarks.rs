@@ -0,0 +1,81 @@
+/// MediaPipe hand landmark indices.
+pub const WRIST: usize = 0;
+pub const THUMB_TIP: usize = 4;
+pub const INDEX_MCP: usize = 5;
+pub const INDEX_TIP: usize = 8;
+pub const MIDDLE_MCP: usize = 9;
+pub const MIDDLE_TIP: usize = 12;
+pub const RING_TIP: usize = 16;
+pub const PINKY_MCP: usize = 17;
+
+/// Distance in the image plane; depth is left out.
+pub fn distance(a: (f32, f32, f32), b: (f32, f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// Wrist to middle knuckle, the yardstick for finger readings.
+pub fn hand_size(lm: &[(f32, f32, f32)]) -> Option<f32> {
+    (lm.len() > MIDDLE_MCP).then(|| distance(lm[WRIST], lm[MIDDLE_MCP])).filter(|&size| size > 1e-4)
+}
+
+/// Thumb-index pinch with hysteresis, so a borderline pinch doesn't flicker.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Pinch {
+    down: bool,
+}
+
+impl Pinch {
+    /// Thumb-index gap, in hand sizes, that closes the pinch...
+    pub const CLOSE: f32 = 0.25;
+    /// ...and the wider gap that opens it again.
+    pub const OPEN: f32 = 0.35;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.down
+    }
+
+    /// Thumb-index gap of `lm` in hand sizes, `None` without a usable hand.
+    pub fn gap(lm: &[(f32, f32, f32)]) -> Option<f32> {
+        let size = hand_size(lm)?;
+        Some(distance(lm[THUMB_TIP], lm[INDEX_TIP]) / size)
+    }
+
+    /// Feeds this frame's gap (see [`Pinch::gap`]) and returns whether the pinch is closed.
+    pub fn update(&mut self, gap: f32) -> bool {
+        let limit = if self.down { Self::OPEN } else { Self::CLOSE };
+        self.down = gap < limit;
+        self.down
+    }
+
+    pub fn reset(&mut self) {
+        self.down = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::test_hand;
+
+    #[test]
+    fn test_pinch_hysteresis() {
+        let mut pinch = Pinch::new();
+        let gap = |g| Pinch::gap(&test_hand(0.5, g)).unwrap();
+        assert!((gap(0.3) - 0.3).abs() < 1e-5);
+
+        // Between the two gaps the pinch keeps whatever state it had
+        assert!(!pinch.update(gap(0.3)));
+        assert!(pinch.update(gap(0.2)));
+        assert!(pinch.update(gap(0.3)));
+        assert!(!pinch.update(gap(0.4)));
+
+        assert!(pinch.update(gap(0.0)));
+        pinch.reset();
+        assert!(!pinch.is_down());
+        assert_eq!(Pinch::gap(&test_hand(0.5, 0.0)[..8]), None);
+    }
+}
@@ -0,0 +1,240 @@
+use crate::core::display::Monitor;
+use crate::core::mapping::{MapperConfig, ScreenConfig};
+use crate::Result;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::time::Instant;
+
+/// Per-monitor overrides on top of the global mapping flags.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Calibration {
+    pub gain: Option<(f32, f32)>,
+    pub zone: Option<(f32, f32)>,
+    pub dead_zone: Option<f32>,
+}
+
+impl Calibration {
+    pub fn apply(&self, base: &MapperConfig, base_zone: (f32, f32)) -> (MapperConfig, (f32, f32)) {
+        let mut config = base.clone();
+        if let Some(gain) = self.gain {
+            config.gain = gain;
+        }
+        if let Some(dead_zone) = self.dead_zone {
+            config.dead_zone = dead_zone;
+        }
+        (config, self.zone.unwrap_or(base_zone))
+    }
+}
+
+/// Learns the active zone from a hand sweep: the range the pointer covered
+/// on either axis, slightly widened so the edges stay reachable.
+#[derive(Clone, Debug)]
+pub struct ZoneSweep {
+    pub until: Instant,
+    range: Option<(f32, f32)>,
+}
+
+impl ZoneSweep {
+    const MARGIN: f32 = 0.02;
+    /// Smaller sweeps are taken as a failed calibration, not a tiny zone.
+    const MIN_SPAN: f32 = 0.15;
+
+    pub fn new(until: Instant) -> Self {
+        Self { until, range: None }
+    }
+
+    pub fn observe(&mut self, (x, y): (f32, f32)) {
+        let (lo, hi) = self.range.unwrap_or((x, x));
+        self.range = Some((lo.min(x).min(y), hi.max(x).max(y)));
+    }
+
+    /// The learned zone, if the sweep was wide enough.
+    pub fn zone(&self) -> Option<(f32, f32)> {
+        let (lo, hi) = self.range?;
+        let zone = ((lo - Self::MARGIN).max(0.0), (hi + Self::MARGIN).min(1.0));
+        (zone.1 - zone.0 >= Self::MIN_SPAN).then_some(zone)
+    }
+}
+
+/// Which part of the desktop the active zone covers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LayoutTarget {
+    /// The bounding box of every monitor.
+    Span,
+    /// One monitor, by index into the layout.
+    Monitor(usize),
+}
+
+/// The full multi-monitor desktop, in logical pixels.
+pub struct DesktopLayout {
+    monitors: Vec<Monitor>,
+    calibrations: HashMap<String, Calibration>,
+    target: LayoutTarget,
+    /// Swipes jump between monitors.
+    pub switch_on_swipe: bool,
+}
+
+impl DesktopLayout {
+    /// Monitors are ordered left to right (then top to bottom), so "next" means "to the right".
+    pub fn new(mut monitors: Vec<Monitor>, start: Option<&str>, span: bool) -> Self {
+        monitors.sort_by_key(|m| (m.x, m.y));
+        let target = if span {
+            LayoutTarget::Span
+        } else {
+            let index = start
+                .and_then(|name| monitors.iter().position(|m| m.name == name))
+                .or_else(|| monitors.iter().position(|m| m.focused))
+                .unwrap_or(0);
+            LayoutTarget::Monitor(index)
+        };
+
+        Self {
+            monitors,
+            calibrations: HashMap::new(),
+            target,
+            switch_on_swipe: false,
+        }
+    }
+
+    pub fn monitors(&self) -> &[Monitor] {
+        &self.monitors
+    }
+
+    pub fn target(&self) -> LayoutTarget {
+        self.target
+    }
+
+    /// Loads `{"DP-1": {"gain_x": 1.2, "gain_y": 1.4, "zone_min": 0.25, "zone_max": 0.75, "dead_zone": 0.01}}`.
+    pub fn load_calibration(&mut self, path: &str) -> Result<()> {
+        let json: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let entries = json.as_object().ok_or_else(|| {
+            crate::AirLinkError::CoreError("Calibration file must be a JSON object keyed by screen name".into())
+        })?;
+
+        for (name, entry) in entries {
+            let num = |key: &str| entry.get(key).and_then(Value::as_f64).map(|v| v as f32);
+            let gain = match (num("gain_x"), num("gain_y")) {
+                (None, None) => None,
+                (x, y) => Some((x.or(y).unwrap_or(1.0), y.or(x).unwrap_or(1.0))),
+            };
+            let zone = num("zone_min").zip(num("zone_max"));
+
+            self.calibrations.insert(name.clone(), Calibration { gain, zone, dead_zone: num("dead_zone") });
+        }
+        Ok(())
+    }
+
+    /// Overrides the active zone of the current target, keeping its other calibration.
+    pub fn set_zone(&mut self, zone: (f32, f32)) {
+        self.calibrations.entry(self.current_name()).or_default().zone = Some(zone);
+    }
+
+    /// Calibration for the current target, if any.
+    pub fn calibration(&self) -> Option<&Calibration> {
+        self.calibrations.get(&self.current_name())
+    }
+
+    pub fn current_name(&self) -> String {
+        match self.target {
+            LayoutTarget::Span => "desktop".to_string(),
+            LayoutTarget::Monitor(i) => self.monitors.get(i).map_or_else(String::new, |m| m.name.clone()),
+        }
+    }
+
+    /// Rectangle the active zone maps onto.
+    pub fn current_screen(&self) -> ScreenConfig {
+        match self.target {
+            LayoutTarget::Span => self.virtual_bounds(),
+            LayoutTarget::Monitor(i) => self.monitors[i].screen_config(),
+        }
+    }
+
+    /// Bounding box of all monitors, each at its own scale.
+    pub fn virtual_bounds(&self) -> ScreenConfig {
+        let rects: Vec<ScreenConfig> = self.monitors.iter().map(Monitor::screen_config).collect();
+        let left = rects.iter().map(|r| r.x_offset).min().unwrap_or(0);
+        let top = rects.iter().map(|r| r.y_offset).min().unwrap_or(0);
+        let right = rects.iter().map(|r| r.x_offset + r.width).max().unwrap_or(0);
+        let bottom = rects.iter().map(|r| r.y_offset + r.height).max().unwrap_or(0);
+
+        ScreenConfig {
+            width: right - left,
+            height: bottom - top,
+            x_offset: left,
+            y_offset: top,
+        }
+    }
+
+    /// Steps to the neighbouring monitor (wrapping). Returns false when there is nowhere to go.
+    pub fn step(&mut self, forward: bool) -> bool {
+        let LayoutTarget::Monitor(i) = self.target else { return false };
+        let n = self.monitors.len();
+        if n < 2 {
+            return false;
+        }
+        self.target = LayoutTarget::Monitor(if forward { (i + 1) % n } else { (i + n - 1) % n });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, width: i32, height: i32, x: i32, y: i32, scale: f32) -> Monitor {
+        Monitor { name: name.into(), width, height, x, y, scale, focused: false }
+    }
+
+    #[test]
+    fn test_mixed_scale_layout() {
+        // 4K laptop panel at 2x, 1080p external to its right, portrait 1440p above-right
+        let monitors = vec![
+            monitor("DP-1", 1920, 1080, 1920, 0, 1.0),
+            monitor("eDP-1", 3840, 2160, 0, 0, 2.0),
+            monitor("DP-2", 1440, 2560, 3840, -400, 1.0),
+        ];
+        let mut layout = DesktopLayout::new(monitors, Some("eDP-1"), false);
+
+        assert_eq!(layout.current_screen(), ScreenConfig { width: 1920, height: 1080, x_offset: 0, y_offset: 0 });
+        assert_eq!(
+            layout.virtual_bounds(),
+            ScreenConfig { width: 5280, height: 2560, x_offset: 0, y_offset: -400 }
+        );
+
+        assert!(layout.step(true));
+        assert_eq!(layout.current_name(), "DP-1");
+        assert!(layout.step(true));
+        assert!(layout.step(true));
+        assert_eq!(layout.current_name(), "eDP-1");
+        assert!(layout.step(false));
+        assert_eq!(layout.current_name(), "DP-2");
+    }
+
+    #[test]
+    fn test_zone_sweep() {
+        let mut sweep = ZoneSweep::new(Instant::now());
+        assert_eq!(sweep.zone(), None);
+        sweep.observe((0.5, 0.5));
+        sweep.observe((0.55, 0.45));
+        assert_eq!(sweep.zone(), None, "barely moved");
+
+        sweep.observe((0.3, 0.4));
+        sweep.observe((0.7, 0.9));
+        let (lo, hi) = sweep.zone().unwrap();
+        assert!((lo - 0.28).abs() < 1e-6 && (hi - 0.92).abs() < 1e-6);
+
+        let monitors = vec![monitor("A", 1920, 1080, 0, 0, 1.0)];
+        let mut layout = DesktopLayout::new(monitors, None, false);
+        layout.set_zone((lo, hi));
+        assert_eq!(layout.calibration().and_then(|c| c.zone), Some((lo, hi)));
+    }
+
+    #[test]
+    fn test_span_does_not_step() {
+        let monitors = vec![monitor("A", 1920, 1080, 0, 0, 1.0), monitor("B", 1920, 1080, 1920, 0, 1.0)];
+        let mut layout = DesktopLayout::new(monitors, None, true);
+        assert!(!layout.step(true));
+        assert_eq!(layout.current_screen().width, 3840);
+    }
+}
@@ -1,32 +1,7 @@
-/// Logic for coordinate transformation and simulation tests.
-pub struct CoordinateMapper {
-    cam_width: f32,
-    cam_height: f32,
-    screen_width: f32,
-    screen_height: f32,
-}
-
-impl CoordinateMapper {
-    pub fn new(cam_w: f32, cam_h: f32, screen_w: f32, screen_h: f32) -> Self {
-        Self {
-            cam_width: cam_w,
-            cam_height: cam_h,
-            screen_width: screen_w,
-            screen_height: screen_h,
-        }
-    }
-
-    pub fn map_to_screen(&self, cam_x: f32, cam_y: f32) -> (i32, i32) {
-        let norm_x = cam_x / self.cam_width;
-        let norm_y = cam_y / self.cam_height;
-        let mirrored_x = 1.0 - norm_x;
-        let screen_x = (mirrored_x * self.screen_width) as i32;
-        let screen_y = (norm_y * self.screen_height) as i32;
-        (screen_x, screen_y)
-    }
-}
-
 /// Exponential Moving Average filter for smooth mouse movement.
+///
+/// `alpha` is the weight of a new sample at [`SmoothFilter::REFERENCE_FPS`]; at
+/// other rates the weight is rescaled so the pointer lags by the same time.
 pub struct SmoothFilter {
     alpha: f32,
     last_x: f32,
@@ -35,6 +10,10 @@ pub struct SmoothFilter {
 }
 
 impl SmoothFilter {
+    pub const REFERENCE_FPS: f32 = 30.0;
+    /// Smooth but responsive.
+    pub const DEFAULT_ALPHA: f32 = 0.2;
+
     pub fn new(alpha: f32) -> Self {
         Self {
             alpha: alpha.clamp(0.0, 1.0),
@@ -44,7 +23,16 @@ impl SmoothFilter {
         }
     }
 
+    /// One step at the reference frame rate.
     pub fn filter(&mut self, x: f32, y: f32) -> (f32, f32) {
+        self.filter_dt(x, y, 1.0 / Self::REFERENCE_FPS)
+    }
+
+    /// One step `dt` seconds after the previous sample.
+    pub fn filter_dt(&mut self, x: f32, y: f32, dt: f32) -> (f32, f32) {
+        // Same decay per second: what's left after one reference frame, raised to the frames elapsed
+        let alpha = if dt > 0.0 { 1.0 - (1.0 - self.alpha).powf(dt * Self::REFERENCE_FPS) } else { self.alpha };
+
         if !self.is_initialized {
             self.last_x = x;
             self.last_y = y;
@@ -52,36 +40,360 @@ impl SmoothFilter {
             return (x, y);
         }
 
-        let smoothed_x = x * self.alpha + self.last_x * (1.0 - self.alpha);
-        let smoothed_y = y * self.alpha + self.last_y * (1.0 - self.alpha);
+        let smoothed_x = x * alpha + self.last_x * (1.0 - alpha);
+        let smoothed_y = y * alpha + self.last_y * (1.0 - alpha);
 
         self.last_x = smoothed_x;
         self.last_y = smoothed_y;
 
         (smoothed_x, smoothed_y)
     }
+
+    /// Drops the history so the next sample is taken as-is.
+    pub fn reset(&mut self) {
+        self.is_initialized = false;
+    }
+}
+
+/// Common interface for the pointer smoothing stages.
+pub trait PointFilter: Send {
+    /// Filters one sample; `dt` is the time since the previous sample in seconds.
+    fn apply(&mut self, x: f32, y: f32, dt: f32) -> (f32, f32);
+    fn reset(&mut self);
+    /// Profile name, as on the command line.
+    fn name(&self) -> &'static str;
+}
+
+impl PointFilter for SmoothFilter {
+    fn apply(&mut self, x: f32, y: f32, dt: f32) -> (f32, f32) {
+        self.filter_dt(x, y, dt)
+    }
+
+    fn reset(&mut self) {
+        SmoothFilter::reset(self);
+    }
+
+    fn name(&self) -> &'static str {
+        "standard"
+    }
+}
+
+/// Second-order IIR section (RBJ cookbook), direct form I.
+#[derive(Clone, Copy, Default)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    /// Retunes as a notch (band-stop) without touching the history.
+    fn set_notch(&mut self, f0: f32, q: f32, fs: f32) {
+        let w0 = 2.0 * std::f32::consts::PI * f0 / fs;
+        let alpha = w0.sin() / (2.0 * q);
+        let a0 = 1.0 + alpha;
+        self.b0 = 1.0 / a0;
+        self.b1 = -2.0 * w0.cos() / a0;
+        self.b2 = 1.0 / a0;
+        self.a1 = -2.0 * w0.cos() / a0;
+        self.a2 = (1.0 - alpha) / a0;
+    }
+
+    /// Starts from a steady state at `v` so the first samples don't ring.
+    fn prime(&mut self, v: f32) {
+        self.x1 = v;
+        self.x2 = v;
+        self.y1 = v;
+        self.y2 = v;
+    }
+
+    fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+/// Tuning for [`TremorFilter`]. Distances are in frame-normalized units.
+#[derive(Clone, Copy, Debug)]
+pub struct TremorConfig {
+    /// Physiological tremor band the notch is allowed to track (Hz).
+    pub band: (f32, f32),
+    /// Notch quality factor; lower is wider.
+    pub q: f32,
+    /// Dead zone radius when the hand is at rest.
+    pub dead_zone: f32,
+    /// Speed (units/s) at which the dead zone has fully vanished.
+    pub dead_zone_speed: f32,
+}
+
+impl Default for TremorConfig {
+    fn default() -> Self {
+        Self {
+            band: (4.0, 12.0),
+            q: 0.7,
+            dead_zone: 0.004,
+            dead_zone_speed: 0.5,
+        }
+    }
+}
+
+/// Accessibility filter for physiological tremor.
+///
+/// An adaptive notch follows the dominant tremor frequency (estimated from
+/// zero crossings of the second difference, which all but removes slow
+/// intentional motion), then a velocity-dependent dead
+/// zone holds the cursor still at rest without slowing deliberate motion.
+pub struct TremorFilter {
+    config: TremorConfig,
+    notch: [Biquad; 2],
+    /// Last two raw samples, for the second difference.
+    history: [(f32, f32); 2],
+    last_residual: f32,
+    crossings: u32,
+    window: f32,
+    tremor_hz: f32,
+    fs: f32,
+    speed: f32,
+    anchor: (f32, f32),
+    last_filtered: (f32, f32),
+    is_initialized: bool,
+}
+
+impl TremorFilter {
+    /// Zero-crossing counting window (s).
+    const WINDOW: f32 = 0.5;
+
+    pub fn new(config: TremorConfig) -> Self {
+        Self {
+            config,
+            notch: [Biquad::default(); 2],
+            history: [(0.0, 0.0); 2],
+            last_residual: 0.0,
+            crossings: 0,
+            window: 0.0,
+            tremor_hz: (config.band.0 * config.band.1).sqrt(),
+            fs: 30.0,
+            speed: 0.0,
+            anchor: (0.0, 0.0),
+            last_filtered: (0.0, 0.0),
+            is_initialized: false,
+        }
+    }
+
+    /// Current estimate of the tremor frequency (Hz).
+    pub fn tremor_hz(&self) -> f32 {
+        self.tremor_hz
+    }
+
+    fn retune(&mut self) {
+        // Stay clear of Nyquist, cameras rarely give us more than 30 FPS
+        let hi = self.config.band.1.min(0.45 * self.fs);
+        let f0 = self.tremor_hz.clamp(self.config.band.0.min(hi), hi);
+        for n in &mut self.notch {
+            n.set_notch(f0, self.config.q, self.fs);
+        }
+    }
+
+    fn estimate_frequency(&mut self, x: f32, y: f32, dt: f32) {
+        let [(x1, y1), (x2, y2)] = self.history;
+        self.history = [(x, y), (x1, y1)];
+
+        // Dominant axis residual is enough to find the frequency
+        let (rx, ry) = (x - 2.0 * x1 + x2, y - 2.0 * y1 + y2);
+        let residual = if rx.abs() >= ry.abs() { rx } else { ry };
+        if residual.signum() != self.last_residual.signum() && self.last_residual != 0.0 {
+            self.crossings += 1;
+        }
+        self.last_residual = residual;
+
+        self.window += dt;
+        if self.window >= Self::WINDOW {
+            let measured = self.crossings as f32 / (2.0 * self.window);
+            if measured >= self.config.band.0 && measured <= self.config.band.1 {
+                self.tremor_hz += 0.3 * (measured - self.tremor_hz);
+                self.retune();
+            }
+            self.crossings = 0;
+            self.window = 0.0;
+        }
+    }
+}
+
+impl PointFilter for TremorFilter {
+    fn apply(&mut self, x: f32, y: f32, dt: f32) -> (f32, f32) {
+        let dt = if dt > 0.0 { dt } else { 1.0 / self.fs };
+
+        if !self.is_initialized {
+            self.fs = 1.0 / dt;
+            self.retune();
+            self.notch[0].prime(x);
+            self.notch[1].prime(y);
+            self.history = [(x, y); 2];
+            self.anchor = (x, y);
+            self.last_filtered = (x, y);
+            self.is_initialized = true;
+            return (x, y);
+        }
+
+        // Follow frame-rate changes, the notch is defined in Hz
+        let fs = 1.0 / dt;
+        if (fs - self.fs).abs() > 0.1 * self.fs {
+            self.fs = fs;
+            self.retune();
+        }
+
+        self.estimate_frequency(x, y, dt);
+        let fx = self.notch[0].process(x);
+        let fy = self.notch[1].process(y);
+
+        // Velocity-dependent dead zone: full radius at rest, none when moving fast
+        let (lx, ly) = self.last_filtered;
+        let inst_speed = ((fx - lx).powi(2) + (fy - ly).powi(2)).sqrt() / dt;
+        self.last_filtered = (fx, fy);
+        self.speed += 0.3 * (inst_speed - self.speed);
+        let radius = self.config.dead_zone * (1.0 - self.speed / self.config.dead_zone_speed).max(0.0);
+
+        let (dx, dy) = (fx - self.anchor.0, fy - self.anchor.1);
+        let dist = (dx * dx + dy * dy).sqrt();
+        if dist > radius {
+            // Drag the anchor along, staying `radius` behind the target
+            let pull = (dist - radius) / dist;
+            self.anchor = (self.anchor.0 + dx * pull, self.anchor.1 + dy * pull);
+        }
+
+        self.anchor
+    }
+
+    fn reset(&mut self) {
+        self.is_initialized = false;
+        self.crossings = 0;
+        self.window = 0.0;
+        self.speed = 0.0;
+        self.last_residual = 0.0;
+    }
+
+    fn name(&self) -> &'static str {
+        "tremor"
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_720p_to_1080p_mapping() {
-        let mapper = CoordinateMapper::new(1280.0, 720.0, 1920.0, 1080.0);
-        let (sx, sy) = mapper.map_to_screen(0.0, 0.0);
-        assert_eq!(sx, 1920);
-        assert_eq!(sy, 0);
-    }
-
     #[test]
     fn test_smoothing_filter() {
         let mut filter = SmoothFilter::new(0.5);
         let (x1, y1) = filter.filter(100.0, 100.0);
-        assert_eq!(x1, 100.0); // First input initialization
+        assert_eq!((x1, y1), (100.0, 100.0)); // First input initialization
 
         let (x2, y2) = filter.filter(200.0, 200.0);
         assert_eq!(x2, 150.0); // (200 * 0.5) + (100 * 0.5) = 150
         assert_eq!(y2, 150.0);
     }
+
+    #[test]
+    fn test_smoothing_is_frame_rate_independent() {
+        // Step from 0 to 1; after 200 ms both rates have covered the same distance
+        let step = |fps: f32| {
+            let mut filter = SmoothFilter::new(0.2);
+            filter.filter_dt(0.0, 0.0, 0.0);
+            let mut x = 0.0;
+            for _ in 0..(0.2 * fps).round() as usize {
+                x = filter.filter_dt(1.0, 1.0, 1.0 / fps).0;
+            }
+            x
+        };
+        assert!((step(15.0) - step(30.0)).abs() < 1e-4);
+        assert!((step(60.0) - step(30.0)).abs() < 1e-4);
+    }
+
+    /// Intentional 0.5 Hz sweep plus 8 Hz tremor, sampled at 30 FPS.
+    fn tremor_signal(t: f32) -> (f32, f32) {
+        let clean = 0.5 + 0.1 * (2.0 * std::f32::consts::PI * 0.5 * t).sin();
+        let tremor = 0.01 * (2.0 * std::f32::consts::PI * 8.0 * t).sin();
+        (clean, tremor)
+    }
+
+    /// Amplitude of the `hz` component in `v` (single-bin DFT).
+    fn amplitude_at(v: &[f32], hz: f32, dt: f32) -> f32 {
+        let (re, im) = v.iter().enumerate().fold((0.0, 0.0), |(re, im), (i, s)| {
+            let phase = 2.0 * std::f32::consts::PI * hz * i as f32 * dt;
+            (re + s * phase.cos(), im + s * phase.sin())
+        });
+        2.0 * (re * re + im * im).sqrt() / v.len() as f32
+    }
+
+    #[test]
+    fn test_tremor_filter_attenuates_tremor() {
+        let dt = 1.0 / 30.0;
+        let mut filter = TremorFilter::new(TremorConfig::default());
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+
+        for i in 0..300 {
+            let t = i as f32 * dt;
+            let (clean, tremor) = tremor_signal(t);
+            let (out, _) = filter.apply(clean + tremor, 0.5, dt);
+            if i >= 120 {
+                inputs.push(clean + tremor);
+                outputs.push(out);
+            }
+        }
+
+        // The 8 Hz component drops to a fifth, the intentional sweep survives
+        let tremor_in = amplitude_at(&inputs, 8.0, dt);
+        let tremor_out = amplitude_at(&outputs, 8.0, dt);
+        assert!(tremor_out < 0.2 * tremor_in, "{} vs {}", tremor_out, tremor_in);
+        assert!((amplitude_at(&outputs, 0.5, dt) - 0.1).abs() < 0.01);
+        assert!((filter.tremor_hz() - 8.0).abs() < 1.5, "tracked {} Hz", filter.tremor_hz());
+    }
+
+    #[test]
+    fn test_tremor_filter_low_lag_on_ramp() {
+        let dt = 1.0 / 30.0;
+        let speed = 0.3;
+        let mut filter = TremorFilter::new(TremorConfig::default());
+        let mut worst_lag = 0.0f32;
+
+        for i in 0..90 {
+            let x = 0.2 + speed * i as f32 * dt;
+            let (out, _) = filter.apply(x, 0.5, dt);
+            if i > 30 {
+                worst_lag = worst_lag.max((x - out) / speed);
+            }
+        }
+
+        // Deliberate motion trails by well under two frames
+        assert!(worst_lag < 2.0 * dt, "lag {} s", worst_lag);
+    }
+
+    #[test]
+    fn test_tremor_filter_holds_still_at_rest() {
+        let dt = 1.0 / 30.0;
+        let mut filter = TremorFilter::new(TremorConfig::default());
+        let mut outputs = Vec::new();
+
+        for i in 0..150 {
+            let t = i as f32 * dt;
+            let tremor = 0.003 * (2.0 * std::f32::consts::PI * 6.0 * t).sin();
+            let (out, _) = filter.apply(0.5 + tremor, 0.5, dt);
+            if t > 2.0 {
+                outputs.push(out - 0.5);
+            }
+        }
+
+        let max_wobble = outputs.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+        assert!(max_wobble < 0.001, "wobble {}", max_wobble);
+    }
 }
@@ -0,0 +1,337 @@
+/// Target display rectangle in virtual-desktop pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScreenConfig {
+    pub width: i32,
+    pub height: i32,
+    pub x_offset: i32,
+    pub y_offset: i32,
+}
+
+/// Transfer function applied to the zone-centered coordinate (-1..=1).
+///
+/// Every curve is odd-symmetric and maps 0 → 0 and ±1 → ±1, so the zone
+/// edges always reach the screen edges before gain is applied.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransferCurve {
+    Linear,
+    /// `|c|^exponent`; above 1 slows the center for precision and speeds up the edges.
+    Power(f32),
+    /// Normalized `tanh(k·c)`; fast through the center, gentle near the edges.
+    Sigmoid(f32),
+    /// Piecewise-linear `(input, output)` points over 0..=1, mirrored for negatives.
+    Table(Vec<(f32, f32)>),
+}
+
+impl TransferCurve {
+    pub fn apply(&self, c: f32) -> f32 {
+        let sign = c.signum();
+        let m = c.abs().min(1.0);
+
+        let out = match self {
+            TransferCurve::Linear => m,
+            TransferCurve::Power(exponent) => m.powf(exponent.max(0.01)),
+            TransferCurve::Sigmoid(k) => {
+                let k = k.max(0.01);
+                (k * m).tanh() / k.tanh()
+            }
+            TransferCurve::Table(points) => interpolate(points, m),
+        };
+
+        sign * out
+    }
+
+    /// Parses `"in:out,in:out,..."`, e.g. `"0.2:0.1,0.6:0.5"`. The 0:0 and 1:1
+    /// end points are implied, so inputs must lie strictly inside 0..1, and the
+    /// result must be non-decreasing.
+    pub fn parse_table(spec: &str) -> Result<Self, String> {
+        let mut points = vec![(0.0, 0.0)];
+        for pair in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (i, o) = pair.split_once(':')
+                .ok_or_else(|| format!("expected `in:out`, got `{}`", pair))?;
+            let i: f32 = i.trim().parse().map_err(|_| format!("bad input value `{}`", i))?;
+            let o: f32 = o.trim().parse().map_err(|_| format!("bad output value `{}`", o))?;
+            if !(0.0..=1.0).contains(&o) {
+                return Err(format!("output of `{}` is outside 0..=1", pair));
+            }
+            if i <= 0.0 || i >= 1.0 {
+                return Err(format!("input of `{}` must be between 0 and 1, exclusive", pair));
+            }
+            points.push((i, o));
+        }
+        points.push((1.0, 1.0));
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        points.dedup_by(|b, a| a.0 == b.0);
+
+        if points.windows(2).any(|w| w[1].1 < w[0].1) {
+            return Err("curve table must be non-decreasing".into());
+        }
+        Ok(TransferCurve::Table(points))
+    }
+}
+
+/// Linear interpolation over points sorted by input.
+fn interpolate(points: &[(f32, f32)], m: f32) -> f32 {
+    let Some(i) = points.iter().position(|p| p.0 >= m) else {
+        return points.last().map_or(m, |p| p.1);
+    };
+    if i == 0 {
+        return points[0].1;
+    }
+    let (x0, y0) = points[i - 1];
+    let (x1, y1) = points[i];
+    y0 + (y1 - y0) * (m - x0) / (x1 - x0)
+}
+
+#[derive(Clone, Debug)]
+pub struct MapperConfig {
+    pub curve: TransferCurve,
+    /// Per-axis gain around the zone center; 2.0 means half the zone spans the screen.
+    pub gain: (f32, f32),
+    /// Rest dead zone as a fraction of the active zone; motion smaller than this is ignored.
+    pub dead_zone: f32,
+    /// Flip X so moving the hand right moves the cursor right on an unmirrored camera.
+    pub mirror: bool,
+}
+
+impl Default for MapperConfig {
+    fn default() -> Self {
+        Self {
+            curve: TransferCurve::Linear,
+            gain: (1.0, 1.0),
+            dead_zone: 0.0,
+            mirror: true,
+        }
+    }
+}
+
+/// Maps frame-normalized tracker coordinates onto a screen rectangle.
+pub struct CoordinateMapper {
+    config: MapperConfig,
+    screen: ScreenConfig,
+    zone: (f32, f32),
+    anchor: Option<(f32, f32)>,
+}
+
+impl CoordinateMapper {
+    /// `zone` is the (min, max) camera region that covers the screen on both axes.
+    pub fn new(config: MapperConfig, screen: ScreenConfig, zone: (f32, f32)) -> Self {
+        Self {
+            config,
+            screen,
+            zone,
+            anchor: None,
+        }
+    }
+
+    pub fn screen(&self) -> ScreenConfig {
+        self.screen
+    }
+
+    pub fn set_screen(&mut self, screen: ScreenConfig) {
+        self.screen = screen;
+        self.anchor = None;
+    }
+
+    pub fn config(&self) -> &MapperConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut MapperConfig {
+        &mut self.config
+    }
+
+    pub fn zone(&self) -> (f32, f32) {
+        self.zone
+    }
+
+    /// Swaps config and zone together, e.g. for a per-monitor calibration.
+    pub fn reconfigure(&mut self, config: MapperConfig, zone: (f32, f32)) {
+        self.config = config;
+        self.zone = zone;
+        self.anchor = None;
+    }
+
+    pub fn in_zone(&self, x: f32, y: f32) -> bool {
+        let (min, max) = self.zone;
+        (min..=max).contains(&x) && (min..=max).contains(&y)
+    }
+
+    /// Forgets the dead-zone anchor (hand lost, clutch released...).
+    pub fn reset(&mut self) {
+        self.anchor = None;
+    }
+
+    /// Crops to the active zone (0..1 across it), mirrored if configured.
+    fn normalize(&self, x: f32, y: f32) -> (f32, f32) {
+        let (min, max) = self.zone;
+        let size = (max - min).max(f32::EPSILON);
+        let nx = ((x - min) / size).clamp(0.0, 1.0);
+        let ny = ((y - min) / size).clamp(0.0, 1.0);
+        if self.config.mirror { (1.0 - nx, ny) } else { (nx, ny) }
+    }
+
+    /// Transfer curve and gain around the zone center, then into the screen rectangle.
+    fn to_screen(&self, nx: f32, ny: f32) -> (i32, i32) {
+        let cx = (self.config.curve.apply(2.0 * nx - 1.0) * self.config.gain.0).clamp(-1.0, 1.0);
+        let cy = (self.config.curve.apply(2.0 * ny - 1.0) * self.config.gain.1).clamp(-1.0, 1.0);
+
+        // The offset is added, never scaled
+        let span_x = (self.screen.width - 1).max(0) as f32;
+        let span_y = (self.screen.height - 1).max(0) as f32;
+        let sx = self.screen.x_offset + ((cx + 1.0) * 0.5 * span_x).round() as i32;
+        let sy = self.screen.y_offset + ((cy + 1.0) * 0.5 * span_y).round() as i32;
+        (sx, sy)
+    }
+
+    /// Frame-normalized point to absolute screen pixels.
+    pub fn map_to_screen(&mut self, x: f32, y: f32) -> (i32, i32) {
+        // 1. Crop to the active zone
+        let (nx, ny) = self.normalize(x, y);
+
+        // 2. Rest dead zone: the anchor trails the hand by at most `dead_zone`
+        let (nx, ny) = match self.anchor {
+            Some((ax, ay)) => {
+                let (dx, dy) = (nx - ax, ny - ay);
+                let dist = (dx * dx + dy * dy).sqrt();
+                let r = self.config.dead_zone;
+                if dist > r {
+                    let pull = (dist - r) / dist;
+                    (ax + dx * pull, ay + dy * pull)
+                } else {
+                    (ax, ay)
+                }
+            }
+            None => (nx, ny),
+        };
+        self.anchor = Some((nx, ny));
+
+        // 3. Transfer curve, gain and screen rectangle
+        self.to_screen(nx, ny)
+    }
+
+    /// Same geometry as [`CoordinateMapper::map_to_screen`] without the rest
+    /// dead zone, for points that are not the cursor (touch contacts).
+    pub fn project(&self, x: f32, y: f32) -> (i32, i32) {
+        let (nx, ny) = self.normalize(x, y);
+        self.to_screen(nx, ny)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FHD: ScreenConfig = ScreenConfig { width: 1920, height: 1080, x_offset: 0, y_offset: 0 };
+
+    fn mapper(curve: TransferCurve) -> CoordinateMapper {
+        CoordinateMapper::new(MapperConfig { curve, ..Default::default() }, FHD, (0.0, 1.0))
+    }
+
+    fn assert_endpoints(curve: &TransferCurve) {
+        assert!(curve.apply(0.0).abs() < 1e-6);
+        assert!((curve.apply(1.0) - 1.0).abs() < 1e-6);
+        assert!((curve.apply(-1.0) + 1.0).abs() < 1e-6);
+        assert!((curve.apply(0.3) + curve.apply(-0.3)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_720p_to_1080p_mapping() {
+        // Pixels of a 1280x720 camera frame, normalized the way trackers report them
+        let mut mapper = mapper(TransferCurve::Linear);
+        let (sx, sy) = mapper.map_to_screen(0.0 / 1280.0, 0.0 / 720.0);
+        // Top-left of the camera is the top-right of the screen once mirrored
+        assert_eq!(sx, 1919);
+        assert_eq!(sy, 0);
+        assert_eq!(mapper.map_to_screen(640.0 / 1280.0, 360.0 / 720.0), (960, 540));
+    }
+
+    #[test]
+    fn test_edges_clamp_to_the_last_pixel() {
+        let mut mapper = mapper(TransferCurve::Linear);
+        // The far edges land on the last column and row, never one past them
+        assert_eq!(mapper.map_to_screen(0.0, 1.0), (1919, 1079));
+        assert_eq!(mapper.map_to_screen(1.0, 1.0), (0, 1079));
+        // Outside the active zone and past full gain still clamp
+        assert_eq!(mapper.map_to_screen(-0.5, 1.5), (1919, 1079));
+        let config = MapperConfig { gain: (3.0, 3.0), ..Default::default() };
+        let screen = ScreenConfig { x_offset: 1920, ..FHD };
+        assert_eq!(CoordinateMapper::new(config, screen, (0.0, 1.0)).project(0.1, 0.9), (1920 + 1919, 1079));
+    }
+
+    #[test]
+    fn test_linear_curve() {
+        let curve = TransferCurve::Linear;
+        assert_endpoints(&curve);
+        assert_eq!(curve.apply(0.25), 0.25);
+
+        let mut mapper = mapper(curve);
+        assert_eq!(mapper.map_to_screen(0.5, 0.5), (960, 540));
+    }
+
+    #[test]
+    fn test_power_curve_slows_center() {
+        let curve = TransferCurve::Power(2.0);
+        assert_endpoints(&curve);
+        assert!((curve.apply(0.5) - 0.25).abs() < 1e-6);
+        assert!(curve.apply(0.2) < 0.2);
+    }
+
+    #[test]
+    fn test_sigmoid_curve_speeds_center() {
+        let curve = TransferCurve::Sigmoid(2.0);
+        assert_endpoints(&curve);
+        assert!(curve.apply(0.2) > 0.2);
+        assert!(curve.apply(0.5) < curve.apply(0.6));
+    }
+
+    #[test]
+    fn test_table_curve() {
+        let curve = TransferCurve::parse_table("0.5:0.2").unwrap();
+        assert_endpoints(&curve);
+        assert!((curve.apply(0.5) - 0.2).abs() < 1e-6);
+        assert!((curve.apply(0.75) - 0.6).abs() < 1e-6);
+        assert!((curve.apply(-0.25) + 0.1).abs() < 1e-6);
+
+        assert!(TransferCurve::parse_table("0.5:0.8,0.6:0.1").is_err());
+        assert!(TransferCurve::parse_table("1.5:0.2").is_err());
+        assert!(TransferCurve::parse_table("0.5:1.5").is_err());
+        assert!(TransferCurve::parse_table("oops").is_err());
+    }
+
+    #[test]
+    fn test_table_rejects_implied_end_points() {
+        // 0:0 and 1:1 are implied; a user point there would be silently dropped
+        assert!(TransferCurve::parse_table("0:0.3").is_err());
+        assert!(TransferCurve::parse_table("1:0.7").is_err());
+        assert!(TransferCurve::parse_table("0.5:0.2,1.0:0.9").is_err());
+    }
+
+    #[test]
+    fn test_gain_is_per_axis_and_offset_is_not_scaled() {
+        let screen = ScreenConfig { width: 1920, height: 1080, x_offset: 1920, y_offset: 100 };
+        let config = MapperConfig { gain: (2.0, 1.0), ..Default::default() };
+        let mut mapper = CoordinateMapper::new(config, screen, (0.0, 1.0));
+
+        // Center stays the center of the target monitor whatever the gain
+        assert_eq!(mapper.map_to_screen(0.5, 0.5), (1920 + 960, 100 + 540));
+        // X doubles (and saturates), Y does not
+        mapper.reset();
+        assert_eq!(mapper.map_to_screen(0.25, 0.25), (1920 + 1919, 100 + 270));
+    }
+
+    #[test]
+    fn test_dead_zone_ignores_small_motion() {
+        let config = MapperConfig { dead_zone: 0.02, mirror: false, ..Default::default() };
+        let mut mapper = CoordinateMapper::new(config, FHD, (0.0, 1.0));
+
+        let rest = mapper.map_to_screen(0.5, 0.5);
+        assert_eq!(mapper.map_to_screen(0.51, 0.505), rest);
+
+        // Larger motion gets through, minus the dead zone
+        let (x, _) = mapper.map_to_screen(0.6, 0.5);
+        assert!(x > rest.0 && x < 1152);
+
+        // Touch contacts use the same geometry without the dead zone
+        assert_eq!(mapper.project(0.51, 0.5), (979, 540));
+    }
+}
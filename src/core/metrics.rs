@@ -0,0 +1,218 @@
+use crate::core::camera::CameraEvent;
+use std::fs;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// CPU time (user + system, all threads) this process has used so far. Linux only.
+pub fn process_cpu_time() -> Option<Duration> {
+    let stat = fs::read_to_string("/proc/self/stat").ok()?;
+    // The command name may contain spaces; fields are counted after its closing paren
+    let mut fields = stat.rsplit_once(')')?.1.split_whitespace().skip(11);
+    let utime: u64 = fields.next()?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    let ticks = utime + stime;
+    let hz = clock_ticks()?;
+    Some(Duration::from_secs(ticks / hz) + Duration::from_nanos(ticks % hz * 1_000_000_000 / hz))
+}
+
+/// USER_HZ, the unit of the `/proc` CPU times, as the kernel hands it to every
+/// process in its auxiliary vector (`AT_CLKTCK`). Read once.
+fn clock_ticks() -> Option<u64> {
+    static TICKS: OnceLock<Option<u64>> = OnceLock::new();
+    *TICKS.get_or_init(|| auxv_value(&fs::read("/proc/self/auxv").ok()?, AT_CLKTCK).filter(|&hz| hz > 0))
+}
+
+const AT_CLKTCK: u64 = 17;
+
+/// Value of `key` in an auxiliary vector: native-endian (key, value) words.
+fn auxv_value(auxv: &[u8], key: u64) -> Option<u64> {
+    const WORD: usize = std::mem::size_of::<usize>();
+    let word = |bytes: &[u8]| usize::from_ne_bytes(bytes.try_into().unwrap()) as u64;
+    auxv.chunks_exact(2 * WORD)
+        .map(|pair| (word(&pair[..WORD]), word(&pair[WORD..])))
+        .take_while(|&(k, _)| k != 0)
+        .find_map(|(k, v)| (k == key).then_some(v))
+}
+
+/// Running pipeline counters, shown by the terminal heartbeat and the GUI.
+#[derive(Clone, Debug, Default)]
+pub struct Metrics {
+    /// Frames processed since start.
+    pub frames: u64,
+    /// Corrupt or failed frames that did not take the camera down.
+    pub camera_errors: u64,
+    pub camera_losses: u64,
+    pub reconnects: u64,
+    /// How long the camera was gone the last time it came back.
+    pub last_downtime: Option<Duration>,
+    /// Human-readable description of the latest camera event.
+    pub last_camera_event: Option<String>,
+    /// Capture to pointer injection, smoothed over recent frames.
+    pub latency: Option<Duration>,
+    /// Tracking is paused until motion shows up.
+    pub idle: bool,
+    /// Times the pipeline dropped to idle.
+    pub idle_entries: u64,
+    /// Last still motion check to first tracked hand, the last time idle ended.
+    pub last_wake_latency: Option<Duration>,
+    /// Process CPU use (% of one core) over the current or last idle stretch.
+    pub idle_cpu: Option<f32>,
+    /// Start of the idle stretch and the process CPU time at that moment.
+    idle_start: Option<(Instant, Duration)>,
+    fps: f32,
+    window_start: Option<Instant>,
+    window_frames: u32,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_frame(&mut self, now: Instant) {
+        self.frames += 1;
+        self.window_frames += 1;
+
+        let start = *self.window_start.get_or_insert(now);
+        let elapsed = now.duration_since(start);
+        if elapsed >= Duration::from_secs(1) {
+            self.fps = self.window_frames as f32 / elapsed.as_secs_f32();
+            self.window_frames = 0;
+            self.window_start = Some(now);
+        }
+    }
+
+    pub fn record_camera_error(&mut self) {
+        self.camera_errors += 1;
+    }
+
+    pub fn record_camera_event(&mut self, event: &CameraEvent) {
+        let text = match event {
+            CameraEvent::Lost { device, reason } => {
+                self.camera_losses += 1;
+                format!("{} lost: {}", device, reason)
+            }
+            CameraEvent::Reconnected { device, downtime, attempts } => {
+                self.reconnects += 1;
+                self.last_downtime = Some(*downtime);
+                format!("{} reconnected after {:.1}s ({} attempts)", device, downtime.as_secs_f32(), attempts)
+            }
+        };
+        self.last_camera_event = Some(text);
+    }
+
+    pub fn record_latency(&mut self, latency: Duration) {
+        self.latency = Some(match self.latency {
+            Some(avg) => avg.mul_f32(0.9) + latency.mul_f32(0.1),
+            None => latency,
+        });
+    }
+
+    pub fn record_idle(&mut self, now: Instant) {
+        self.idle = true;
+        self.idle_entries += 1;
+        self.idle_start = process_cpu_time().map(|cpu| (now, cpu));
+    }
+
+    /// Updates `idle_cpu` from the process CPU clock; call while idle.
+    pub fn sample_idle_cpu(&mut self, now: Instant) {
+        let Some((start, cpu_start)) = self.idle_start else { return };
+        let wall = now.duration_since(start);
+        if wall < Duration::from_secs(1) {
+            return;
+        }
+        if let Some(cpu) = process_cpu_time() {
+            self.idle_cpu = Some(cpu.saturating_sub(cpu_start).as_secs_f32() / wall.as_secs_f32() * 100.0);
+        }
+    }
+
+    pub fn record_wake(&mut self, now: Instant) {
+        self.sample_idle_cpu(now);
+        self.idle = false;
+        self.idle_start = None;
+    }
+
+    pub fn record_wake_latency(&mut self, latency: Duration) {
+        self.last_wake_latency = Some(latency);
+    }
+
+    /// Frames per second over the last full one-second window.
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+
+    /// One-line summary for logs and the status bar.
+    pub fn summary(&self) -> String {
+        let mut line = format!("FPS: {:.0} | Frames: {} | Camera errors: {}", self.fps, self.frames, self.camera_errors);
+        if let Some(latency) = self.latency {
+            line.push_str(&format!(" | Latency: {:.0} ms", latency.as_secs_f32() * 1000.0));
+        }
+        if self.camera_losses > 0 {
+            line.push_str(&format!(" | Reconnects: {}/{}", self.reconnects, self.camera_losses));
+        }
+        if self.idle {
+            line.push_str(" | Idle");
+        }
+        if let Some(cpu) = self.idle_cpu {
+            line.push_str(&format!(" | Idle CPU: {:.1}%", cpu));
+        }
+        if let Some(latency) = self.last_wake_latency {
+            line.push_str(&format!(" | Wake: {} ms", latency.as_millis()));
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fps_and_reconnect_counters() {
+        let mut metrics = Metrics::new();
+        let start = Instant::now();
+        for i in 0..=30 {
+            metrics.record_frame(start + Duration::from_millis(i * 1000 / 30));
+        }
+        assert!((metrics.fps() - 30.0).abs() < 1.5);
+
+        metrics.record_camera_event(&CameraEvent::Lost { device: "cam".into(), reason: "unplugged".into() });
+        metrics.record_camera_event(&CameraEvent::Reconnected {
+            device: "cam".into(),
+            downtime: Duration::from_millis(1500),
+            attempts: 3,
+        });
+        assert_eq!((metrics.camera_losses, metrics.reconnects), (1, 1));
+        assert_eq!(metrics.last_downtime, Some(Duration::from_millis(1500)));
+        assert!(metrics.summary().contains("Reconnects: 1/1"));
+
+        metrics.record_latency(Duration::from_millis(40));
+        metrics.record_latency(Duration::from_millis(140));
+        assert!(metrics.summary().contains("Latency: 50 ms"));
+
+        metrics.record_idle(start);
+        assert!(metrics.summary().contains("Idle"));
+        metrics.record_wake(start + Duration::from_secs(2));
+        metrics.record_wake_latency(Duration::from_millis(180));
+        assert_eq!((metrics.idle, metrics.idle_entries), (false, 1));
+        assert!(metrics.summary().ends_with("Wake: 180 ms"));
+    }
+
+    #[test]
+    fn test_auxv_lookup() {
+        let words = |pairs: &[(usize, usize)]| -> Vec<u8> {
+            pairs.iter().flat_map(|&(k, v)| [k.to_ne_bytes(), v.to_ne_bytes()]).flatten().collect()
+        };
+        assert_eq!(auxv_value(&words(&[(6, 4096), (17, 250), (0, 0)]), AT_CLKTCK), Some(250));
+        // Nothing is read past the AT_NULL terminator
+        assert_eq!(auxv_value(&words(&[(6, 4096), (0, 0), (17, 250)]), AT_CLKTCK), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_process_cpu_time() {
+        // /proc may be missing in a sandbox; the time is optional there too
+        let Some(before) = process_cpu_time() else { return };
+        assert!(process_cpu_time().unwrap() >= before);
+    }
+}
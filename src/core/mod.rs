@@ -1,8 +1,38 @@
 pub mod camera;
+pub mod source;
+pub mod frame;
+pub mod preprocess;
+pub mod transform;
+pub mod model;
+pub mod metrics;
 pub mod app;
 pub mod logic;
+pub mod mapping;
+pub mod display;
+pub mod layout;
 pub mod vision;
+pub mod face;
+pub mod tracker;
+pub mod landmarks;
 pub mod input;
+pub mod gamepad;
+pub mod touch;
+pub mod pen;
+pub mod gesture;
+pub mod clutch;
+pub mod dwell;
+pub mod idle;
+pub mod predict;
+pub mod control;
+pub mod stream;
+pub mod osc;
+pub mod remote;
+pub mod udp;
+pub mod dump;
+pub mod dataset;
+pub mod eval;
+#[cfg(test)]
+pub mod testing;
 
 pub use camera::CameraManager;
 pub use input::MouseManager;
\ No newline at end of file
@@ -0,0 +1,500 @@
+use crate::core::preprocess::{Layout, Normalization};
+use crate::Result;
+use std::fs;
+use std::path::Path;
+use toml::{Table, Value};
+
+/// ONNX custom metadata key holding an embedded descriptor (same TOML as the sidecar).
+pub const METADATA_KEY: &str = "air_link.descriptor";
+
+/// A model output, by position or by graph name.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OutputRef {
+    Index(usize),
+    Name(String),
+    /// Position a built-in descriptor guesses; left unbound when the model
+    /// has fewer outputs, unlike one the user asked for.
+    Assumed(usize),
+}
+
+/// Unit of landmark coordinates in the landmark output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Units {
+    /// Pixels of the model input.
+    Pixels,
+    /// 0..1 across the model input.
+    Normalized,
+    /// Pixels when the index tip is past 1.1, as older exports varied.
+    Auto,
+}
+
+/// How the presence output encodes confidence.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Score {
+    Probability,
+    Logit,
+    /// Probability when in 0..=1, otherwise a logit.
+    Auto,
+}
+
+/// What a landmark model expects and produces. Loaded from `<model>.toml` next
+/// to the `.onnx` file or from the model's metadata; keys left out keep the
+/// tracker's built-in values.
+///
+/// ```toml
+/// [input]
+/// size = 256
+/// layout = "nhwc"        # or "nchw"
+/// range = [-1.0, 1.0]    # or mean = [..] and std = [..] over 0..1 pixels
+///
+/// [output]
+/// landmarks = "Identity"  # name, or position like 0
+/// presence = "Identity_1"
+/// score = "logit"         # "probability", "logit" or "auto"
+/// threshold = 0.6
+/// handedness = 2
+/// units = "pixels"        # "pixels", "normalized" or "auto"
+///
+/// [landmarks]
+/// count = 21
+/// stride = 3              # values per landmark (x, y[, z, ...])
+/// order = [0, 1, 2]       # model index of each MediaPipe joint, when they differ
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelDescriptor {
+    /// Input tensor name; required only when the model has several inputs.
+    pub input: Option<String>,
+    pub size: u32,
+    pub layout: Layout,
+    pub normalization: Normalization,
+    pub landmarks: OutputRef,
+    pub presence: Option<OutputRef>,
+    pub score: Score,
+    /// Minimum presence probability.
+    pub threshold: f32,
+    pub handedness: Option<OutputRef>,
+    pub units: Units,
+    /// Landmarks the model outputs.
+    pub count: usize,
+    pub stride: usize,
+    pub order: Option<Vec<usize>>,
+}
+
+/// Name and shape (`-1` for dynamic dimensions) of a model input or output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Port {
+    pub name: String,
+    pub shape: Vec<i64>,
+}
+
+/// Input/output ports of a loaded model, independent of the runtime.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Signature {
+    pub inputs: Vec<Port>,
+    pub outputs: Vec<Port>,
+}
+
+/// A descriptor checked against a model, with outputs resolved to positions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    pub input: String,
+    pub landmarks: usize,
+    pub presence: Option<usize>,
+    pub handedness: Option<usize>,
+}
+
+fn invalid(msg: String) -> crate::AirLinkError {
+    crate::AirLinkError::CoreError(format!("Model descriptor: {}", msg))
+}
+
+impl ModelDescriptor {
+    /// MediaPipe hand landmark export shipped in `assets/`.
+    pub fn hand() -> Self {
+        Self {
+            input: None,
+            size: 224,
+            layout: Layout::Nchw,
+            normalization: Normalization::UNIT,
+            landmarks: OutputRef::Index(0),
+            presence: Some(OutputRef::Assumed(1)),
+            score: Score::Probability,
+            threshold: 0.7,
+            handedness: Some(OutputRef::Assumed(2)),
+            units: Units::Auto,
+            count: 21,
+            stride: 3,
+            order: None,
+        }
+    }
+
+    /// MediaPipe face mesh (468 points in input pixels).
+    pub fn face() -> Self {
+        Self {
+            size: 192,
+            score: Score::Auto,
+            handedness: None,
+            units: Units::Pixels,
+            count: 468,
+            ..Self::hand()
+        }
+    }
+
+    /// Descriptor for `model_path`: the sidecar `.toml` wins over `metadata`
+    /// (the model's embedded descriptor), which wins over `builtin`.
+    pub fn resolve(model_path: &str, metadata: Option<String>, builtin: Self) -> Result<(Self, String)> {
+        let sidecar = Path::new(model_path).with_extension("toml");
+        if sidecar.exists() {
+            let text = fs::read_to_string(&sidecar)?;
+            return Ok((builtin.parse(&text)?, sidecar.display().to_string()));
+        }
+        match metadata {
+            Some(text) => Ok((builtin.parse(&text)?, format!("{} metadata", model_path))),
+            None => Ok((builtin, "built-in".to_string())),
+        }
+    }
+
+    /// Overrides `self` with the keys set in a descriptor TOML.
+    pub fn parse(mut self, text: &str) -> Result<Self> {
+        let table: Table = text.parse().map_err(|e| invalid(format!("{}", e)))?;
+        check_keys(&table, "", &["input", "output", "landmarks"])?;
+        let empty = Table::new();
+        let section = |name: &str| match table.get(name) {
+            Some(Value::Table(t)) => Ok(t),
+            Some(_) => Err(invalid(format!("[{}] must be a table", name))),
+            None => Ok(&empty),
+        };
+
+        let input = section("input")?;
+        check_keys(input, "input", &["name", "size", "layout", "range", "mean", "std"])?;
+        if let Some(v) = input.get("name") {
+            self.input = Some(string(v, "input.name")?.to_string());
+        }
+        if let Some(v) = input.get("size") {
+            self.size = integer(v, "input.size")? as u32;
+        }
+        if let Some(v) = input.get("layout") {
+            self.layout = match string(v, "input.layout")? {
+                "nchw" => Layout::Nchw,
+                "nhwc" => Layout::Nhwc,
+                other => return Err(invalid(format!("unknown input.layout '{}'", other))),
+            };
+        }
+        if let Some(v) = input.get("range") {
+            let [lo, hi] = floats::<2>(v, "input.range")?;
+            self.normalization = Normalization::range(lo, hi);
+        }
+        if let Some(v) = input.get("mean") {
+            self.normalization.mean = floats(v, "input.mean")?;
+        }
+        if let Some(v) = input.get("std") {
+            self.normalization.std = floats(v, "input.std")?;
+        }
+
+        let output = section("output")?;
+        check_keys(output, "output", &["landmarks", "presence", "score", "threshold", "handedness", "units"])?;
+        if let Some(v) = output.get("landmarks") {
+            self.landmarks = output_ref(v, "output.landmarks")?;
+        }
+        if let Some(v) = output.get("presence") {
+            self.presence = optional_output(v, "output.presence")?;
+        }
+        if let Some(v) = output.get("handedness") {
+            self.handedness = optional_output(v, "output.handedness")?;
+        }
+        if let Some(v) = output.get("score") {
+            self.score = match string(v, "output.score")? {
+                "probability" => Score::Probability,
+                "logit" => Score::Logit,
+                "auto" => Score::Auto,
+                other => return Err(invalid(format!("unknown output.score '{}'", other))),
+            };
+        }
+        if let Some(v) = output.get("threshold") {
+            self.threshold = float(v, "output.threshold")?;
+        }
+        if let Some(v) = output.get("units") {
+            self.units = match string(v, "output.units")? {
+                "pixels" => Units::Pixels,
+                "normalized" => Units::Normalized,
+                "auto" => Units::Auto,
+                other => return Err(invalid(format!("unknown output.units '{}'", other))),
+            };
+        }
+
+        let landmarks = section("landmarks")?;
+        check_keys(landmarks, "landmarks", &["count", "stride", "order"])?;
+        if let Some(v) = landmarks.get("count") {
+            self.count = integer(v, "landmarks.count")? as usize;
+        }
+        if let Some(v) = landmarks.get("stride") {
+            self.stride = integer(v, "landmarks.stride")? as usize;
+        }
+        if let Some(v) = landmarks.get("order") {
+            let list = v.as_array().ok_or_else(|| invalid("landmarks.order must be a list".into()))?;
+            self.order = Some(list.iter().map(|i| integer(i, "landmarks.order").map(|i| i as usize)).collect::<Result<_>>()?);
+        }
+        Ok(self)
+    }
+
+    /// Checks the descriptor against the model it describes. `joints` is how
+    /// many landmarks the tracker reads (in MediaPipe order).
+    pub fn validate(&self, signature: &Signature, joints: usize) -> Result<Bindings> {
+        if self.size == 0 {
+            return Err(invalid("input.size must be positive".into()));
+        }
+        if self.normalization.std.contains(&0.0) {
+            return Err(invalid("input.std must not contain 0".into()));
+        }
+        if !(0.0..=1.0).contains(&self.threshold) {
+            return Err(invalid(format!("output.threshold {} is outside 0..1", self.threshold)));
+        }
+        if self.stride < 2 {
+            return Err(invalid("landmarks.stride must be at least 2 (x, y)".into()));
+        }
+        match &self.order {
+            Some(order) if order.len() != joints => {
+                return Err(invalid(format!("landmarks.order lists {} joints, expected {}", order.len(), joints)));
+            }
+            Some(order) => {
+                if let Some(i) = order.iter().find(|&&i| i >= self.count) {
+                    return Err(invalid(format!("landmarks.order index {} is past count {}", i, self.count)));
+                }
+            }
+            None if self.count < joints => {
+                return Err(invalid(format!("landmarks.count {} is below the {} joints needed", self.count, joints)));
+            }
+            None => {}
+        }
+
+        // Input: the named one, or the only one
+        let input = match &self.input {
+            Some(name) => signature.inputs.iter().find(|p| &p.name == name)
+                .ok_or_else(|| invalid(format!("model has no input '{}'", name)))?,
+            None if signature.inputs.len() == 1 => &signature.inputs[0],
+            None => return Err(invalid(format!("model has {} inputs, set input.name", signature.inputs.len()))),
+        };
+        let size = self.size as i64;
+        let expected = match self.layout {
+            Layout::Nchw => [1, 3, size, size],
+            Layout::Nhwc => [1, size, size, 3],
+        };
+        let fits = input.shape.len() == 4 && input.shape.iter().zip(expected).all(|(&d, e)| d < 0 || d == e);
+        if !fits {
+            return Err(invalid(format!(
+                "input '{}' has shape {:?}, descriptor expects {:?} ({:?})", input.name, input.shape, expected, self.layout
+            )));
+        }
+
+        let resolve = |output: &OutputRef| match output {
+            OutputRef::Index(i) | OutputRef::Assumed(i) if *i < signature.outputs.len() => Ok(*i),
+            OutputRef::Index(i) | OutputRef::Assumed(i) => Err(invalid(format!("model has {} outputs, no output {}", signature.outputs.len(), i))),
+            OutputRef::Name(name) => signature.outputs.iter().position(|p| &p.name == name)
+                .ok_or_else(|| invalid(format!("model has no output '{}'", name))),
+        };
+        let optional = |output: &Option<OutputRef>| match output {
+            Some(OutputRef::Assumed(i)) => Ok((*i < signature.outputs.len()).then_some(*i)),
+            output => output.as_ref().map(resolve).transpose(),
+        };
+        let landmarks = resolve(&self.landmarks)?;
+
+        // Dynamic shapes are only known per run
+        let shape = &signature.outputs[landmarks].shape;
+        let needed = self.count * self.stride;
+        if shape.iter().all(|&d| d >= 0) && (shape.iter().product::<i64>() as usize) < needed {
+            return Err(invalid(format!(
+                "output '{}' has shape {:?}, too small for {} x {} landmarks",
+                signature.outputs[landmarks].name, shape, self.count, self.stride
+            )));
+        }
+
+        Ok(Bindings {
+            input: input.name.clone(),
+            landmarks,
+            presence: optional(&self.presence)?,
+            handedness: optional(&self.handedness)?,
+        })
+    }
+
+    /// Presence output value as a probability.
+    pub fn probability(&self, raw: f32) -> f32 {
+        let sigmoid = |x: f32| 1.0 / (1.0 + (-x).exp());
+        match self.score {
+            Score::Probability => raw,
+            Score::Logit => sigmoid(raw),
+            Score::Auto if (0.0..=1.0).contains(&raw) => raw,
+            Score::Auto => sigmoid(raw),
+        }
+    }
+
+    /// MediaPipe joint `joint` in model-normalized coordinates (z scaled alike, 0 without z).
+    /// `data` must hold `count * stride` values.
+    pub fn landmark(&self, data: &[f32], joint: usize) -> (f32, f32, f32) {
+        let i = self.order.as_ref().map_or(joint, |order| order[joint]) * self.stride;
+        let scale = self.unit_scale(data);
+        let z = if self.stride > 2 { data[i + 2] } else { 0.0 };
+        (data[i] * scale, data[i + 1] * scale, z * scale)
+    }
+
+    fn unit_scale(&self, data: &[f32]) -> f32 {
+        let pixels = match self.units {
+            Units::Pixels => true,
+            Units::Normalized => false,
+            Units::Auto => {
+                // Index fingertip for hands, first point otherwise
+                let joint = if self.count >= 21 { 8 } else { 0 };
+                let probe = self.order.as_ref().and_then(|o| o.get(joint).copied()).unwrap_or(joint);
+                let i = probe * self.stride;
+                data[i] > 1.1 || data[i + 1] > 1.1
+            }
+        };
+        if pixels { 1.0 / self.size as f32 } else { 1.0 }
+    }
+}
+
+fn check_keys(table: &Table, section: &str, allowed: &[&str]) -> Result<()> {
+    match table.keys().find(|k| !allowed.contains(&k.as_str())) {
+        Some(key) if section.is_empty() => Err(invalid(format!("unknown section [{}]", key))),
+        Some(key) => Err(invalid(format!("unknown key {}.{}", section, key))),
+        None => Ok(()),
+    }
+}
+
+fn string<'a>(v: &'a Value, key: &str) -> Result<&'a str> {
+    v.as_str().ok_or_else(|| invalid(format!("{} must be a string", key)))
+}
+
+fn integer(v: &Value, key: &str) -> Result<u64> {
+    v.as_integer().and_then(|i| u64::try_from(i).ok())
+        .ok_or_else(|| invalid(format!("{} must be a non-negative integer", key)))
+}
+
+fn float(v: &Value, key: &str) -> Result<f32> {
+    v.as_float().or_else(|| v.as_integer().map(|i| i as f64)).map(|f| f as f32)
+        .ok_or_else(|| invalid(format!("{} must be a number", key)))
+}
+
+/// A list of exactly `N` numbers; a single number is repeated.
+fn floats<const N: usize>(v: &Value, key: &str) -> Result<[f32; N]> {
+    if let Ok(f) = float(v, key) {
+        return Ok([f; N]);
+    }
+    let list = v.as_array().filter(|a| a.len() == N)
+        .ok_or_else(|| invalid(format!("{} must be a number or a list of {}", key, N)))?;
+    let mut out = [0.0; N];
+    for (o, v) in out.iter_mut().zip(list) {
+        *o = float(v, key)?;
+    }
+    Ok(out)
+}
+
+fn output_ref(v: &Value, key: &str) -> Result<OutputRef> {
+    match v {
+        Value::String(name) => Ok(OutputRef::Name(name.clone())),
+        v => integer(v, key).map(|i| OutputRef::Index(i as usize)),
+    }
+}
+
+/// An output reference, or `false` to say the model has no such output.
+fn optional_output(v: &Value, key: &str) -> Result<Option<OutputRef>> {
+    match v {
+        Value::Boolean(false) => Ok(None),
+        v => output_ref(v, key).map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(name: &str, shape: &[i64]) -> Port {
+        Port { name: name.into(), shape: shape.to_vec() }
+    }
+
+    fn mediapipe() -> Signature {
+        Signature {
+            inputs: vec![port("input_1", &[1, 3, 224, 224])],
+            outputs: vec![port("Identity", &[1, 63]), port("Identity_1", &[1, 1]), port("Identity_2", &[1, 1])],
+        }
+    }
+
+    #[test]
+    fn test_builtin_matches_bundled_model() {
+        let bindings = ModelDescriptor::hand().validate(&mediapipe(), 21).unwrap();
+        assert_eq!(bindings, Bindings { input: "input_1".into(), landmarks: 0, presence: Some(1), handedness: Some(2) });
+    }
+
+    #[test]
+    fn test_builtin_tolerates_missing_optional_outputs() {
+        // Landmarks and presence only: the guessed handedness output is dropped
+        let two = Signature { outputs: mediapipe().outputs[..2].to_vec(), ..mediapipe() };
+        let bindings = ModelDescriptor::hand().validate(&two, 21).unwrap();
+        assert_eq!((bindings.presence, bindings.handedness), (Some(1), None));
+
+        // An output the user names must exist
+        let named = ModelDescriptor::hand().parse("[output]\nhandedness = 2").unwrap();
+        assert!(named.validate(&two, 21).is_err());
+    }
+
+    #[test]
+    fn test_sidecar_overrides_and_validates() {
+        let desc = ModelDescriptor::hand().parse(r#"
+            [input]
+            size = 256
+            layout = "nhwc"
+            range = [-1.0, 1.0]
+
+            [output]
+            landmarks = "landmarks"
+            presence = "score"
+            score = "logit"
+            handedness = false
+            units = "normalized"
+
+            [landmarks]
+            count = 42
+            stride = 2
+        "#).unwrap();
+        assert_eq!(desc.normalization, Normalization { mean: [0.5; 3], std: [0.5; 3] });
+        assert_eq!(desc.handedness, None);
+
+        let signature = Signature {
+            inputs: vec![port("image", &[-1, 256, 256, 3])],
+            outputs: vec![port("score", &[1]), port("landmarks", &[1, 84])],
+        };
+        let bindings = desc.validate(&signature, 21).unwrap();
+        assert_eq!((bindings.landmarks, bindings.presence), (1, Some(0)));
+
+        // Same descriptor, model exported NCHW: rejected at load
+        let nchw = Signature { inputs: vec![port("image", &[1, 3, 256, 256])], ..signature };
+        assert!(desc.validate(&nchw, 21).is_err());
+    }
+
+    #[test]
+    fn test_rejects_mismatches() {
+        let hand = ModelDescriptor::hand;
+        assert!(hand().parse("[input]\nsise = 224").is_err());
+        assert!(hand().parse("[output]\nunits = \"cm\"").is_err());
+
+        // Too few landmark values, missing output, bad ordering
+        let small = Signature { outputs: vec![port("Identity", &[1, 42])], ..mediapipe() };
+        assert!(hand().parse("[output]\npresence = false\nhandedness = false").unwrap().validate(&small, 21).is_err());
+        assert!(hand().parse("[output]\nlandmarks = \"xyz\"").unwrap().validate(&mediapipe(), 21).is_err());
+        assert!(hand().parse("[landmarks]\norder = [0, 1, 2]").unwrap().validate(&mediapipe(), 21).is_err());
+    }
+
+    #[test]
+    fn test_landmark_order_and_units() {
+        // Two joints stored reversed, in pixels of a 4px input
+        let desc = ModelDescriptor {
+            size: 4,
+            units: Units::Pixels,
+            count: 2,
+            stride: 2,
+            order: Some(vec![1, 0]),
+            ..ModelDescriptor::hand()
+        };
+        let data = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(desc.landmark(&data, 0), (0.75, 1.0, 0.0));
+        assert_eq!(desc.landmark(&data, 1), (0.25, 0.5, 0.0));
+    }
+}
@@ -0,0 +1,203 @@
+use crate::core::landmarks::{distance, INDEX_TIP, THUMB_TIP};
+use crate::core::stream::{self, Sample};
+use crate::core::udp::UdpSender;
+use crate::core::vision::Handedness;
+use crate::Result;
+use std::net::ToSocketAddrs;
+use tracing::{info, warn};
+
+pub const DEFAULT_TEMPLATE: &str = "/air-link/{name}";
+
+/// One OSC argument.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Arg<'a> {
+    Int(i32),
+    Float(f32),
+    Str(&'a str),
+}
+
+/// Null-terminated and padded to a multiple of four bytes.
+fn push_str(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(s.as_bytes());
+    out.extend(std::iter::repeat_n(0, 4 - s.len() % 4));
+}
+
+pub fn encode_message(address: &str, args: &[Arg]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(address.len() + 8 + args.len() * 8);
+    push_str(&mut out, address);
+    let tags: String = std::iter::once(',')
+        .chain(args.iter().map(|arg| match arg {
+            Arg::Int(_) => 'i',
+            Arg::Float(_) => 'f',
+            Arg::Str(_) => 's',
+        }))
+        .collect();
+    push_str(&mut out, &tags);
+    for arg in args {
+        match arg {
+            Arg::Int(v) => out.extend_from_slice(&v.to_be_bytes()),
+            Arg::Float(v) => out.extend_from_slice(&v.to_be_bytes()),
+            Arg::Str(s) => push_str(&mut out, s),
+        }
+    }
+    out
+}
+
+/// `#bundle` with the "immediately" time tag.
+pub fn encode_bundle(messages: &[Vec<u8>]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(16 + messages.iter().map(|m| m.len() + 4).sum::<usize>());
+    push_str(&mut out, "#bundle");
+    out.extend_from_slice(&1u64.to_be_bytes());
+    for message in messages {
+        out.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        out.extend_from_slice(message);
+    }
+    out
+}
+
+/// Address pattern with `{name}` (what the message carries, e.g. `pointer` or
+/// `landmark/8`) and optionally `{hand}` (`left`, `right` or `unknown`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddressTemplate(String);
+
+impl AddressTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        if !template.starts_with('/') || !template.contains("{name}") {
+            return Err(crate::AirLinkError::CliError(format!(
+                "OSC address template `{}` must start with '/' and contain {{name}}", template
+            )));
+        }
+        let rest = template.replace("{name}", "").replace("{hand}", "");
+        if rest.contains(['{', '}', ' ', '#']) {
+            return Err(crate::AirLinkError::CliError(format!(
+                "OSC address template `{}` may only use {{name}} and {{hand}}", template
+            )));
+        }
+        Ok(Self(template.to_string()))
+    }
+
+    pub fn address(&self, name: &str, hand: Option<Handedness>) -> String {
+        let hand = match hand {
+            Some(Handedness::Left) => "left",
+            Some(Handedness::Right) => "right",
+            None => "unknown",
+        };
+        self.0.replace("{hand}", hand).replace("{name}", name)
+    }
+}
+
+/// Messages for one frame, in the order they are bundled.
+pub fn frame_messages(template: &AddressTemplate, sample: &Sample) -> Vec<Vec<u8>> {
+    let outbox = sample.outbox;
+    let hand = outbox.handedness;
+    let message = |name: &str, args: &[Arg]| encode_message(&template.address(name, hand), args);
+    let lm = &outbox.landmarks;
+
+    let mut messages = vec![message("present", &[Arg::Int(!lm.is_empty() as i32)])];
+    if let Some((x, y)) = sample.pointer {
+        messages.push(message("pointer", &[Arg::Float(x), Arg::Float(y)]));
+    }
+    if let Some((x, y)) = outbox.cursor {
+        messages.push(message("cursor", &[Arg::Int(x), Arg::Int(y)]));
+    }
+    if !lm.is_empty() {
+        messages.push(message("pose", &[Arg::Str(stream::pose_name(sample.pose))]));
+    }
+    if lm.len() > INDEX_TIP {
+        let pinch = distance(lm[THUMB_TIP], lm[INDEX_TIP]);
+        messages.push(message("pinch", &[Arg::Float(pinch)]));
+    }
+    for (i, &(x, y, z)) in lm.iter().enumerate() {
+        messages.push(message(&format!("landmark/{}", i), &[Arg::Float(x), Arg::Float(y), Arg::Float(z)]));
+    }
+    for event in &outbox.events {
+        messages.push(message("event", &[Arg::Str(event.name())]));
+    }
+    messages
+}
+
+/// Sends every processed frame as one OSC bundle over UDP.
+pub struct OscSink {
+    udp: UdpSender,
+    template: AddressTemplate,
+}
+
+impl OscSink {
+    pub fn new(target: &str, template: AddressTemplate) -> Result<Self> {
+        let addr = target.to_socket_addrs()?.next().ok_or_else(|| {
+            crate::AirLinkError::CliError(format!("OSC target `{}` did not resolve", target))
+        })?;
+        let udp = UdpSender::new(addr)?;
+        info!("🎹 Sending OSC to {} ({})", addr, template.0);
+        Ok(Self { udp, template })
+    }
+
+    pub fn send(&mut self, sample: &Sample) {
+        let bundle = encode_bundle(&frame_messages(&self.template, sample));
+        if let Err(e) = self.udp.send(&bundle) {
+            warn!("OSC send to {} failed: {}", self.udp.target(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::gesture::HandPose;
+    use crate::core::stream::{Outbox, StreamEvent};
+    use std::net::UdpSocket;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_message_encoding() {
+        // Padding per the OSC 1.0 spec: "/p\0\0", ",fs\0", float, "ab\0\0"
+        let bytes = encode_message("/p", &[Arg::Float(1.0), Arg::Str("ab")]);
+        assert_eq!(bytes, [b"/p\0\0".as_slice(), b",fs\0", &1.0f32.to_be_bytes(), b"ab\0\0"].concat());
+        // A string filling its four bytes still gets a terminator
+        assert_eq!(encode_message("/abc", &[]).len(), 8 + 4);
+
+        let template = AddressTemplate::parse("/synth/{hand}/{name}").unwrap();
+        assert_eq!(template.address("landmark/8", Some(Handedness::Left)), "/synth/left/landmark/8");
+        assert!(AddressTemplate::parse("/synth/{hand}").is_err());
+        assert!(AddressTemplate::parse("/synth/{name}/{index}").is_err());
+    }
+
+    #[test]
+    fn test_bundle_over_udp() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let template = AddressTemplate::parse(DEFAULT_TEMPLATE).unwrap();
+        let mut sink = OscSink::new(&receiver.local_addr().unwrap().to_string(), template).unwrap();
+
+        let mut landmarks = vec![(0.5, 0.5, 0.0); 21];
+        landmarks[THUMB_TIP] = (0.4, 0.5, 0.0);
+        landmarks[INDEX_TIP] = (0.4, 0.8, 0.0);
+        let outbox = Outbox { landmarks, events: vec![StreamEvent::ClickDown], ..Outbox::default() };
+        sink.send(&Sample {
+            frame: 1,
+            timestamp: Instant::now(),
+            pose: HandPose::Open,
+            pointer: Some((0.4, 0.8)),
+            engaged: true,
+            idle: false,
+            paused: false,
+            outbox: &outbox,
+        });
+
+        let mut buf = [0u8; 8192];
+        let len = receiver.recv(&mut buf).unwrap();
+        let mut rest = &buf[16..len];
+        assert_eq!(&buf[..8], b"#bundle\0");
+
+        let mut messages = Vec::new();
+        while !rest.is_empty() {
+            let size = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            messages.push(rest[4..4 + size].to_vec());
+            rest = &rest[4 + size..];
+        }
+        // present, pointer, pose, pinch, 21 landmarks, event
+        assert_eq!(messages.len(), 4 + 21 + 1);
+        assert_eq!(messages[3], encode_message("/air-link/pinch", &[Arg::Float((0.5f32 - 0.8).abs())]));
+        assert_eq!(messages[25], encode_message("/air-link/event", &[Arg::Str("click_down")]));
+    }
+}
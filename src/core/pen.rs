@@ -0,0 +1,156 @@
+use crate::core::landmarks::{Pinch, INDEX_TIP, THUMB_TIP, WRIST};
+
+/// `ABS_PRESSURE` range of the virtual pen.
+pub const PRESSURE_MAX: i32 = 4095;
+
+/// Lightest pressure of a pen in contact, as a fraction of the maximum.
+const MIN_PRESSURE: f32 = 0.05;
+/// Weight of the new sample in position and pressure smoothing.
+const SMOOTHING: f32 = 0.5;
+
+/// Where pen pressure comes from while the fingers pinch.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PressureSource {
+    /// Closing the pinch further, from touch-down to tips together.
+    Pinch,
+    /// Pushing the index tip toward the camera after touch-down, full
+    /// pressure this much (landmark z, in frame widths) past it.
+    Depth(f32),
+}
+
+/// The pen as one frame saw it: always in proximity, touching or hovering.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PenSample<P = (f32, f32)> {
+    /// Between the thumb and index tips, frame-normalized.
+    pub position: P,
+    pub contact: bool,
+    /// 0 while hovering, up to [`PRESSURE_MAX`].
+    pub pressure: i32,
+}
+
+impl<P> PenSample<P> {
+    pub fn map<Q>(self, f: impl FnOnce(P) -> Q) -> PenSample<Q> {
+        PenSample { position: f(self.position), contact: self.contact, pressure: self.pressure }
+    }
+}
+
+/// Hover and contact from the thumb-index pinch, with proportional pressure.
+pub struct PenTracker {
+    source: PressureSource,
+    /// Touches down and lifts the pen.
+    pinch: Pinch,
+    position: Option<(f32, f32)>,
+    /// Unscaled pressure (0..=1), `Some` while in contact.
+    pressure: Option<f32>,
+    /// Index tip depth at touch-down, for [`PressureSource::Depth`].
+    depth_at_contact: f32,
+}
+
+impl PenTracker {
+    pub fn new(source: PressureSource) -> Self {
+        Self { source, pinch: Pinch::new(), position: None, pressure: None, depth_at_contact: 0.0 }
+    }
+
+    pub fn source(&self) -> PressureSource {
+        self.source
+    }
+
+    /// The hand left: the pen leaves proximity.
+    pub fn reset(&mut self) {
+        self.pinch.reset();
+        self.position = None;
+        self.pressure = None;
+    }
+
+    /// `None` when there is no hand, so the pen is out of proximity.
+    pub fn update(&mut self, landmarks: &[(f32, f32, f32)]) -> Option<PenSample> {
+        let lm = landmarks;
+        let Some(gap) = Pinch::gap(lm) else {
+            self.reset();
+            return None;
+        };
+
+        let (thumb, index) = (lm[THUMB_TIP], lm[INDEX_TIP]);
+        let tip = ((thumb.0 + index.0) / 2.0, (thumb.1 + index.1) / 2.0);
+        let position = match self.position {
+            Some((x, y)) => (x + (tip.0 - x) * SMOOTHING, y + (tip.1 - y) * SMOOTHING),
+            None => tip,
+        };
+        self.position = Some(position);
+
+        // Depth: how far the index tip is in front of the wrist
+        let depth = lm[WRIST].2 - index.2;
+        if !self.pinch.update(gap) {
+            self.pressure = None;
+            return Some(PenSample { position, contact: false, pressure: 0 });
+        }
+
+        if self.pressure.is_none() {
+            self.depth_at_contact = depth;
+        }
+        let target = match self.source {
+            PressureSource::Pinch => 1.0 - gap / Pinch::CLOSE,
+            PressureSource::Depth(range) => (depth - self.depth_at_contact) / range,
+        }
+        .clamp(0.0, 1.0);
+        let pressure = match self.pressure {
+            Some(p) => p + (target - p) * SMOOTHING,
+            None => target,
+        };
+        self.pressure = Some(pressure);
+
+        let scaled = MIN_PRESSURE + (1.0 - MIN_PRESSURE) * pressure;
+        Some(PenSample { position, contact: true, pressure: (scaled * PRESSURE_MAX as f32).round() as i32 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The shared test hand with the index tip at depth `index_z`.
+    fn hand(gap: f32, index_z: f32) -> Vec<(f32, f32, f32)> {
+        let mut lm = crate::core::testing::test_hand(0.5, gap);
+        lm[INDEX_TIP].2 = index_z;
+        lm
+    }
+
+    #[test]
+    fn test_hover_contact_and_pinch_pressure() {
+        let mut pen = PenTracker::new(PressureSource::Pinch);
+        let hover = pen.update(&hand(1.0, 0.0)).unwrap();
+        assert_eq!((hover.contact, hover.pressure), (false, 0));
+        assert!((hover.position.0 - 0.6).abs() < 1e-5);
+
+        // Just past touch-down: light pressure, never zero
+        let light = pen.update(&hand(0.24, 0.0)).unwrap();
+        assert!(light.contact && light.pressure > 0 && light.pressure < PRESSURE_MAX / 5, "{:?}", light);
+        // Tips together: pressure climbs toward the maximum
+        let mut firm = light;
+        for _ in 0..10 {
+            firm = pen.update(&hand(0.0, 0.0)).unwrap();
+        }
+        assert!(firm.pressure > PRESSURE_MAX * 9 / 10);
+
+        // Opening a little stays down (hysteresis), opening wide hovers again
+        assert!(pen.update(&hand(0.3, 0.0)).unwrap().contact);
+        assert!(!pen.update(&hand(0.4, 0.0)).unwrap().contact);
+        assert_eq!(pen.update(&[]), None);
+    }
+
+    #[test]
+    fn test_depth_pressure_is_relative_to_touch_down() {
+        let mut pen = PenTracker::new(PressureSource::Depth(0.1));
+        // Touching down with the hand already leaning in starts light
+        let down = pen.update(&hand(0.1, -0.2)).unwrap();
+        assert_eq!(down.pressure, (MIN_PRESSURE * PRESSURE_MAX as f32).round() as i32);
+
+        // Half the range further toward the camera, once smoothing settles
+        let mut pushed = down;
+        for _ in 0..10 {
+            pushed = pen.update(&hand(0.1, -0.25)).unwrap();
+        }
+        let expected = (MIN_PRESSURE + (1.0 - MIN_PRESSURE) * 0.5) * PRESSURE_MAX as f32;
+        assert!((pushed.pressure as f32 - expected).abs() < 10.0, "{:?}", pushed);
+    }
+}
@@ -0,0 +1,176 @@
+use std::time::{Duration, Instant};
+
+/// Tuning for [`Predictor`]. Distances are in frame-normalized units.
+#[derive(Clone, Copy, Debug)]
+pub struct PredictorConfig {
+    /// Never look further ahead than this, whatever the measured latency.
+    pub horizon: Duration,
+    /// Add the acceleration term (`a·t²/2`) on top of velocity.
+    pub acceleration: bool,
+    /// Largest distance the prediction may move the pointer.
+    pub max_offset: f32,
+}
+
+impl Default for PredictorConfig {
+    fn default() -> Self {
+        Self {
+            horizon: Duration::from_millis(80),
+            acceleration: false,
+            max_offset: 0.05,
+        }
+    }
+}
+
+/// Extrapolates the filtered pointer by the pipeline latency so the cursor
+/// sits where the finger is now, not where it was at capture.
+///
+/// Overshoot protection: velocity restarts on direction reversals, a braking
+/// hand is never predicted past where it stops, and the offset is capped.
+pub struct Predictor {
+    config: PredictorConfig,
+    last: Option<(Instant, (f32, f32))>,
+    velocity: (f32, f32),
+    acceleration: (f32, f32),
+}
+
+impl Predictor {
+    /// Velocity/acceleration smoothing time constant (s).
+    const TAU: f32 = 0.03;
+    /// Below this speed (units/s) the hand is at rest and left alone.
+    const MIN_SPEED: f32 = 0.05;
+
+    pub fn new(config: PredictorConfig) -> Self {
+        Self {
+            config,
+            last: None,
+            velocity: (0.0, 0.0),
+            acceleration: (0.0, 0.0),
+        }
+    }
+
+    /// Predicted position `latency` after the sample `pos` taken at `t`.
+    pub fn predict(&mut self, pos: (f32, f32), t: Instant, latency: Duration) -> (f32, f32) {
+        let Some((t0, p0)) = self.last.replace((t, pos)) else { return pos };
+        let dt = t.saturating_duration_since(t0).as_secs_f32();
+        if dt <= 0.0 {
+            return pos;
+        }
+
+        let raw = ((pos.0 - p0.0) / dt, (pos.1 - p0.1) / dt);
+        if dot(raw, self.velocity) < 0.0 {
+            // Reversal: the old velocity would only push further the wrong way
+            self.velocity = raw;
+            self.acceleration = (0.0, 0.0);
+        } else {
+            let k = 1.0 - (-dt / Self::TAU).exp();
+            let previous = self.velocity;
+            self.velocity = lerp(previous, raw, k);
+            let raw_accel = ((self.velocity.0 - previous.0) / dt, (self.velocity.1 - previous.1) / dt);
+            self.acceleration = lerp(self.acceleration, raw_accel, k);
+        }
+
+        let speed = length(self.velocity);
+        if speed < Self::MIN_SPEED {
+            return pos;
+        }
+
+        let lead = latency.min(self.config.horizon).as_secs_f32();
+        let (v, a) = (self.velocity, self.acceleration);
+        let mut offset = (v.0 * lead, v.1 * lead);
+        if self.config.acceleration {
+            offset.0 += 0.5 * a.0 * lead * lead;
+            offset.1 += 0.5 * a.1 * lead * lead;
+        }
+        if dot(offset, v) <= 0.0 {
+            return pos;
+        }
+
+        // Braking: no further than the stopping distance v²/2a
+        let mut limit = self.config.max_offset;
+        let along = dot(a, v) / speed;
+        if along < 0.0 {
+            limit = limit.min(speed * speed / (2.0 * -along));
+        }
+        let len = length(offset);
+        if len > limit {
+            offset = (offset.0 * limit / len, offset.1 * limit / len);
+        }
+
+        (pos.0 + offset.0, pos.1 + offset.1)
+    }
+
+    /// Forgets the motion history, e.g. when the hand was lost.
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.velocity = (0.0, 0.0);
+        self.acceleration = (0.0, 0.0);
+    }
+}
+
+fn dot(a: (f32, f32), b: (f32, f32)) -> f32 {
+    a.0 * b.0 + a.1 * b.1
+}
+
+fn length(v: (f32, f32)) -> f32 {
+    dot(v, v).sqrt()
+}
+
+fn lerp(a: (f32, f32), b: (f32, f32), k: f32) -> (f32, f32) {
+    (a.0 + (b.0 - a.0) * k, a.1 + (b.1 - a.1) * k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 30.0;
+
+    fn at(start: Instant, i: usize) -> Instant {
+        start + Duration::from_secs_f32(i as f32 * DT)
+    }
+
+    #[test]
+    fn test_leads_constant_motion_by_latency() {
+        let mut predictor = Predictor::new(PredictorConfig::default());
+        let start = Instant::now();
+        let latency = Duration::from_millis(60);
+        let mut predicted = (0.0, 0.0);
+        for i in 0..30 {
+            let x = 0.2 + 0.5 * i as f32 * DT;
+            predicted = predictor.predict((x, 0.5), at(start, i), latency);
+        }
+        // 0.5 units/s for 60 ms
+        let x = 0.2 + 0.5 * 29.0 * DT;
+        assert!((predicted.0 - (x + 0.03)).abs() < 1e-3, "{:?}", predicted);
+        assert!((predicted.1 - 0.5).abs() < 1e-6);
+
+        // The horizon caps the lead however slow the pipeline is
+        let far = predictor.predict((x + 0.5 * DT, 0.5), at(start, 30), Duration::from_millis(500));
+        assert!((far.0 - (x + 0.5 * DT) - 0.04).abs() < 1e-3, "{:?}", far);
+    }
+
+    #[test]
+    fn test_no_overshoot_on_sudden_stop() {
+        let config = PredictorConfig { acceleration: true, ..PredictorConfig::default() };
+        let mut predictor = Predictor::new(config);
+        let start = Instant::now();
+        let latency = Duration::from_millis(80);
+
+        // 0.3 units/s right (0.01 per frame), then an abrupt stop at x = 0.59
+        for i in 0..20 {
+            predictor.predict((0.4 + i as f32 * 0.01, 0.5), at(start, i), latency);
+        }
+        let stop = 0.4 + 19.0 * 0.01;
+        for i in 20..30 {
+            let (x, _) = predictor.predict((stop, 0.5), at(start, i), latency);
+            assert!(x - stop < 0.01, "frame {} predicted {}", i, x);
+            if i >= 23 {
+                assert!(x - stop < 1e-3, "frame {} still ahead at {}", i, x);
+            }
+        }
+
+        // Reversal predicts the new direction at once
+        let (x, _) = predictor.predict((stop - 0.01, 0.5), at(start, 30), latency);
+        assert!(x < stop - 0.01);
+    }
+}
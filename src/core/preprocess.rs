@@ -0,0 +1,526 @@
+use crate::core::frame::{yuv_to_rgb, Frame, PixelFormat};
+use crate::core::transform::Affine;
+use crate::Result;
+use image::codecs::jpeg::JpegDecoder;
+use image::{ColorType, ImageDecoder};
+use std::io::Cursor;
+
+/// Bilinear tap: two neighbouring source indices and the weight of the second.
+/// Taps that land outside the frame are padding.
+#[derive(Copy, Clone, Debug)]
+struct Tap {
+    i0: usize,
+    i1: usize,
+    w: f32,
+    inside: bool,
+}
+
+/// Pixel-center aligned taps for `dst` outputs along one axis of an
+/// axis-aligned model-to-frame map `frame = scale * model + offset`.
+fn taps(src: usize, dst: usize, scale: f32, offset: f32) -> Vec<Tap> {
+    let last = src.saturating_sub(1);
+    (0..dst)
+        .map(|d| {
+            let f = scale * (d as f32 + 0.5) / dst as f32 + offset;
+            let s = (f * src as f32 - 0.5).clamp(0.0, last as f32);
+            let i0 = s as usize;
+            Tap { i0, i1: (i0 + 1).min(last), w: s - i0 as f32, inside: (0.0..=1.0).contains(&f) }
+        })
+        .collect()
+}
+
+/// Uncompressed pixels to resample; MJPEG is decoded into one of these first.
+struct Source<'a> {
+    data: &'a [u8],
+    width: usize,
+    height: usize,
+    format: PixelFormat,
+}
+
+struct Taps {
+    x: Vec<Tap>,
+    y: Vec<Tap>,
+    /// Rows of the half-height NV12 chroma plane.
+    chroma_y: Vec<Tap>,
+}
+
+/// Vertically blended source rows, reused across frames.
+#[derive(Default)]
+struct Rows {
+    main: Vec<f32>,
+    chroma: Vec<f32>,
+}
+
+/// Memory order of the model input tensor.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// Planar, `[1, 3, H, W]`.
+    #[default]
+    Nchw,
+    /// Interleaved, `[1, H, W, 3]`.
+    Nhwc,
+}
+
+/// Per-channel `(value - mean) / std`, applied after pixels are scaled to 0..=1.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Normalization {
+    pub mean: [f32; 3],
+    pub std: [f32; 3],
+}
+
+impl Normalization {
+    /// Plain 0..=1.
+    pub const UNIT: Normalization = Normalization { mean: [0.0; 3], std: [1.0; 3] };
+
+    /// Maps 0..=1 onto `lo..=hi`, e.g. `(-1.0, 1.0)`.
+    pub fn range(lo: f32, hi: f32) -> Self {
+        let std = 1.0 / (hi - lo);
+        Self { mean: [-lo * std; 3], std: [std; 3] }
+    }
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Self::UNIT
+    }
+}
+
+/// Reusable planar (NCHW) float input for a square model, filled straight from
+/// camera buffers. Resampling (bilinear), color conversion and normalization
+/// to 0..=1 happen in one pass over the rows the output actually needs.
+///
+/// The frame is placed through an [`Affine`] map (letterbox by default); areas
+/// of the input outside the frame are zero. Models that want another layout or
+/// value range get a final pass over the planes (see [`TensorInput::with_format`]).
+pub struct TensorInput {
+    size: usize,
+    data: Vec<f32>,
+    layout: Layout,
+    normalization: Normalization,
+    /// Interleaved copy of `data` for NHWC models.
+    packed: Vec<f32>,
+    /// Decoded MJPEG pixels, kept between frames.
+    scratch: Vec<u8>,
+    taps: Taps,
+    /// Frame size and transform the taps were built for.
+    built_for: Option<((usize, usize), Affine)>,
+    /// Frame-normalized to model-normalized map of the last fill.
+    transform: Affine,
+    rows: Rows,
+}
+
+impl TensorInput {
+    pub fn new(size: u32) -> Self {
+        let size = size as usize;
+        Self {
+            size,
+            data: vec![0.0; 3 * size * size],
+            layout: Layout::Nchw,
+            normalization: Normalization::UNIT,
+            packed: Vec::new(),
+            scratch: Vec::new(),
+            taps: Taps { x: Vec::new(), y: Vec::new(), chroma_y: Vec::new() },
+            built_for: None,
+            transform: Affine::IDENTITY,
+            rows: Rows::default(),
+        }
+    }
+
+    pub fn with_format(mut self, layout: Layout, normalization: Normalization) -> Self {
+        self.layout = layout;
+        self.normalization = normalization;
+        self.packed = match layout {
+            Layout::Nchw => Vec::new(),
+            Layout::Nhwc => vec![0.0; self.data.len()],
+        };
+        self
+    }
+
+    pub fn shape(&self) -> [usize; 4] {
+        match self.layout {
+            Layout::Nchw => [1, 3, self.size, self.size],
+            Layout::Nhwc => [1, self.size, self.size, 3],
+        }
+    }
+
+    pub fn data(&self) -> &[f32] {
+        match self.layout {
+            Layout::Nchw => &self.data,
+            Layout::Nhwc => &self.packed,
+        }
+    }
+
+    /// Frame-normalized to model-normalized map used by the last fill; invert it
+    /// to bring model outputs back onto the frame.
+    pub fn transform(&self) -> Affine {
+        self.transform
+    }
+
+    /// Letterboxed fill: the whole frame, undistorted, centered in the input.
+    pub fn fill(&mut self, frame: &Frame) -> Result<()> {
+        self.fill_with(frame, Affine::letterbox(frame.width, frame.height))
+    }
+
+    /// Fill through any frame-to-model map (crop, rotation, letterbox...).
+    pub fn fill_with(&mut self, frame: &Frame, transform: Affine) -> Result<()> {
+        frame.check()?;
+        let inverse = transform.inverse().ok_or_else(|| {
+            crate::AirLinkError::CoreError("Degenerate preprocessing transform".into())
+        })?;
+        self.transform = transform;
+
+        let source = match frame.format {
+            PixelFormat::Mjpeg => {
+                let (width, height, format) = decode_jpeg(&frame.data, &mut self.scratch)?;
+                Source { data: &self.scratch, width, height, format }
+            }
+            format => Source {
+                data: &frame.data,
+                width: frame.width as usize,
+                height: frame.height as usize,
+                format,
+            },
+        };
+        if source.width == 0 || source.height == 0 {
+            return Err(crate::AirLinkError::CoreError("Empty frame".into()));
+        }
+
+        if inverse.is_axis_aligned() {
+            let key = ((source.width, source.height), transform);
+            if self.built_for != Some(key) {
+                let [[sx, _, ox], [_, sy, oy]] = inverse.m;
+                self.taps = Taps {
+                    x: taps(source.width, self.size, sx, ox),
+                    y: taps(source.height, self.size, sy, oy),
+                    chroma_y: taps(source.height / 2, self.size, sy, oy),
+                };
+                self.built_for = Some(key);
+            }
+            resample(&source, &self.taps, &mut self.rows, &mut self.data, self.size);
+        } else {
+            // Rotations mix both axes, so sample pixel by pixel
+            sample_rotated(&source, &inverse, &mut self.data, self.size);
+        }
+
+        self.reformat();
+        Ok(())
+    }
+
+    /// Model-specific value range and layout, on top of the 0..=1 NCHW planes.
+    fn reformat(&mut self) {
+        let n = self.size * self.size;
+        if self.normalization != Normalization::UNIT {
+            let Normalization { mean, std } = self.normalization;
+            for (c, plane) in self.data.chunks_exact_mut(n).enumerate() {
+                let scale = 1.0 / std[c];
+                for v in plane {
+                    *v = (*v - mean[c]) * scale;
+                }
+            }
+        }
+
+        if self.layout == Layout::Nhwc {
+            let (r, rest) = self.data.split_at(n);
+            let (g, b) = rest.split_at(n);
+            for (i, px) in self.packed.chunks_exact_mut(3).enumerate() {
+                px.copy_from_slice(&[r[i], g[i], b[i]]);
+            }
+        }
+    }
+}
+
+pub(crate) fn decode_jpeg(data: &[u8], scratch: &mut Vec<u8>) -> Result<(usize, usize, PixelFormat)> {
+    let jpeg_err = |e: image::ImageError| crate::AirLinkError::CoreError(format!("MJPEG decode failed: {}", e));
+
+    let decoder = JpegDecoder::new(Cursor::new(data)).map_err(jpeg_err)?;
+    let (width, height) = decoder.dimensions();
+    let format = match decoder.color_type() {
+        ColorType::Rgb8 => PixelFormat::Rgb,
+        ColorType::L8 => PixelFormat::Gray,
+        other => {
+            return Err(crate::AirLinkError::CoreError(format!("Unsupported MJPEG color type {:?}", other)));
+        }
+    };
+
+    scratch.resize(decoder.total_bytes() as usize, 0);
+    decoder.read_image(scratch).map_err(jpeg_err)?;
+    Ok((width as usize, height as usize, format))
+}
+
+/// Picks the widest instruction set this CPU has; the loops below are written
+/// so the compiler vectorizes them.
+fn resample(src: &Source, taps: &Taps, rows: &mut Rows, out: &mut [f32], size: usize) {
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("avx2") && std::arch::is_x86_feature_detected!("fma") {
+        // SAFETY: both features were just detected on this CPU
+        unsafe { resample_avx2(src, taps, rows, out, size) };
+        return;
+    }
+    resample_rows(src, taps, rows, out, size);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+unsafe fn resample_avx2(src: &Source, taps: &Taps, rows: &mut Rows, out: &mut [f32], size: usize) {
+    resample_rows(src, taps, rows, out, size);
+}
+
+#[inline(always)]
+fn resample_rows(src: &Source, taps: &Taps, rows: &mut Rows, out: &mut [f32], size: usize) {
+    let (w, h) = (src.width, src.height);
+    let row_len = match src.format {
+        PixelFormat::Rgb | PixelFormat::Bgr => w * 3,
+        PixelFormat::Yuyv => w * 2,
+        PixelFormat::Gray | PixelFormat::Nv12 | PixelFormat::Mjpeg => w,
+    };
+    rows.main.resize(row_len, 0.0);
+    rows.chroma.resize(w, 0.0);
+
+    let (r_plane, rest) = out.split_at_mut(size * size);
+    let (g_plane, b_plane) = rest.split_at_mut(size * size);
+    let line = |offset: usize, len: usize, i: usize| &src.data[offset + i * len..][..len];
+    let lerp = |row: &[f32], a: usize, b: usize, t: f32| row[a] + (row[b] - row[a]) * t;
+
+    for (oy, ty) in taps.y.iter().enumerate() {
+        let r = &mut r_plane[oy * size..][..size];
+        let g = &mut g_plane[oy * size..][..size];
+        let b = &mut b_plane[oy * size..][..size];
+        if !ty.inside {
+            // Letterbox bar
+            for plane in [r, g, b] {
+                plane.fill(0.0);
+            }
+            continue;
+        }
+
+        // 1. Vertical: blend the two source rows, whatever their layout
+        blend(line(0, row_len, ty.i0), line(0, row_len, ty.i1), ty.w, &mut rows.main);
+        let row = &rows.main;
+
+        // 2. Horizontal: sample channels (R,G,B or Y,U,V) in the native layout
+        let yuv = match src.format {
+            PixelFormat::Rgb | PixelFormat::Bgr | PixelFormat::Mjpeg => {
+                let (ri, bi) = if src.format == PixelFormat::Bgr { (2, 0) } else { (0, 2) };
+                for (ox, tx) in taps.x.iter().enumerate() {
+                    let (p0, p1) = (tx.i0 * 3, tx.i1 * 3);
+                    r[ox] = lerp(row, p0 + ri, p1 + ri, tx.w);
+                    g[ox] = lerp(row, p0 + 1, p1 + 1, tx.w);
+                    b[ox] = lerp(row, p0 + bi, p1 + bi, tx.w);
+                }
+                false
+            }
+            PixelFormat::Gray => {
+                for (ox, tx) in taps.x.iter().enumerate() {
+                    let v = lerp(row, tx.i0, tx.i1, tx.w);
+                    (r[ox], g[ox], b[ox]) = (v, v, v);
+                }
+                false
+            }
+            PixelFormat::Yuyv => {
+                // Y0 U Y1 V: luma at 2x, chroma shared by each pixel pair
+                for (ox, tx) in taps.x.iter().enumerate() {
+                    let (c0, c1) = ((tx.i0 / 2) * 4, (tx.i1 / 2) * 4);
+                    r[ox] = lerp(row, tx.i0 * 2, tx.i1 * 2, tx.w);
+                    g[ox] = lerp(row, c0 + 1, c1 + 1, tx.w);
+                    b[ox] = lerp(row, c0 + 3, c1 + 3, tx.w);
+                }
+                true
+            }
+            PixelFormat::Nv12 => {
+                let tc = taps.chroma_y[oy];
+                let plane = w * h;
+                blend(line(plane, w, tc.i0), line(plane, w, tc.i1), tc.w, &mut rows.chroma);
+                let chroma = &rows.chroma;
+                for (ox, tx) in taps.x.iter().enumerate() {
+                    let (c0, c1) = (tx.i0 & !1, tx.i1 & !1);
+                    r[ox] = lerp(row, tx.i0, tx.i1, tx.w);
+                    g[ox] = lerp(chroma, c0, c1, tx.w);
+                    b[ox] = lerp(chroma, c0 + 1, c1 + 1, tx.w);
+                }
+                true
+            }
+        };
+
+        // 3. Color conversion and 0..=1 normalization over contiguous planes
+        finish_row(r, g, b, yuv);
+
+        for (ox, tx) in taps.x.iter().enumerate() {
+            if !tx.inside {
+                (r[ox], g[ox], b[ox]) = (0.0, 0.0, 0.0);
+            }
+        }
+    }
+}
+
+/// Native channels (R,G,B or Y,U,V) of one pixel.
+fn fetch(src: &Source, x: usize, y: usize) -> [f32; 3] {
+    let w = src.width;
+    let d = src.data;
+    match src.format {
+        PixelFormat::Rgb | PixelFormat::Mjpeg => {
+            let i = (y * w + x) * 3;
+            [d[i] as f32, d[i + 1] as f32, d[i + 2] as f32]
+        }
+        PixelFormat::Bgr => {
+            let i = (y * w + x) * 3;
+            [d[i + 2] as f32, d[i + 1] as f32, d[i] as f32]
+        }
+        PixelFormat::Gray => [d[y * w + x] as f32; 3],
+        PixelFormat::Yuyv => {
+            let pair = (y * w + (x & !1)) * 2;
+            [d[pair + (x & 1) * 2] as f32, d[pair + 1] as f32, d[pair + 3] as f32]
+        }
+        PixelFormat::Nv12 => {
+            let uv = w * src.height + (y / 2) * w + (x & !1);
+            [d[y * w + x] as f32, d[uv] as f32, d[uv + 1] as f32]
+        }
+    }
+}
+
+/// General path for transforms with rotation: bilinear sample per output pixel.
+fn sample_rotated(src: &Source, inverse: &Affine, out: &mut [f32], size: usize) {
+    let (w, h) = (src.width, src.height);
+    let yuv = matches!(src.format, PixelFormat::Yuyv | PixelFormat::Nv12);
+    let n = size * size;
+
+    for oy in 0..size {
+        for ox in 0..size {
+            let model = ((ox as f32 + 0.5) / size as f32, (oy as f32 + 0.5) / size as f32);
+            let (fx, fy) = inverse.apply(model);
+            let i = oy * size + ox;
+            if !(0.0..=1.0).contains(&fx) || !(0.0..=1.0).contains(&fy) {
+                (out[i], out[n + i], out[2 * n + i]) = (0.0, 0.0, 0.0);
+                continue;
+            }
+
+            let sx = (fx * w as f32 - 0.5).clamp(0.0, (w - 1) as f32);
+            let sy = (fy * h as f32 - 0.5).clamp(0.0, (h - 1) as f32);
+            let (x0, y0) = (sx as usize, sy as usize);
+            let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+            let (tx, ty) = (sx - x0 as f32, sy - y0 as f32);
+
+            let (p00, p10, p01, p11) = (fetch(src, x0, y0), fetch(src, x1, y0), fetch(src, x0, y1), fetch(src, x1, y1));
+            let mut c = [0.0; 3];
+            for k in 0..3 {
+                let top = p00[k] + (p10[k] - p00[k]) * tx;
+                let bottom = p01[k] + (p11[k] - p01[k]) * tx;
+                c[k] = top + (bottom - top) * ty;
+            }
+            let [r, g, b] = if yuv { yuv_to_rgb(c[0], c[1], c[2]) } else { c };
+            out[i] = (r / 255.0).clamp(0.0, 1.0);
+            out[n + i] = (g / 255.0).clamp(0.0, 1.0);
+            out[2 * n + i] = (b / 255.0).clamp(0.0, 1.0);
+        }
+    }
+}
+
+#[inline(always)]
+fn blend(a: &[u8], b: &[u8], t: f32, out: &mut [f32]) {
+    for ((o, &a), &b) in out.iter_mut().zip(a).zip(b) {
+        let a = a as f32;
+        *o = a + (b as f32 - a) * t;
+    }
+}
+
+#[inline(always)]
+fn finish_row(r: &mut [f32], g: &mut [f32], b: &mut [f32], yuv: bool) {
+    const NORM: f32 = 1.0 / 255.0;
+    for ((r, g), b) in r.iter_mut().zip(g.iter_mut()).zip(b.iter_mut()) {
+        let [cr, cg, cb] = if yuv { yuv_to_rgb(*r, *g, *b) } else { [*r, *g, *b] };
+        *r = (cr * NORM).clamp(0.0, 1.0);
+        *g = (cg * NORM).clamp(0.0, 1.0);
+        *b = (cb * NORM).clamp(0.0, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(format: PixelFormat, width: u32, height: u32, data: Vec<u8>) -> Frame {
+        Frame::new(data, width, height, format)
+    }
+
+    /// Mean of each output plane.
+    fn plane_means(input: &TensorInput) -> [f32; 3] {
+        let n = input.size * input.size;
+        let mean = |c: usize| input.data()[c * n..(c + 1) * n].iter().sum::<f32>() / n as f32;
+        [mean(0), mean(1), mean(2)]
+    }
+
+    #[test]
+    fn test_letterbox_pads_and_round_trips() {
+        // 8x4 white frame into 4x4: rows 0 and 3 are bars, rows 1-2 are the frame
+        let mut input = TensorInput::new(4);
+        input.fill(&frame(PixelFormat::Gray, 8, 4, vec![255; 32])).unwrap();
+        let red = &input.data()[..16];
+        assert!(red[..4].iter().chain(&red[12..]).all(|&v| v == 0.0));
+        assert!(red[4..12].iter().all(|&v| v == 1.0));
+
+        // A model-space point maps back onto the frame it came from
+        let back = input.transform().inverse().unwrap();
+        let (x, y) = back.apply((0.5, 0.25));
+        assert!((x - 0.5).abs() < 1e-6 && y.abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_rotated_fill_matches_aligned() {
+        // A half turn of a uniform frame only moves the bars
+        let data = vec![200; 8 * 8 * 3];
+        let mut input = TensorInput::new(4);
+        let half_turn = Affine::rotate_about((0.5, 0.5), std::f32::consts::PI);
+        input.fill_with(&frame(PixelFormat::Rgb, 8, 8, data), half_turn).unwrap();
+        assert!(input.data().iter().all(|&v| (v - 200.0 / 255.0).abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_rgb_downscale_averages_blocks() {
+        // 2x2 blocks of 0 and 255 columns: every output pixel straddles one of each
+        let (w, h) = (8u32, 8u32);
+        let data = (0..w * h).flat_map(|i| if i % 2 == 0 { [0, 0, 0] } else { [255, 255, 255] }).collect();
+        let mut input = TensorInput::new(4);
+        input.fill(&frame(PixelFormat::Rgb, w, h, data)).unwrap();
+
+        assert_eq!(input.shape(), [1, 3, 4, 4]);
+        assert!(input.data().iter().all(|&v| (v - 0.5).abs() < 1e-4));
+    }
+
+    #[test]
+    fn test_yuv_formats_match_rgb() {
+        // BT.601 video-range red, as YUYV and as NV12
+        let (w, h) = (16u32, 8u32);
+        let (y, u, v) = (81u8, 90u8, 240u8);
+        let yuyv = (0..w * h / 2).flat_map(|_| [y, u, y, v]).collect();
+        let mut nv12 = vec![y; (w * h) as usize];
+        nv12.extend((0..w * h / 4).flat_map(|_| [u, v]));
+
+        let mut input = TensorInput::new(8);
+        for frame in [frame(PixelFormat::Yuyv, w, h, yuyv), frame(PixelFormat::Nv12, w, h, nv12)] {
+            // Stretched, so no letterbox bars dilute the means
+            input.fill_with(&frame, Affine::IDENTITY).unwrap();
+            let [r, g, b] = plane_means(&input);
+            assert!(r > 0.98 && g < 0.02 && b < 0.02, "{:?}: {:?}", frame.format, (r, g, b));
+
+            // The display path agrees with the tensor path
+            let rgb = frame.to_image().unwrap().to_rgb8();
+            assert!(rgb.get_pixel(3, 3)[0] > 250 && rgb.get_pixel(3, 3)[1] < 5);
+        }
+    }
+
+    #[test]
+    fn test_nhwc_and_normalization() {
+        let mut input = TensorInput::new(2).with_format(Layout::Nhwc, Normalization::range(-1.0, 1.0));
+        input.fill(&frame(PixelFormat::Rgb, 2, 2, [255, 0, 0].repeat(4))).unwrap();
+        assert_eq!(input.shape(), [1, 2, 2, 3]);
+        assert_eq!(&input.data()[..6], &[1.0, -1.0, -1.0, 1.0, -1.0, -1.0]);
+    }
+
+    #[test]
+    fn test_bgr_and_short_frames() {
+        let mut input = TensorInput::new(2);
+        input.fill(&frame(PixelFormat::Bgr, 2, 2, [255, 0, 0].repeat(4))).unwrap();
+        assert_eq!(plane_means(&input), [0.0, 0.0, 1.0]);
+
+        assert!(input.fill(&frame(PixelFormat::Yuyv, 4, 4, vec![0; 10])).is_err());
+    }
+}
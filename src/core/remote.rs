@@ -0,0 +1,684 @@
+use crate::core::input::{Button, MouseManager, PointerEvent};
+use crate::core::udp::UdpSender;
+use crate::Result;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender, TrySendError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+pub const MAGIC: [u8; 4] = *b"ALRP";
+pub const VERSION: u8 = 2;
+/// Every message has the same size, so TCP needs no extra framing.
+pub const PACKET_LEN: usize = 64;
+/// Authenticated bytes; the truncated HMAC-SHA256 tag follows.
+const BODY_LEN: usize = 48;
+const MIN_KEY_LEN: usize = 8;
+
+/// A held button is let go when the link has been quiet this long.
+const LINK_TIMEOUT: Duration = Duration::from_secs(1);
+/// Missed clicks replayed at most; more means the link was down, not lossy.
+const MAX_REPLAY: u16 = 4;
+const TCP_RETRY: Duration = Duration::from_secs(1);
+/// An unanswered hello is sent again after this long.
+const HELLO_RETRY: Duration = Duration::from_millis(250);
+/// Packets waiting for the pointer loop; more are dropped and show up as lost.
+const PACKET_QUEUE: usize = 64;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Transport {
+    Udp,
+    Tcp,
+}
+
+/// Full pointer state, repeated in every packet. Moves are absolute and held
+/// buttons are levels, so any later packet repairs a lost one; clicks are
+/// counters so the receiver can replay the ones it missed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RemoteState {
+    pub position: Option<(i32, i32)>,
+    pub left_down: bool,
+    pub right_down: bool,
+    pub left_clicks: u16,
+    pub right_clicks: u16,
+    pub resets: u16,
+}
+
+impl RemoteState {
+    /// Folds one `MouseManager` call into the state.
+    pub fn apply(&mut self, event: PointerEvent) {
+        match event {
+            PointerEvent::Move { x, y } => self.position = Some((x, y)),
+            PointerEvent::Press(Button::Left) => self.left_down = true,
+            PointerEvent::Press(Button::Right) => self.right_down = true,
+            PointerEvent::Release(Button::Left) => self.left_down = false,
+            PointerEvent::Release(Button::Right) => self.right_down = false,
+            PointerEvent::Click(Button::Left) => self.left_clicks = self.left_clicks.wrapping_add(1),
+            PointerEvent::Click(Button::Right) => self.right_clicks = self.right_clicks.wrapping_add(1),
+            PointerEvent::Reset => {
+                self.resets = self.resets.wrapping_add(1);
+                self.position = None;
+                self.left_down = false;
+                self.right_down = false;
+            }
+        }
+    }
+}
+
+/// Link messages. The receiver picks a random epoch when it starts and hands
+/// out sessions within it; state packets carry both, so nothing recorded
+/// before a receiver restart, or from an older session, is accepted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// Sender asks for a session; the nonce ties the answer to this request.
+    Hello { nonce: u64 },
+    /// Receiver grants `session` in its current `epoch`.
+    Welcome { epoch: u64, session: u64, nonce: u64 },
+    /// Receiver tells the sender its session is from another epoch.
+    Rekey { epoch: u64, session: u64 },
+    State { epoch: u64, session: u64, seq: u64, state: RemoteState },
+}
+
+fn mac(key: &[u8]) -> Hmac<Sha256> {
+    Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length")
+}
+
+pub fn encode_packet(key: &[u8], message: &Message) -> [u8; PACKET_LEN] {
+    let (kind, epoch, session, seq, state) = match *message {
+        Message::Hello { nonce } => (1, 0, 0, nonce, RemoteState::default()),
+        Message::Welcome { epoch, session, nonce } => (2, epoch, session, nonce, RemoteState::default()),
+        Message::Rekey { epoch, session } => (3, epoch, session, 0, RemoteState::default()),
+        Message::State { epoch, session, seq, state } => (0, epoch, session, seq, state),
+    };
+    let flags = state.position.is_some() as u8 | (state.left_down as u8) << 1 | (state.right_down as u8) << 2;
+    let (x, y) = state.position.unwrap_or_default();
+
+    let mut packet = [0u8; PACKET_LEN];
+    packet[..4].copy_from_slice(&MAGIC);
+    packet[4] = VERSION;
+    packet[5] = kind;
+    packet[6] = flags;
+    packet[8..16].copy_from_slice(&epoch.to_le_bytes());
+    packet[16..24].copy_from_slice(&session.to_le_bytes());
+    packet[24..32].copy_from_slice(&seq.to_le_bytes());
+    packet[32..36].copy_from_slice(&x.to_le_bytes());
+    packet[36..40].copy_from_slice(&y.to_le_bytes());
+    packet[40..42].copy_from_slice(&state.left_clicks.to_le_bytes());
+    packet[42..44].copy_from_slice(&state.right_clicks.to_le_bytes());
+    packet[44..46].copy_from_slice(&state.resets.to_le_bytes());
+
+    let mut mac = mac(key);
+    mac.update(&packet[..BODY_LEN]);
+    let tag = mac.finalize().into_bytes();
+    packet[BODY_LEN..].copy_from_slice(&tag[..PACKET_LEN - BODY_LEN]);
+    packet
+}
+
+/// The message in `packet`, or `None` for anything not sent with `key`.
+pub fn decode_packet(key: &[u8], packet: &[u8]) -> Option<Message> {
+    if packet.len() != PACKET_LEN || packet[..4] != MAGIC || packet[4] != VERSION {
+        return None;
+    }
+    let mut mac = mac(key);
+    mac.update(&packet[..BODY_LEN]);
+    mac.verify_truncated_left(&packet[BODY_LEN..]).ok()?;
+
+    let u16_at = |i: usize| u16::from_le_bytes([packet[i], packet[i + 1]]);
+    let u64_at = |i: usize| u64::from_le_bytes(packet[i..i + 8].try_into().unwrap());
+    let i32_at = |i: usize| i32::from_le_bytes(packet[i..i + 4].try_into().unwrap());
+    let (epoch, session, seq) = (u64_at(8), u64_at(16), u64_at(24));
+    match packet[5] {
+        0 => {
+            let flags = packet[6];
+            let state = RemoteState {
+                position: (flags & 1 != 0).then(|| (i32_at(32), i32_at(36))),
+                left_down: flags & 2 != 0,
+                right_down: flags & 4 != 0,
+                left_clicks: u16_at(40),
+                right_clicks: u16_at(42),
+                resets: u16_at(44),
+            };
+            Some(Message::State { epoch, session, seq, state })
+        }
+        1 => Some(Message::Hello { nonce: seq }),
+        2 => Some(Message::Welcome { epoch, session, nonce: seq }),
+        3 => Some(Message::Rekey { epoch, session }),
+        _ => None,
+    }
+}
+
+/// Unpredictable enough for epochs and nonces: std seeds `RandomState` from
+/// the OS, and the clock keeps two calls apart.
+fn random_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos()));
+    hasher.finish()
+}
+
+fn check_key(key: &str) -> Result<Vec<u8>> {
+    if key.len() < MIN_KEY_LEN {
+        return Err(crate::AirLinkError::CliError(format!(
+            "Remote key must be at least {} characters", MIN_KEY_LEN
+        )));
+    }
+    Ok(key.as_bytes().to_vec())
+}
+
+enum Link {
+    Udp(UdpSender),
+    /// `None` while disconnected; reconnects are retried at most every [`TCP_RETRY`].
+    Tcp(Option<TcpStream>, Instant),
+}
+
+/// Tracking side: turns `MouseManager` calls into authenticated state packets.
+pub struct RemoteSender {
+    key: Vec<u8>,
+    target: SocketAddr,
+    link: Link,
+    /// `(epoch, session)` granted by the receiver; nothing is sent without one.
+    session: Option<(u64, u64)>,
+    /// Nonce of the unanswered UDP hello and when it went out.
+    hello: Option<(u64, Instant)>,
+    seq: u64,
+    state: RemoteState,
+}
+
+impl RemoteSender {
+    pub fn connect(target: &str, key: &str, transport: Transport) -> Result<Self> {
+        let key = check_key(key)?;
+        let addr = target.to_socket_addrs()?.next().ok_or_else(|| {
+            crate::AirLinkError::CliError(format!("Remote target `{}` did not resolve", target))
+        })?;
+        let link = match transport {
+            Transport::Udp => Link::Udp(UdpSender::new(addr)?),
+            Transport::Tcp => Link::Tcp(None, Instant::now()),
+        };
+        let mut sender = Self { key, target: addr, link, session: None, hello: None, seq: 0, state: RemoteState::default() };
+        if let Link::Tcp(stream, _) = &mut sender.link {
+            let (connected, session) = tcp_connect(addr, &sender.key)?;
+            *stream = Some(connected);
+            sender.session = Some(session);
+        }
+        info!("🛰️  Sending pointer events to {} over {:?}", addr, transport);
+        Ok(sender)
+    }
+
+    pub fn send(&mut self, event: PointerEvent) -> Result<()> {
+        self.state.apply(event);
+        self.handshake()?;
+        let Some((epoch, session)) = self.session else { return Ok(()) };
+        self.seq += 1;
+        let packet = encode_packet(&self.key, &Message::State { epoch, session, seq: self.seq, state: self.state });
+
+        match &mut self.link {
+            // Lost datagrams are repaired by the next packet
+            Link::Udp(udp) => udp.send(&packet)?,
+            Link::Tcp(stream, _) => {
+                if let Some(s) = stream
+                    && let Err(e) = s.write_all(&packet)
+                {
+                    warn!("🛰️  Lost connection to {} ({}), retrying", self.target, e);
+                    *stream = None;
+                    self.session = None;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets a session from the receiver, or a new one after it restarted.
+    /// Never blocks longer than a TCP connect.
+    fn handshake(&mut self) -> Result<()> {
+        match &mut self.link {
+            Link::Udp(udp) => {
+                let mut buf = [0u8; PACKET_LEN];
+                while let Some(len) = udp.try_recv(&mut buf) {
+                    match decode_packet(&self.key, &buf[..len]) {
+                        Some(Message::Welcome { epoch, session, nonce })
+                            if self.hello.is_some_and(|(sent, _)| sent == nonce) =>
+                        {
+                            info!("🛰️  Receiver {} granted session {:x}", self.target, session);
+                            self.session = Some((epoch, session));
+                            self.hello = None;
+                            self.seq = 0;
+                        }
+                        Some(Message::Rekey { epoch, session }) if self.session == Some((epoch, session)) => {
+                            info!("🛰️  Receiver {} restarted, asking for a new session", self.target);
+                            self.session = None;
+                        }
+                        _ => {}
+                    }
+                }
+                if self.session.is_none() && self.hello.is_none_or(|(_, sent)| sent.elapsed() >= HELLO_RETRY) {
+                    let nonce = random_u64();
+                    udp.send(&encode_packet(&self.key, &Message::Hello { nonce }))?;
+                    self.hello = Some((nonce, Instant::now()));
+                }
+            }
+            Link::Tcp(stream, last_attempt) => {
+                if stream.is_none() && last_attempt.elapsed() >= TCP_RETRY {
+                    *last_attempt = Instant::now();
+                    if let Ok((connected, session)) = tcp_connect(self.target, &self.key) {
+                        info!("🛰️  Reconnected to {}", self.target);
+                        *stream = Some(connected);
+                        self.session = Some(session);
+                        self.seq = 0;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Connects and asks for a session, returning the stream and `(epoch, session)`.
+fn tcp_connect(addr: SocketAddr, key: &[u8]) -> Result<(TcpStream, (u64, u64))> {
+    // Short timeouts: this runs on the pipeline thread
+    let mut stream = TcpStream::connect_timeout(&addr, Duration::from_millis(500))?;
+    stream.set_nodelay(true)?;
+    stream.set_write_timeout(Some(Duration::from_millis(100)))?;
+    stream.set_read_timeout(Some(Duration::from_millis(500)))?;
+
+    let nonce = random_u64();
+    stream.write_all(&encode_packet(key, &Message::Hello { nonce }))?;
+    let mut reply = [0u8; PACKET_LEN];
+    stream.read_exact(&mut reply)?;
+    match decode_packet(key, &reply) {
+        Some(Message::Welcome { epoch, session, nonce: echoed }) if echoed == nonce => Ok((stream, (epoch, session))),
+        _ => Err(crate::AirLinkError::CoreError(format!("{} did not grant a session; check the key", addr))),
+    }
+}
+
+/// Link counters, logged by `serve-input`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinkStats {
+    pub accepted: u64,
+    /// Sequence gaps: packets that never arrived.
+    pub lost: u64,
+    /// Duplicates and packets overtaken by a newer one.
+    pub stale: u64,
+    /// Wrong key, corrupt, or from an older session or another epoch.
+    pub rejected: u64,
+}
+
+/// What [`RemoteReceiver::accept`] made of a packet.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Received {
+    pub events: Vec<PointerEvent>,
+    /// Handshake answer for whoever sent the packet.
+    pub reply: Option<[u8; PACKET_LEN]>,
+}
+
+/// Display side: checks packets and turns state changes back into events.
+pub struct RemoteReceiver {
+    key: Vec<u8>,
+    /// Picked at start; sessions granted before a restart carry another one.
+    epoch: u64,
+    /// Last session granted, so later senders get higher numbers.
+    granted: u64,
+    /// `(session, last seq)` of the sender being followed.
+    current: Option<(u64, u64)>,
+    /// Last session sent a rekey, to log it once.
+    rekeyed: Option<(u64, u64)>,
+    state: RemoteState,
+    last_packet: Option<Instant>,
+    pub stats: LinkStats,
+}
+
+impl RemoteReceiver {
+    pub fn new(key: &str) -> Result<Self> {
+        Ok(Self {
+            key: check_key(key)?,
+            epoch: random_u64(),
+            granted: 0,
+            current: None,
+            rekeyed: None,
+            state: RemoteState::default(),
+            last_packet: None,
+            stats: LinkStats::default(),
+        })
+    }
+
+    /// Answers handshakes, and returns the events that bring the local
+    /// pointer up to date with a state packet.
+    pub fn accept(&mut self, packet: &[u8], now: Instant) -> Received {
+        let (session, seq, next) = match decode_packet(&self.key, packet) {
+            Some(Message::Hello { nonce }) => {
+                self.granted += 1;
+                let welcome = Message::Welcome { epoch: self.epoch, session: self.granted, nonce };
+                return Received { events: Vec::new(), reply: Some(encode_packet(&self.key, &welcome)) };
+            }
+            Some(Message::State { epoch, session, .. }) if epoch != self.epoch => {
+                if self.rekeyed.replace((epoch, session)) != Some((epoch, session)) {
+                    info!("🛰️  Sender session {:x} predates this receiver, asking for a new one", session);
+                }
+                self.stats.rejected += 1;
+                let rekey = Message::Rekey { epoch, session };
+                return Received { events: Vec::new(), reply: Some(encode_packet(&self.key, &rekey)) };
+            }
+            Some(Message::State { session, seq, state, .. }) => (session, seq, state),
+            _ => {
+                self.stats.rejected += 1;
+                return Received::default();
+            }
+        };
+
+        let mut events = Vec::new();
+        match self.current {
+            Some((current, _)) if session < current => {
+                // Replayed or from a sender that has since reconnected
+                self.stats.rejected += 1;
+                return Received::default();
+            }
+            Some((current, last)) if session == current => {
+                if seq <= last {
+                    self.stats.stale += 1;
+                    return Received::default();
+                }
+                self.stats.lost += seq - last - 1;
+            }
+            _ => {
+                // New sender: start from a clean pointer, without replaying its history
+                info!("🛰️  Following sender session {:x}", session);
+                events.push(PointerEvent::Reset);
+                self.state = RemoteState { position: None, left_down: false, right_down: false, ..next };
+            }
+        }
+        self.current = Some((session, seq));
+        self.last_packet = Some(now);
+        self.stats.accepted += 1;
+
+        let mut previous = std::mem::replace(&mut self.state, next);
+        if next.resets != previous.resets {
+            events.push(PointerEvent::Reset);
+            previous.position = None;
+            previous.left_down = false;
+            previous.right_down = false;
+        }
+        if let Some((x, y)) = next.position.filter(|_| next.position != previous.position) {
+            events.push(PointerEvent::Move { x, y });
+        }
+        for (button, before, after) in [
+            (Button::Left, previous.left_clicks, next.left_clicks),
+            (Button::Right, previous.right_clicks, next.right_clicks),
+        ] {
+            let missed = after.wrapping_sub(before).min(MAX_REPLAY);
+            events.extend((0..missed).map(|_| PointerEvent::Click(button)));
+        }
+        for (button, before, after) in [
+            (Button::Left, previous.left_down, next.left_down),
+            (Button::Right, previous.right_down, next.right_down),
+        ] {
+            if after != before {
+                events.push(if after { PointerEvent::Press(button) } else { PointerEvent::Release(button) });
+            }
+        }
+        Received { events, reply: None }
+    }
+
+    /// Lets go of held buttons once the link has been quiet for [`LINK_TIMEOUT`],
+    /// so a lost release can't leave a drag stuck.
+    pub fn expire(&mut self, now: Instant) -> Vec<PointerEvent> {
+        let quiet = self.last_packet.is_some_and(|t| now.duration_since(t) >= LINK_TIMEOUT);
+        if !quiet || !(self.state.left_down || self.state.right_down) {
+            return Vec::new();
+        }
+        warn!("🛰️  Link quiet for {:?}, releasing buttons", LINK_TIMEOUT);
+        let mut events = Vec::new();
+        for (button, held) in [(Button::Left, &mut self.state.left_down), (Button::Right, &mut self.state.right_down)] {
+            if std::mem::take(held) {
+                events.push(PointerEvent::Release(button));
+            }
+        }
+        events
+    }
+}
+
+/// Where a packet came from, for the handshake reply.
+enum Peer {
+    Udp(SocketAddr),
+    Tcp(Arc<TcpStream>),
+}
+
+/// `serve-input`: owns the local pointer device and applies whatever an
+/// authenticated sender asks for. Runs until the process is stopped.
+pub fn serve(listen: &str, key: &str, transport: Transport, mouse: &mut MouseManager) -> Result<()> {
+    let mut receiver = RemoteReceiver::new(key)?;
+    let (tx, packets) = mpsc::sync_channel::<(Vec<u8>, Peer)>(PACKET_QUEUE);
+
+    let mut udp_reply = None;
+    match transport {
+        Transport::Udp => {
+            let socket = UdpSocket::bind(listen)?;
+            info!("🛰️  Waiting for pointer events on udp://{}", socket.local_addr()?);
+            udp_reply = Some(socket.try_clone()?);
+            std::thread::spawn(move || {
+                let mut buf = [0u8; 2048];
+                while let Ok((len, from)) = socket.recv_from(&mut buf) {
+                    // A full queue means the pointer loop is behind: drop rather than buffer
+                    if let Err(TrySendError::Disconnected(_)) = tx.try_send((buf[..len].to_vec(), Peer::Udp(from))) {
+                        break;
+                    }
+                }
+            });
+        }
+        Transport::Tcp => {
+            let listener = TcpListener::bind(listen)?;
+            info!("🛰️  Waiting for pointer events on tcp://{}", listener.local_addr()?);
+            std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let tx = tx.clone();
+                    std::thread::spawn(move || read_packets(stream, tx));
+                }
+            });
+        }
+    }
+
+    let mut reported = LinkStats::default();
+    let mut last_report = Instant::now();
+    loop {
+        let events = match packets.recv_timeout(LINK_TIMEOUT / 4) {
+            Ok((packet, peer)) => {
+                let received = receiver.accept(&packet, Instant::now());
+                if let Some(reply) = received.reply {
+                    let sent = match (&peer, &udp_reply) {
+                        (Peer::Udp(addr), Some(socket)) => socket.send_to(&reply, addr).map(|_| ()),
+                        (Peer::Tcp(stream), _) => (&**stream).write_all(&reply),
+                        (Peer::Udp(_), None) => Ok(()),
+                    };
+                    if let Err(e) = sent {
+                        warn!("🛰️  Could not answer a sender: {}", e);
+                    }
+                }
+                received.events
+            }
+            Err(RecvTimeoutError::Timeout) => receiver.expire(Instant::now()),
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(crate::AirLinkError::CoreError("Remote listener stopped".into()));
+            }
+        };
+        for event in events {
+            if let Err(e) = mouse.apply(event) {
+                println!("❌ MOUSE ERROR: {}", e);
+            }
+        }
+
+        if last_report.elapsed() >= Duration::from_secs(10) && receiver.stats != reported {
+            let s = receiver.stats;
+            println!("Link - accepted: {} | lost: {} | stale: {} | rejected: {}", s.accepted, s.lost, s.stale, s.rejected);
+            reported = s;
+            last_report = Instant::now();
+        }
+    }
+}
+
+fn read_packets(stream: TcpStream, tx: SyncSender<(Vec<u8>, Peer)>) {
+    let peer = stream.peer_addr().ok();
+    info!("🛰️  Sender {:?} connected", peer);
+    let stream = Arc::new(stream);
+    let mut packet = [0u8; PACKET_LEN];
+    while (&*stream).read_exact(&mut packet).is_ok() {
+        if let Err(TrySendError::Disconnected(_)) = tx.try_send((packet.to_vec(), Peer::Tcp(Arc::clone(&stream)))) {
+            return;
+        }
+    }
+    info!("🛰️  Sender {:?} disconnected", peer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "correct horse battery";
+
+    fn state_packet(receiver: &RemoteReceiver, session: u64, seq: u64, state: RemoteState) -> [u8; PACKET_LEN] {
+        encode_packet(KEY.as_bytes(), &Message::State { epoch: receiver.epoch, session, seq, state })
+    }
+
+    /// Plays `serve-input` on `socket` until the sender has a session.
+    fn grant_udp(socket: &UdpSocket, receiver: &mut RemoteReceiver, sender: &mut RemoteSender) {
+        sender.send(PointerEvent::Move { x: 0, y: 0 }).unwrap();
+        let mut buf = [0u8; 128];
+        let (len, from) = socket.recv_from(&mut buf).unwrap();
+        let reply = receiver.accept(&buf[..len], Instant::now()).reply.expect("welcome");
+        socket.send_to(&reply, from).unwrap();
+        // Let the welcome arrive before the next send polls for it
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_packets_are_authenticated() {
+        let state = RemoteState { position: Some((-5, 1080)), left_down: true, left_clicks: 3, ..RemoteState::default() };
+        let message = Message::State { epoch: 7, session: 9, seq: 42, state };
+        let packet = encode_packet(KEY.as_bytes(), &message);
+        assert_eq!(decode_packet(KEY.as_bytes(), &packet), Some(message));
+        assert_eq!(decode_packet(b"wrong key!", &packet), None);
+
+        let mut tampered = packet;
+        tampered[8] ^= 1;
+        assert_eq!(decode_packet(KEY.as_bytes(), &tampered), None);
+        assert!(RemoteReceiver::new("short").is_err());
+    }
+
+    #[test]
+    fn test_loss_recovery_over_udp() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let mut sender = RemoteSender::connect(&socket.local_addr().unwrap().to_string(), KEY, Transport::Udp).unwrap();
+        let mut receiver = RemoteReceiver::new(KEY).unwrap();
+        grant_udp(&socket, &mut receiver, &mut sender);
+
+        let sent = [
+            PointerEvent::Move { x: 10, y: 10 },
+            PointerEvent::Click(Button::Left),
+            PointerEvent::Press(Button::Left),
+            PointerEvent::Move { x: 20, y: 20 },
+            PointerEvent::Release(Button::Left),
+        ];
+        let mut packets = Vec::new();
+        for event in sent {
+            sender.send(event).unwrap();
+            let mut buf = [0u8; 128];
+            let len = socket.recv(&mut buf).unwrap();
+            packets.push(buf[..len].to_vec());
+        }
+
+        let now = Instant::now();
+        let mut accept = |packet: &[u8]| receiver.accept(packet, now).events;
+        assert_eq!(accept(&packets[0]), [PointerEvent::Reset, PointerEvent::Move { x: 10, y: 10 }]);
+        // The click and the press are lost; the next packet carries both
+        assert_eq!(accept(&packets[3]), [
+            PointerEvent::Move { x: 20, y: 20 },
+            PointerEvent::Click(Button::Left),
+            PointerEvent::Press(Button::Left),
+        ]);
+        assert_eq!(accept(&packets[1]), [], "overtaken");
+        assert_eq!(accept(&packets[4]), [PointerEvent::Release(Button::Left)]);
+        let mut forged = packets[4].clone();
+        forged[24] = 99;
+        assert_eq!(accept(&forged), []);
+        assert_eq!(receiver.stats, LinkStats { accepted: 3, lost: 2, stale: 1, rejected: 1 });
+
+        // A release that never arrives is made up once the link goes quiet
+        let pressed = RemoteState { left_down: true, ..RemoteState::default() };
+        receiver.accept(&state_packet(&receiver, receiver.granted, 10, pressed), now);
+        assert_eq!(receiver.expire(now + Duration::from_millis(500)), []);
+        assert_eq!(receiver.expire(now + LINK_TIMEOUT), [PointerEvent::Release(Button::Left)]);
+        assert_eq!(receiver.expire(now + LINK_TIMEOUT * 2), []);
+    }
+
+    #[test]
+    fn test_tcp_framing() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = mpsc::sync_channel(PACKET_QUEUE);
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            read_packets(stream, tx);
+        });
+        // Answers the hello the way `serve` does
+        let mut receiver = RemoteReceiver::new(KEY).unwrap();
+        let serving = std::thread::spawn(move || {
+            let mut events = Vec::new();
+            while let Ok((packet, peer)) = rx.recv_timeout(Duration::from_secs(2)) {
+                let received = receiver.accept(&packet, Instant::now());
+                if let (Some(reply), Peer::Tcp(stream)) = (received.reply, peer) {
+                    (&*stream).write_all(&reply).unwrap();
+                }
+                events.extend(received.events);
+                if events.len() == 3 {
+                    break;
+                }
+            }
+            events
+        });
+
+        let mut sender = RemoteSender::connect(&addr.to_string(), KEY, Transport::Tcp).unwrap();
+        sender.send(PointerEvent::Move { x: 1, y: 2 }).unwrap();
+        sender.send(PointerEvent::Click(Button::Right)).unwrap();
+        assert_eq!(serving.join().unwrap(), [
+            PointerEvent::Reset,
+            PointerEvent::Move { x: 1, y: 2 },
+            PointerEvent::Click(Button::Right),
+        ]);
+    }
+
+    #[test]
+    fn test_receiver_restart_rekeys_the_sender() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let mut sender = RemoteSender::connect(&socket.local_addr().unwrap().to_string(), KEY, Transport::Udp).unwrap();
+        let mut before = RemoteReceiver::new(KEY).unwrap();
+        grant_udp(&socket, &mut before, &mut sender);
+        sender.send(PointerEvent::Move { x: 1, y: 1 }).unwrap();
+        let mut buf = [0u8; 128];
+        let (len, from) = socket.recv_from(&mut buf).unwrap();
+        let recorded = buf[..len].to_vec();
+
+        // After a restart the recorded packet is refused, and its sender told to rekey
+        let mut receiver = RemoteReceiver::new(KEY).unwrap();
+        let now = Instant::now();
+        let received = receiver.accept(&recorded, now);
+        assert_eq!(received.events, []);
+        assert_eq!(receiver.stats.rejected, 1);
+        socket.send_to(&received.reply.expect("rekey"), from).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+
+        // The sender asks again and picks up where it is, without a restart
+        sender.send(PointerEvent::Move { x: 2, y: 2 }).unwrap();
+        let len = socket.recv(&mut buf).unwrap();
+        assert!(matches!(decode_packet(KEY.as_bytes(), &buf[..len]), Some(Message::Hello { .. })));
+        let welcome = receiver.accept(&buf[..len], now).reply.expect("welcome");
+        socket.send_to(&welcome, from).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        sender.send(PointerEvent::Move { x: 3, y: 3 }).unwrap();
+        let len = socket.recv(&mut buf).unwrap();
+        assert_eq!(receiver.accept(&buf[..len], now).events, [PointerEvent::Reset, PointerEvent::Move { x: 3, y: 3 }]);
+        assert_eq!(receiver.accept(&recorded, now).events, []);
+    }
+}
@@ -0,0 +1,289 @@
+use crate::core::camera::CameraConfig;
+use crate::core::frame::{Frame, PixelFormat};
+use crate::core::CameraManager;
+use crate::Result;
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// One frame from a [`FrameSource`].
+pub struct SourceFrame {
+    pub frame: Frame,
+    /// Seconds from the first frame: dequeue time for cameras, media time for
+    /// videos, `None` for still images.
+    pub time: Option<f64>,
+    /// File name, for image folders.
+    pub name: Option<String>,
+}
+
+enum Input {
+    Camera { camera: Box<CameraManager>, start: Option<Instant> },
+    /// `ffmpeg` decoding to a stream of PPM images on its stdout. Its stderr
+    /// is drained on a thread, so a chatty decoder can't block on a full pipe.
+    Video { child: Child, stdout: BufReader<ChildStdout>, stderr: Option<JoinHandle<String>>, fps: Option<f64> },
+    Images { paths: Vec<PathBuf>, next: usize },
+}
+
+/// Frames for offline tools (`dump`, `eval`...): a live camera, a video file
+/// or a folder of images, in order.
+pub struct FrameSource {
+    input: Input,
+    count: u64,
+}
+
+/// Files the `image` crate can decode, by extension.
+pub fn is_image(path: &Path) -> bool {
+    image::ImageFormat::from_path(path).is_ok_and(|f| f.reading_enabled())
+}
+
+/// Images in `dir`, sorted by file name.
+pub fn list_images(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && is_image(path))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+impl FrameSource {
+    pub fn camera(config: CameraConfig) -> Result<Self> {
+        let mut camera = CameraManager::new(config)?;
+        camera.start()?;
+        Ok(Self { input: Input::Camera { camera: Box::new(camera), start: None }, count: 0 })
+    }
+
+    /// Decodes `path` with `ffmpeg`, which must be on `PATH`.
+    pub fn video(path: &Path) -> Result<Self> {
+        let mut ffmpeg = Command::new("ffmpeg");
+        ffmpeg.args(["-v", "error", "-nostdin", "-i"])
+            .arg(path)
+            .args(["-f", "image2pipe", "-vcodec", "ppm", "-"]);
+        let fps = probe_fps(path);
+        let source = Self::decoder(ffmpeg, fps)
+            .map_err(|e| crate::AirLinkError::CoreError(format!("Cannot run ffmpeg to decode {} ({})", path.display(), e)))?;
+        info!("🎞️  Decoding {} at {}", path.display(), fps.map_or("unknown rate".to_string(), |f| format!("{:.2} fps", f)));
+        Ok(source)
+    }
+
+    /// Reads PPM frames from `command`'s stdout until it exits.
+    fn decoder(mut command: Command, fps: Option<f64>) -> std::io::Result<Self> {
+        let mut child = command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+        let mut pipe = child.stderr.take().expect("stderr is piped");
+        let stderr = std::thread::spawn(move || {
+            let mut text = String::new();
+            let _ = pipe.read_to_string(&mut text);
+            text
+        });
+        Ok(Self { input: Input::Video { child, stdout, stderr: Some(stderr), fps }, count: 0 })
+    }
+
+    pub fn images(dir: &Path) -> Result<Self> {
+        let paths = list_images(dir)?;
+        if paths.is_empty() {
+            return Err(crate::AirLinkError::CliError(format!("No images in {}", dir.display())));
+        }
+        info!("🖼️  {} images in {}", paths.len(), dir.display());
+        Ok(Self { input: Input::Images { paths, next: 0 }, count: 0 })
+    }
+
+    /// A folder is read as images, an image file as a single frame, anything else as video.
+    pub fn open(path: &str) -> Result<Self> {
+        let path = Path::new(path);
+        if path.is_dir() {
+            return Self::images(path);
+        }
+        if !path.exists() {
+            return Err(crate::AirLinkError::CliError(format!("{} does not exist", path.display())));
+        }
+        if is_image(path) {
+            return Ok(Self { input: Input::Images { paths: vec![path.to_path_buf()], next: 0 }, count: 0 });
+        }
+        Self::video(path)
+    }
+
+    /// Frames left, when known up front.
+    pub fn remaining(&self) -> Option<usize> {
+        match &self.input {
+            Input::Images { paths, next } => Some(paths.len() - next),
+            _ => None,
+        }
+    }
+
+    /// The next frame, or `None` at the end of a file source. Cameras wait
+    /// out errors and reconnects.
+    pub fn next_frame(&mut self) -> Result<Option<SourceFrame>> {
+        let next = match &mut self.input {
+            Input::Camera { camera, start } => loop {
+                match camera.capture_frame() {
+                    Ok(frame) => {
+                        let start = *start.get_or_insert(frame.timestamp);
+                        let time = frame.timestamp.duration_since(start).as_secs_f64();
+                        break Some(SourceFrame { frame, time: Some(time), name: None });
+                    }
+                    // The camera manager reports its own reconnect progress
+                    Err(_) => std::thread::sleep(Duration::from_millis(100)),
+                }
+            },
+            Input::Video { child, stdout, stderr, fps } => match read_ppm(stdout)? {
+                Some((width, height, data)) => Some(SourceFrame {
+                    frame: Frame::new(data, width, height, PixelFormat::Rgb),
+                    time: fps.map(|fps| self.count as f64 / fps),
+                    name: None,
+                }),
+                None => {
+                    // A bad path or codec also just closes the pipe; only the exit status tells
+                    let status = child.wait()?;
+                    let message = stderr.take().and_then(|h| h.join().ok()).unwrap_or_default();
+                    if !status.success() {
+                        return Err(crate::AirLinkError::CoreError(format!(
+                            "ffmpeg failed ({}): {}", status, message.trim()
+                        )));
+                    }
+                    if !message.trim().is_empty() {
+                        warn!("ffmpeg: {}", message.trim());
+                    }
+                    None
+                }
+            },
+            Input::Images { paths, next } => loop {
+                let Some(path) = paths.get(*next) else { break None };
+                *next += 1;
+                match image::open(path) {
+                    Ok(img) => {
+                        let name = path.file_name().map(|n| n.to_string_lossy().into_owned());
+                        break Some(SourceFrame { frame: Frame::from_image(&img), time: None, name });
+                    }
+                    Err(e) => warn!("Skipping {}: {}", path.display(), e),
+                }
+            },
+        };
+        Ok(next.map(|mut next| {
+            next.frame.sequence = self.count;
+            self.count += 1;
+            next
+        }))
+    }
+}
+
+impl Drop for FrameSource {
+    fn drop(&mut self) {
+        if let Input::Video { child, .. } = &mut self.input {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Average frame rate of the first video stream, from `ffprobe`.
+fn probe_fps(path: &Path) -> Option<f64> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "v:0", "-show_entries", "stream=avg_frame_rate", "-of", "csv=p=0"])
+        .arg(path)
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let (num, den) = text.trim().split_once('/')?;
+    let fps = num.parse::<f64>().ok()? / den.parse::<f64>().ok()?;
+    (fps.is_finite() && fps > 0.0).then_some(fps)
+}
+
+/// Next whitespace-separated header token; `None` at a clean end of stream.
+fn read_token(reader: &mut impl BufRead) -> Result<Option<String>> {
+    let mut token = Vec::new();
+    let mut byte = [0u8];
+    loop {
+        match reader.read_exact(&mut byte) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof && token.is_empty() => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        if byte[0].is_ascii_whitespace() {
+            if !token.is_empty() {
+                return Ok(Some(String::from_utf8_lossy(&token).into_owned()));
+            }
+        } else {
+            token.push(byte[0]);
+        }
+    }
+}
+
+/// One binary PPM (`P6`, 8-bit) image: width, height and packed RGB.
+pub fn read_ppm(reader: &mut impl BufRead) -> Result<Option<(u32, u32, Vec<u8>)>> {
+    let Some(magic) = read_token(reader)? else { return Ok(None) };
+    let bad = |what: &str| crate::AirLinkError::CoreError(format!("Bad PPM frame from ffmpeg: {}", what));
+    if magic != "P6" {
+        return Err(bad(&format!("magic {}", magic)));
+    }
+    let mut number = || -> Result<u32> {
+        read_token(reader)?.and_then(|t| t.parse().ok()).ok_or_else(|| bad("header"))
+    };
+    let (width, height, max) = (number()?, number()?, number()?);
+    if max != 255 {
+        return Err(bad(&format!("max value {}", max)));
+    }
+    let mut data = vec![0u8; width as usize * height as usize * 3];
+    reader.read_exact(&mut data)?;
+    Ok(Some((width, height, data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ppm_stream() {
+        let mut stream = Vec::new();
+        for shade in [10u8, 20] {
+            stream.extend_from_slice(b"P6\n2 1\n255\n");
+            stream.extend_from_slice(&[shade; 6]);
+        }
+        let mut reader = BufReader::new(stream.as_slice());
+        assert_eq!(read_ppm(&mut reader).unwrap(), Some((2, 1, vec![10; 6])));
+        assert_eq!(read_ppm(&mut reader).unwrap(), Some((2, 1, vec![20; 6])));
+        assert_eq!(read_ppm(&mut reader).unwrap(), None);
+
+        let mut truncated = BufReader::new(&b"P6\n2 1\n255\nabc"[..]);
+        assert!(read_ppm(&mut truncated).is_err());
+    }
+
+    #[test]
+    fn test_decoder_failure_is_an_error() {
+        let sh = |script: &str| {
+            let mut command = Command::new("sh");
+            command.args(["-c", script]);
+            command
+        };
+        let mut ok = FrameSource::decoder(sh("printf 'P6 1 1 255 abc'"), Some(10.0)).unwrap();
+        assert_eq!(ok.next_frame().unwrap().unwrap().frame.data, b"abc");
+        assert!(ok.next_frame().unwrap().is_none());
+
+        let mut failed = FrameSource::decoder(sh("echo 'No such file or directory' >&2; exit 1"), None).unwrap();
+        let error = failed.next_frame().err().expect("ffmpeg's exit status is checked").to_string();
+        assert!(error.contains("No such file or directory"), "{}", error);
+    }
+
+    #[test]
+    fn test_image_folder_in_name_order() {
+        let dir = crate::core::testing::TempDir::new("source");
+        for (name, shade) in [("b.png", 200u8), ("a.png", 100)] {
+            image::RgbImage::from_pixel(4, 2, image::Rgb([shade; 3])).save(dir.join(name)).unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "not an image").unwrap();
+        fs::write(dir.join("c.png"), "corrupt").unwrap();
+
+        let mut source = FrameSource::open(dir.to_str().unwrap()).unwrap();
+        assert_eq!(source.remaining(), Some(3));
+        let first = source.next_frame().unwrap().unwrap();
+        assert_eq!((first.name.as_deref(), first.frame.sequence, first.frame.data[0]), (Some("a.png"), 0, 100));
+        let second = source.next_frame().unwrap().unwrap();
+        assert_eq!((second.name.as_deref(), second.frame.sequence, second.time), (Some("b.png"), 1, None));
+        // The corrupt file is skipped
+        assert!(source.next_frame().unwrap().is_none());
+    }
+}
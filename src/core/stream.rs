@@ -0,0 +1,477 @@
+use crate::core::dwell::DwellAction;
+use crate::core::gesture::{HandPose, Swipe};
+use crate::core::udp::UdpSender;
+use crate::core::vision::Handedness;
+use crate::Result;
+use serde_json::{json, Value};
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+use tungstenite::{Message, WebSocket};
+
+/// Bumped on any incompatible change to either encoding.
+pub const SCHEMA_VERSION: u8 = 1;
+/// First four bytes of every binary message.
+pub const MAGIC: [u8; 4] = *b"ALNK";
+/// Binary header size; the pointer and cursor follow at fixed offsets.
+pub const HEADER_LEN: usize = 34;
+
+/// Messages a slow WebSocket client may fall behind before frames are dropped for it.
+const CLIENT_QUEUE: usize = 8;
+/// How often an idle WebSocket connection checks for pings and close frames.
+const CLIENT_POLL: Duration = Duration::from_millis(50);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WireFormat {
+    Json,
+    Binary,
+}
+
+/// Discrete things that happened during a frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StreamEvent {
+    ClickDown,
+    ClickUp,
+    Engaged,
+    Disengaged,
+    Dwell(DwellAction),
+    Swipe(Swipe),
+    Paused,
+    Resumed,
+}
+
+impl StreamEvent {
+    pub fn name(self) -> &'static str {
+        match self {
+            StreamEvent::ClickDown => "click_down",
+            StreamEvent::ClickUp => "click_up",
+            StreamEvent::Engaged => "engaged",
+            StreamEvent::Disengaged => "disengaged",
+            StreamEvent::Dwell(DwellAction::Left) => "dwell_left",
+            StreamEvent::Dwell(DwellAction::Right) => "dwell_right",
+            StreamEvent::Dwell(DwellAction::Double) => "dwell_double",
+            StreamEvent::Dwell(DwellAction::Drag) => "dwell_drag",
+            StreamEvent::Swipe(Swipe::Left) => "swipe_left",
+            StreamEvent::Swipe(Swipe::Right) => "swipe_right",
+            StreamEvent::Paused => "paused",
+            StreamEvent::Resumed => "resumed",
+        }
+    }
+
+    /// Binary event code, 1-based in the order of the schema table.
+    pub fn code(self) -> u8 {
+        match self {
+            StreamEvent::ClickDown => 1,
+            StreamEvent::ClickUp => 2,
+            StreamEvent::Engaged => 3,
+            StreamEvent::Disengaged => 4,
+            StreamEvent::Dwell(DwellAction::Left) => 5,
+            StreamEvent::Dwell(DwellAction::Right) => 6,
+            StreamEvent::Dwell(DwellAction::Double) => 7,
+            StreamEvent::Dwell(DwellAction::Drag) => 8,
+            StreamEvent::Swipe(Swipe::Left) => 9,
+            StreamEvent::Swipe(Swipe::Right) => 10,
+            StreamEvent::Paused => 11,
+            StreamEvent::Resumed => 12,
+        }
+    }
+}
+
+/// What the pipeline collects for the stream while a frame is processed.
+#[derive(Clone, Debug, Default)]
+pub struct Outbox {
+    pub landmarks: Vec<(f32, f32, f32)>,
+    pub handedness: Option<Handedness>,
+    /// Screen position the cursor was moved to.
+    pub cursor: Option<(i32, i32)>,
+    pub events: Vec<StreamEvent>,
+}
+
+impl Outbox {
+    pub fn clear(&mut self) {
+        self.landmarks.clear();
+        self.handedness = None;
+        self.cursor = None;
+        self.events.clear();
+    }
+}
+
+/// One processed frame, as published.
+#[derive(Copy, Clone, Debug)]
+pub struct Sample<'a> {
+    /// Camera sequence number of the frame.
+    pub frame: u64,
+    /// Frame time, taken when the camera buffer was dequeued.
+    pub timestamp: Instant,
+    pub pose: HandPose,
+    /// Pointer in frame-normalized coordinates.
+    pub pointer: Option<(f32, f32)>,
+    pub engaged: bool,
+    pub idle: bool,
+    pub paused: bool,
+    pub outbox: &'a Outbox,
+}
+
+/// Frame (dequeue) time as microseconds since the Unix epoch.
+fn unix_micros(t: Instant) -> u64 {
+    let age = Instant::now().saturating_duration_since(t);
+    SystemTime::now()
+        .checked_sub(age)
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_micros() as u64)
+}
+
+/// Five decimals is well below a pixel and keeps the JSON short.
+fn round(v: f32) -> f64 {
+    (v as f64 * 1e5).round() / 1e5
+}
+
+pub fn pose_name(pose: HandPose) -> &'static str {
+    match pose {
+        HandPose::Open => "open",
+        HandPose::Fist => "fist",
+        HandPose::PalmAway => "palm_away",
+        HandPose::Unknown => "unknown",
+    }
+}
+
+pub fn encode_json(seq: u64, sample: &Sample) -> Value {
+    let outbox = sample.outbox;
+    let hand = (!outbox.landmarks.is_empty()).then(|| json!({
+        "handedness": outbox.handedness.map(|h| match h {
+            Handedness::Left => "left",
+            Handedness::Right => "right",
+        }),
+        "pose": pose_name(sample.pose),
+        "landmarks": outbox.landmarks.iter().map(|&(x, y, z)| [round(x), round(y), round(z)]).collect::<Vec<_>>(),
+    }));
+    json!({
+        "v": SCHEMA_VERSION,
+        "seq": seq,
+        "frame": sample.frame,
+        "t": unix_micros(sample.timestamp),
+        "engaged": sample.engaged,
+        "idle": sample.idle,
+        "paused": sample.paused,
+        "pointer": sample.pointer.map(|(x, y)| [round(x), round(y)]),
+        "cursor": outbox.cursor.map(|(x, y)| [x, y]),
+        "hand": hand,
+        "events": outbox.events.iter().map(|e| e.name()).collect::<Vec<_>>(),
+    })
+}
+
+/// Little-endian layout documented in the README ("Landmark Streaming").
+pub fn encode_binary(seq: u64, sample: &Sample) -> Vec<u8> {
+    let outbox = sample.outbox;
+    let flags = [
+        !outbox.landmarks.is_empty(),
+        sample.pointer.is_some(),
+        outbox.cursor.is_some(),
+        sample.engaged,
+        sample.idle,
+        sample.paused,
+    ]
+    .iter()
+    .enumerate()
+    .fold(0u8, |acc, (bit, &set)| acc | ((set as u8) << bit));
+    let handedness = match outbox.handedness {
+        None => 0u8,
+        Some(Handedness::Left) => 1,
+        Some(Handedness::Right) => 2,
+    };
+    let pose = match sample.pose {
+        HandPose::Unknown => 0u8,
+        HandPose::Open => 1,
+        HandPose::Fist => 2,
+        HandPose::PalmAway => 3,
+    };
+    let landmarks = outbox.landmarks.len().min(u8::MAX as usize);
+    let events = outbox.events.len().min(u8::MAX as usize);
+
+    let mut out = Vec::with_capacity(HEADER_LEN + 16 + landmarks * 12 + events);
+    out.extend_from_slice(&MAGIC);
+    out.extend_from_slice(&[SCHEMA_VERSION, flags, handedness, pose]);
+    out.extend_from_slice(&seq.to_le_bytes());
+    out.extend_from_slice(&sample.frame.to_le_bytes());
+    out.extend_from_slice(&unix_micros(sample.timestamp).to_le_bytes());
+    out.extend_from_slice(&[landmarks as u8, events as u8]);
+
+    let (px, py) = sample.pointer.unwrap_or_default();
+    let (cx, cy) = outbox.cursor.unwrap_or_default();
+    for v in [px.to_le_bytes(), py.to_le_bytes(), cx.to_le_bytes(), cy.to_le_bytes()] {
+        out.extend_from_slice(&v);
+    }
+    for &(x, y, z) in &outbox.landmarks[..landmarks] {
+        for v in [x, y, z] {
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+    out.extend(outbox.events[..events].iter().map(|e| e.code()));
+    out
+}
+
+/// Queues of the connected WebSocket clients.
+type Clients = Arc<Mutex<Vec<SyncSender<Message>>>>;
+
+/// Feeds one client its queue until either side goes away. The socket is
+/// non-blocking so it can also be read between messages: that is where
+/// tungstenite answers pings and close frames.
+fn serve_client(socket: &mut WebSocket<TcpStream>, queue: Receiver<Message>) {
+    // A full send buffer keeps the frame queued inside tungstenite; only real errors end the connection
+    let alive = |result: tungstenite::Result<()>| match result {
+        Ok(()) => true,
+        Err(tungstenite::Error::Io(e)) => e.kind() == ErrorKind::WouldBlock,
+        Err(_) => false,
+    };
+    if socket.get_mut().set_nonblocking(true).is_err() {
+        return;
+    }
+    loop {
+        match queue.recv_timeout(CLIENT_POLL) {
+            Ok(message) => {
+                if !alive(socket.send(message)) {
+                    return;
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+        // Clients have nothing to say beyond control frames; anything else is dropped
+        loop {
+            match socket.read() {
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => return,
+            }
+        }
+        if !alive(socket.flush()) {
+            return;
+        }
+    }
+}
+
+/// Encoded message, shared by every transport.
+enum Payload {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+/// Publishes every processed frame to local tools over WebSocket and/or UDP.
+///
+/// Publishing never blocks the pipeline: WebSocket clients each get a small
+/// queue and miss frames when they fall behind, UDP datagrams are fire and
+/// forget. Either way the gap shows up in `seq`.
+pub struct StreamServer {
+    format: WireFormat,
+    seq: u64,
+    websocket: Option<(SocketAddr, Clients)>,
+    /// Each target with whether its last send failed, so an outage logs once.
+    udp: Vec<(UdpSender, bool)>,
+}
+
+impl StreamServer {
+    pub fn new(format: WireFormat) -> Self {
+        Self { format, seq: 0, websocket: None, udp: Vec::new() }
+    }
+
+    /// Starts a WebSocket server on `addr` (e.g. `127.0.0.1:9001`).
+    pub fn listen_websocket(mut self, addr: &str) -> Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let local = listener.local_addr()?;
+        let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+
+        let registry = Arc::clone(&clients);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let registry = Arc::clone(&registry);
+                std::thread::spawn(move || {
+                    let peer = stream.peer_addr().ok();
+                    let _ = stream.set_nodelay(true);
+                    let mut socket = match tungstenite::accept(stream) {
+                        Ok(socket) => socket,
+                        Err(e) => {
+                            warn!("Stream handshake with {:?} failed: {}", peer, e);
+                            return;
+                        }
+                    };
+                    info!("📡 Stream client {:?} connected", peer);
+                    let (tx, rx) = mpsc::sync_channel(CLIENT_QUEUE);
+                    registry.lock().unwrap().push(tx);
+                    // Ends when the client goes away; the publisher then drops its queue
+                    serve_client(&mut socket, rx);
+                    info!("📡 Stream client {:?} disconnected", peer);
+                });
+            }
+        });
+
+        info!("📡 Streaming landmarks on ws://{}", local);
+        self.websocket = Some((local, clients));
+        Ok(self)
+    }
+
+    /// Adds a UDP destination (e.g. `127.0.0.1:9002`); one datagram per frame.
+    pub fn send_udp(mut self, target: &str) -> Result<Self> {
+        let addr = target.to_socket_addrs()?.next().ok_or_else(|| {
+            crate::AirLinkError::CoreError(format!("UDP target `{}` did not resolve", target))
+        })?;
+        self.udp.push((UdpSender::new(addr)?, false));
+        info!("📡 Streaming landmarks to udp://{}", addr);
+        Ok(self)
+    }
+
+    /// Address the WebSocket server actually bound, e.g. for port 0.
+    pub fn websocket_addr(&self) -> Option<SocketAddr> {
+        self.websocket.as_ref().map(|(addr, _)| *addr)
+    }
+
+    pub fn publish(&mut self, sample: &Sample) {
+        let seq = self.seq;
+        self.seq += 1;
+        let payload = match self.format {
+            WireFormat::Json => Payload::Text(encode_json(seq, sample).to_string()),
+            WireFormat::Binary => Payload::Bytes(encode_binary(seq, sample)),
+        };
+
+        for (udp, failing) in &mut self.udp {
+            let bytes = match &payload {
+                Payload::Text(text) => text.as_bytes(),
+                Payload::Bytes(bytes) => bytes,
+            };
+            let result = udp.send(bytes);
+            match &result {
+                Err(e) if !*failing => warn!("UDP stream to {} failed: {}", udp.target(), e),
+                Ok(()) if *failing => info!("📡 UDP stream to {} is back", udp.target()),
+                _ => {}
+            }
+            *failing = result.is_err();
+        }
+
+        if let Some((_, clients)) = &self.websocket {
+            let mut clients = clients.lock().unwrap();
+            if clients.is_empty() {
+                return;
+            }
+            let message = match payload {
+                Payload::Text(text) => Message::Text(text),
+                Payload::Bytes(bytes) => Message::Binary(bytes),
+            };
+            // A full queue skips this frame for that client; a closed one is dropped
+            clients.retain(|tx| !matches!(tx.try_send(message.clone()), Err(TrySendError::Disconnected(_))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::UdpSocket;
+    use std::time::Duration;
+
+    fn outbox() -> Outbox {
+        Outbox {
+            landmarks: (0..21).map(|i| (i as f32 / 20.0, 0.5, -0.01)).collect(),
+            handedness: Some(Handedness::Right),
+            cursor: Some((960, 540)),
+            events: vec![StreamEvent::ClickDown, StreamEvent::Dwell(DwellAction::Right)],
+        }
+    }
+
+    fn sample(outbox: &Outbox) -> Sample<'_> {
+        Sample {
+            frame: 42,
+            timestamp: Instant::now(),
+            pose: HandPose::Open,
+            pointer: Some((0.25, 0.75)),
+            engaged: true,
+            idle: false,
+            paused: false,
+            outbox,
+        }
+    }
+
+    #[test]
+    fn test_binary_layout() {
+        let outbox = outbox();
+        let bytes = encode_binary(7, &sample(&outbox));
+        assert_eq!(bytes.len(), HEADER_LEN + 16 + 21 * 12 + 2);
+        assert_eq!(&bytes[..4], b"ALNK");
+        assert_eq!(&bytes[4..8], &[SCHEMA_VERSION, 0b1111, 2, 1]);
+        assert_eq!(u64::from_le_bytes(bytes[8..16].try_into().unwrap()), 7);
+        assert_eq!(u64::from_le_bytes(bytes[16..24].try_into().unwrap()), 42);
+        assert_eq!(&bytes[32..34], &[21, 2]);
+        assert_eq!(f32::from_le_bytes(bytes[38..42].try_into().unwrap()), 0.75);
+        assert_eq!(i32::from_le_bytes(bytes[42..46].try_into().unwrap()), 960);
+        assert_eq!(&bytes[bytes.len() - 2..], &[1, 6]);
+    }
+
+    #[test]
+    fn test_loopback_websocket_and_udp() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let mut server = StreamServer::new(WireFormat::Json)
+            .listen_websocket("127.0.0.1:0").unwrap()
+            .send_udp(&receiver.local_addr().unwrap().to_string()).unwrap();
+
+        let url = format!("ws://{}", server.websocket_addr().unwrap());
+        let client = std::thread::spawn(move || {
+            let (mut socket, _) = tungstenite::connect(url).unwrap();
+            let mut seqs = Vec::new();
+            while seqs.len() < 3 {
+                if let Message::Text(text) = socket.read().unwrap() {
+                    let json: Value = serde_json::from_str(&text).unwrap();
+                    seqs.push(json["seq"].as_u64().unwrap());
+                    if seqs.len() == 1 {
+                        assert_eq!(json["hand"]["landmarks"].as_array().unwrap().len(), 21);
+                        assert_eq!(json["events"], json!(["click_down", "dwell_right"]));
+                        assert_eq!(json["cursor"], json!([960, 540]));
+                    }
+                }
+            }
+            seqs
+        });
+
+        // Publish until the client has registered and read three messages
+        let outbox = outbox();
+        let mut buf = [0u8; 65536];
+        let mut first_datagram = None;
+        while !client.is_finished() {
+            server.publish(&sample(&outbox));
+            if first_datagram.is_none() {
+                let len = receiver.recv(&mut buf).unwrap();
+                first_datagram = Some(serde_json::from_slice::<Value>(&buf[..len]).unwrap());
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        let seqs = client.join().unwrap();
+        assert!(seqs.windows(2).all(|w| w[1] == w[0] + 1), "{:?}", seqs);
+        let datagram = first_datagram.unwrap();
+        assert_eq!((datagram["seq"].as_u64(), datagram["frame"].as_u64()), (Some(0), Some(42)));
+        assert_eq!(datagram["hand"]["handedness"], "right");
+        assert!(datagram["t"].as_u64().unwrap() > 1_600_000_000_000_000);
+    }
+
+    #[test]
+    fn test_websocket_answers_ping_and_close() {
+        let server = StreamServer::new(WireFormat::Json).listen_websocket("127.0.0.1:0").unwrap();
+        let addr = server.websocket_addr().unwrap();
+        let stream = TcpStream::connect(addr).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let (mut socket, _) = tungstenite::client(format!("ws://{}", addr), stream).unwrap();
+
+        socket.send(Message::Ping(b"hi".to_vec())).unwrap();
+        assert_eq!(socket.read().unwrap(), Message::Pong(b"hi".to_vec()));
+
+        socket.close(None).unwrap();
+        loop {
+            match socket.read() {
+                Ok(Message::Close(_)) => {}
+                Err(tungstenite::Error::ConnectionClosed) => break,
+                other => panic!("close was not answered: {:?}", other),
+            }
+        }
+    }
+}
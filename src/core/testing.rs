@@ -0,0 +1,50 @@
+//! Fixtures shared by the unit tests of the core modules.
+
+use crate::core::landmarks::{INDEX_TIP, MIDDLE_MCP, THUMB_TIP};
+use crate::core::vision::{HandResult, Handedness};
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// A hand of size 0.2, fingers up, with the thumb `gap` hand sizes to the
+/// right of the index tip.
+pub fn test_hand(x: f32, gap: f32) -> Vec<(f32, f32, f32)> {
+    let mut lm = vec![(x, 0.5, 0.0); 21];
+    lm[MIDDLE_MCP] = (x, 0.3, 0.0);
+    lm[INDEX_TIP] = (x, 0.2, 0.0);
+    lm[THUMB_TIP] = (x + gap * 0.2, 0.2, 0.0);
+    lm
+}
+
+/// A detection of `landmarks`, with the tips taken from them.
+pub fn hand_result(landmarks: Vec<(f32, f32, f32)>, handedness: Option<Handedness>) -> HandResult {
+    let tip = |i: usize| (landmarks[i].0, landmarks[i].1);
+    HandResult { index_tip: tip(INDEX_TIP), thumb_tip: tip(THUMB_TIP), landmarks, handedness }
+}
+
+/// An empty directory under the system temp dir, removed again on drop.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// `name` keeps tests running in parallel apart.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("air-link-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
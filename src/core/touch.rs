@@ -0,0 +1,234 @@
+use crate::core::landmarks::{Pinch, INDEX_TIP, MIDDLE_TIP, THUMB_TIP, WRIST};
+
+/// Contacts the virtual touchscreen can hold at once (two hands, five fingers).
+pub const MAX_SLOTS: usize = 10;
+/// `ABS_MT_TRACKING_ID` wraps below this.
+pub const MAX_TRACKING_ID: i32 = 65535;
+
+/// Fingertips tested in depth mode: two fingers of one hand zoom and rotate.
+const DEPTH_FINGERS: [usize; 2] = [INDEX_TIP, MIDDLE_TIP];
+/// A depth contact lifts once the fingertip is back to this fraction of the threshold.
+const DEPTH_RELEASE: f32 = 0.7;
+/// Weight of the new position in each contact's smoothing.
+const SMOOTHING: f32 = 0.5;
+/// A hand whose wrist moved less than this (frame-normalized) since the last
+/// frame is the same hand, wherever it is in the list.
+const SAME_HAND: f32 = 0.2;
+
+/// When a fingertip counts as touching the virtual surface.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TouchTest {
+    /// Each pinching hand (thumb on index) is one contact, at the pinch point;
+    /// two hands pinch-zoom and rotate.
+    Pinch,
+    /// Index and middle tips touch when this much closer to the camera than
+    /// the wrist (landmark z, in frame widths).
+    Depth(f32),
+}
+
+/// One finger on the surface, in MT protocol B terms.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Contact<P = (f32, f32)> {
+    pub slot: usize,
+    /// Constant while the finger stays down, new for every touch.
+    pub tracking_id: i32,
+    pub position: P,
+}
+
+impl<P> Contact<P> {
+    pub fn map<Q>(self, f: impl FnOnce(P) -> Q) -> Contact<Q> {
+        Contact { slot: self.slot, tracking_id: self.tracking_id, position: f(self.position) }
+    }
+}
+
+/// A hand seen last frame.
+#[derive(Copy, Clone, Debug)]
+struct Hand {
+    id: usize,
+    wrist: (f32, f32),
+    pinch: Pinch,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Active {
+    /// (hand id, fingertip landmark) the contact belongs to.
+    key: (usize, usize),
+    tracking_id: i32,
+    position: (f32, f32),
+}
+
+/// Turns hand landmarks into touch contacts with stable slots and tracking IDs.
+pub struct TouchTracker {
+    test: TouchTest,
+    slots: [Option<Active>; MAX_SLOTS],
+    /// Hands of the last frame, so a hand keeps its contacts (and pinch
+    /// state) when another one comes or goes.
+    hands: Vec<Hand>,
+    next_hand: usize,
+    next_id: i32,
+}
+
+impl TouchTracker {
+    pub fn new(test: TouchTest) -> Self {
+        Self { test, slots: [None; MAX_SLOTS], hands: Vec::new(), next_hand: 0, next_id: 0 }
+    }
+
+    pub fn test(&self) -> TouchTest {
+        self.test
+    }
+
+    fn is_down(&self, key: (usize, usize)) -> bool {
+        self.slots.iter().flatten().any(|a| a.key == key)
+    }
+
+    /// Matches this frame's hands to last frame's by wrist position; the
+    /// nearest within [`SAME_HAND`] keeps its id and pinch state, the rest
+    /// start fresh. `self.hands` ends up in the order of `hands`.
+    fn follow(&mut self, hands: &[&[(f32, f32, f32)]]) {
+        let mut previous = std::mem::take(&mut self.hands);
+        for lm in hands {
+            let wrist = (lm[WRIST].0, lm[WRIST].1);
+            let dist = |h: &Hand| ((h.wrist.0 - wrist.0).powi(2) + (h.wrist.1 - wrist.1).powi(2)).sqrt();
+            let nearest = (0..previous.len())
+                .filter(|&i| dist(&previous[i]) < SAME_HAND)
+                .min_by(|&a, &b| dist(&previous[a]).total_cmp(&dist(&previous[b])));
+            let hand = match nearest {
+                Some(i) => Hand { wrist, ..previous.swap_remove(i) },
+                None => {
+                    self.next_hand += 1;
+                    Hand { id: self.next_hand, wrist, pinch: Pinch::new() }
+                }
+            };
+            self.hands.push(hand);
+        }
+    }
+
+    /// Frame-normalized points of every finger passing the touching test.
+    fn touching(&mut self, hands: &[&[(f32, f32, f32)]]) -> Vec<((usize, usize), (f32, f32))> {
+        let hands: Vec<_> = hands.iter().copied().filter(|lm| lm.len() > MIDDLE_TIP).collect();
+        self.follow(&hands);
+        let mut out = Vec::new();
+        for (i, lm) in hands.iter().enumerate() {
+            let id = self.hands[i].id;
+            match self.test {
+                TouchTest::Pinch => {
+                    let gap = Pinch::gap(lm).unwrap_or(f32::INFINITY);
+                    if self.hands[i].pinch.update(gap) {
+                        let (thumb, index) = (lm[THUMB_TIP], lm[INDEX_TIP]);
+                        out.push(((id, INDEX_TIP), ((thumb.0 + index.0) / 2.0, (thumb.1 + index.1) / 2.0)));
+                    }
+                }
+                TouchTest::Depth(threshold) => {
+                    for tip in DEPTH_FINGERS {
+                        let key = (id, tip);
+                        let limit = if self.is_down(key) { threshold * DEPTH_RELEASE } else { threshold };
+                        if lm[WRIST].2 - lm[tip].2 > limit {
+                            out.push((key, (lm[tip].0, lm[tip].1)));
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Updates the contacts from this frame's hands and returns those down.
+    pub fn update(&mut self, hands: &[&[(f32, f32, f32)]]) -> Vec<Contact> {
+        let touching = self.touching(hands);
+
+        // Lift fingers that stopped touching; keep the rest in their slots
+        for slot in &mut self.slots {
+            if slot.is_some_and(|a| !touching.iter().any(|(key, _)| *key == a.key)) {
+                *slot = None;
+            }
+        }
+        for (key, position) in touching {
+            if let Some(active) = self.slots.iter_mut().flatten().find(|a| a.key == key) {
+                let (x, y) = active.position;
+                active.position = (x + (position.0 - x) * SMOOTHING, y + (position.1 - y) * SMOOTHING);
+            } else if let Some(free) = self.slots.iter_mut().find(|s| s.is_none()) {
+                *free = Some(Active { key, tracking_id: self.next_id, position });
+                self.next_id = (self.next_id + 1) % (MAX_TRACKING_ID + 1);
+            }
+        }
+
+        self.slots.iter().enumerate()
+            .filter_map(|(slot, a)| a.map(|a| Contact { slot, tracking_id: a.tracking_id, position: a.position }))
+            .collect()
+    }
+
+    /// Lifts every finger.
+    pub fn clear(&mut self) {
+        self.slots = [None; MAX_SLOTS];
+        self.hands.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::testing::test_hand as hand;
+
+    #[test]
+    fn test_pinch_contacts_keep_their_slots() {
+        let mut touch = TouchTracker::new(TouchTest::Pinch);
+        let (left, right) = (hand(0.3, 0.1), hand(0.7, 1.0));
+        let contacts = touch.update(&[&left, &right]);
+        assert_eq!(contacts.len(), 1);
+        assert_eq!((contacts[0].slot, contacts[0].tracking_id), (0, 0));
+        assert!((contacts[0].position.0 - 0.31).abs() < 1e-5);
+
+        // Second hand pinches: next slot, next ID. 0.3 is past the touch-down
+        // gap but within the release gap, so the first stays down.
+        let (left, right) = (hand(0.3, 0.3), hand(0.7, 0.1));
+        let contacts = touch.update(&[&left, &right]);
+        assert_eq!(contacts.iter().map(|c| (c.slot, c.tracking_id)).collect::<Vec<_>>(), [(0, 0), (1, 1)]);
+
+        // The first lifts; touching again takes the free slot with a new ID
+        let left = hand(0.3, 1.0);
+        assert_eq!(touch.update(&[&left, &right]).len(), 1);
+        let left = hand(0.3, 0.0);
+        let contacts = touch.update(&[&left, &right]);
+        assert_eq!(contacts.iter().map(|c| (c.slot, c.tracking_id)).collect::<Vec<_>>(), [(0, 2), (1, 1)]);
+
+        touch.clear();
+        assert_eq!(touch.update(&[]), []);
+    }
+
+    #[test]
+    fn test_two_hands_keep_their_contacts() {
+        let mut touch = TouchTracker::new(TouchTest::Pinch);
+        let (left, right) = (hand(0.3, 0.1), hand(0.7, 0.1));
+        let ids = |contacts: Vec<Contact>| contacts.iter().map(|c| (c.slot, c.tracking_id)).collect::<Vec<_>>();
+        assert_eq!(ids(touch.update(&[&left, &right])), [(0, 0), (1, 1)]);
+
+        // The detector lists the hands the other way round: same contacts
+        let contacts = touch.update(&[&right, &left]);
+        assert_eq!(ids(contacts.clone()), [(0, 0), (1, 1)]);
+        assert!(contacts[0].position.0 < 0.5);
+
+        // The left hand leaves; the right one keeps its finger down
+        assert_eq!(ids(touch.update(&[&right])), [(1, 1)]);
+        // and the pinch state goes with the hand, not the list index
+        let right = hand(0.7, 0.3);
+        assert_eq!(ids(touch.update(&[&right])), [(1, 1)]);
+    }
+
+    #[test]
+    fn test_depth_contacts() {
+        let mut touch = TouchTracker::new(TouchTest::Depth(0.08));
+        let mut lm = hand(0.5, 1.0);
+        lm[INDEX_TIP].2 = -0.1;
+        lm[MIDDLE_TIP] = (0.6, 0.2, -0.05);
+        let contacts = touch.update(&[&lm]);
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].position, (0.5, 0.2));
+
+        // Both fingers pushed in; the index eases back but stays past the release depth
+        lm[INDEX_TIP].2 = -0.06;
+        lm[MIDDLE_TIP].2 = -0.09;
+        let contacts = touch.update(&[&lm]);
+        assert_eq!(contacts.iter().map(|c| c.tracking_id).collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(contacts[1].map(|(x, y)| ((x * 1000.0) as i32, (y * 1000.0) as i32)).position, (600, 200));
+    }
+}
@@ -0,0 +1,34 @@
+use crate::core::gesture::HandPose;
+use crate::core::vision::HandResult;
+use crate::core::frame::Frame;
+use crate::Result;
+use std::time::Instant;
+
+/// What any tracker (hand, face...) hands to the pointer pipeline.
+pub struct TrackerOutput {
+    /// Point driving the cursor, frame-normalized.
+    pub pointer: (f32, f32),
+    /// The tracker's click gesture (pinch, blink...) is currently held.
+    pub click: bool,
+    /// Hand pose, `Unknown` for trackers without hands.
+    pub pose: HandPose,
+    /// Raw hand landmarks when the tracker is hand-based.
+    pub hand: Option<HandResult>,
+    /// Further hands, for trackers that follow more than one (two-hand touch).
+    pub other_hands: Vec<HandResult>,
+    /// Dequeue time and sequence number of the frame this came from.
+    pub timestamp: Instant,
+    pub sequence: u64,
+}
+
+/// A landmark model that can drive the pointer. Filters, active zone and
+/// the input sink downstream are shared by every implementation.
+pub trait Tracker: Send {
+    /// `frame` is in the camera's native format; see `TensorInput` for turning it into model input.
+    fn track(&mut self, frame: &Frame) -> Result<Option<TrackerOutput>>;
+
+    /// (min, max) of the camera region mapped onto the screen, on both axes.
+    fn active_zone(&self) -> (f32, f32) {
+        (0.2, 0.8)
+    }
+}
@@ -0,0 +1,182 @@
+/// 2D affine map `p' = M·p + t`, stored row-major as `[[a, b, tx], [c, d, ty]]`.
+///
+/// Used between frame-normalized coordinates (0..1 across the camera image)
+/// and model-normalized ones (0..1 across the square network input).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Affine {
+    pub m: [[f32; 3]; 2],
+}
+
+impl Affine {
+    pub const IDENTITY: Affine = Affine { m: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] };
+
+    pub fn translate(tx: f32, ty: f32) -> Self {
+        Self { m: [[1.0, 0.0, tx], [0.0, 1.0, ty]] }
+    }
+
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Self { m: [[sx, 0.0, 0.0], [0.0, sy, 0.0]] }
+    }
+
+    /// Rotation about the origin; with y pointing down this turns clockwise on screen.
+    pub fn rotate(radians: f32) -> Self {
+        let (sin, cos) = radians.sin_cos();
+        Self { m: [[cos, -sin, 0.0], [sin, cos, 0.0]] }
+    }
+
+    /// Rotation about `center`.
+    pub fn rotate_about(center: (f32, f32), radians: f32) -> Self {
+        Self::translate(-center.0, -center.1)
+            .then(&Self::rotate(radians))
+            .then(&Self::translate(center.0, center.1))
+    }
+
+    /// Maps the `(x0, y0)..(x1, y1)` rectangle of the frame onto 0..1.
+    pub fn crop(x0: f32, y0: f32, x1: f32, y1: f32) -> Self {
+        Self::translate(-x0, -y0).then(&Self::scale(1.0 / (x1 - x0), 1.0 / (y1 - y0)))
+    }
+
+    /// Fits a `width × height` frame into a square input without distortion:
+    /// the long side fills the input, the short side is centered and padded.
+    pub fn letterbox(width: u32, height: u32) -> Self {
+        let (w, h) = (width.max(1) as f32, height.max(1) as f32);
+        let side = w.max(h);
+        let (sx, sy) = (w / side, h / side);
+        Self::scale(sx, sy).then(&Self::translate((1.0 - sx) / 2.0, (1.0 - sy) / 2.0))
+    }
+
+    /// Crops a square (in pixels) around frame-normalized `points`, `scale`
+    /// times their extent, so a small face or hand still fills the model
+    /// input. `None` without a usable extent.
+    pub fn around(points: &[(f32, f32)], width: u32, height: u32, scale: f32) -> Option<Self> {
+        let (w, h) = (width.max(1) as f32, height.max(1) as f32);
+        let (mut min, mut max) = ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN));
+        for &(x, y) in points {
+            min = (min.0.min(x * w), min.1.min(y * h));
+            max = (max.0.max(x * w), max.1.max(y * h));
+        }
+        let half = (max.0 - min.0).max(max.1 - min.1) * scale / 2.0;
+        if half.is_nan() || half < 1.0 {
+            return None;
+        }
+        let (cx, cy) = ((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0);
+        Some(Self::crop((cx - half) / w, (cy - half) / h, (cx + half) / w, (cy + half) / h))
+    }
+
+    /// `self` first, then `next`.
+    pub fn then(&self, next: &Affine) -> Affine {
+        let (a, b) = (&next.m, &self.m);
+        let mut m = [[0.0; 3]; 2];
+        for (row, out) in m.iter_mut().enumerate() {
+            out[0] = a[row][0] * b[0][0] + a[row][1] * b[1][0];
+            out[1] = a[row][0] * b[0][1] + a[row][1] * b[1][1];
+            out[2] = a[row][0] * b[0][2] + a[row][1] * b[1][2] + a[row][2];
+        }
+        Affine { m }
+    }
+
+    pub fn inverse(&self) -> Option<Affine> {
+        let [[a, b, tx], [c, d, ty]] = self.m;
+        let det = a * d - b * c;
+        if det.abs() < 1e-9 {
+            return None;
+        }
+        let (ia, ib, ic, id) = (d / det, -b / det, -c / det, a / det);
+        Some(Affine { m: [[ia, ib, -(ia * tx + ib * ty)], [ic, id, -(ic * tx + id * ty)]] })
+    }
+
+    pub fn apply(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let [[a, b, tx], [c, d, ty]] = self.m;
+        (a * x + b * y + tx, c * x + d * y + ty)
+    }
+
+    /// How many frame widths one input width spans, for a map from a square
+    /// input onto a `width × height` frame. Converts the model's depth, which
+    /// is in input widths, into the frame widths the thresholds use.
+    pub fn width_scale(&self, width: u32, height: u32) -> f32 {
+        let aspect = height.max(1) as f32 / width.max(1) as f32;
+        self.m[0][0].hypot(self.m[1][0] * aspect)
+    }
+
+    /// No rotation or shear, so each output axis depends on one input axis.
+    pub fn is_axis_aligned(&self) -> bool {
+        self.m[0][1].abs() < 1e-6 && self.m[1][0].abs() < 1e-6
+    }
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn close(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3
+    }
+
+    #[test]
+    fn test_letterbox_landscape() {
+        // 640x480: full width, 3/4 height centered with 1/8 bars
+        let t = Affine::letterbox(640, 480);
+        assert!(close(t.apply((0.0, 0.0)), (0.0, 0.125)));
+        assert!(close(t.apply((1.0, 1.0)), (1.0, 0.875)));
+        assert!(close(t.apply((0.5, 0.5)), (0.5, 0.5)));
+        assert!(t.is_axis_aligned());
+    }
+
+    #[test]
+    fn test_width_scale() {
+        // Landscape letterbox: the input is exactly as wide as the frame
+        let landscape = Affine::letterbox(1280, 720).inverse().unwrap();
+        assert!((landscape.width_scale(1280, 720) - 1.0).abs() < 1e-5);
+        // Portrait: the input spans the height, 16/9 frame widths
+        let portrait = Affine::letterbox(720, 1280).inverse().unwrap();
+        assert!((portrait.width_scale(720, 1280) - 16.0 / 9.0).abs() < 1e-4);
+        // A crop a quarter of the frame wide, turned a quarter turn
+        let crop = Affine::crop(0.5, 0.0, 0.75, 0.25).then(&Affine::rotate_about((0.5, 0.5), 1.0)).inverse().unwrap();
+        assert!((crop.width_scale(1000, 1000) - 0.25).abs() < 1e-4);
+    }
+
+    fn affine() -> impl Strategy<Value = Affine> {
+        (
+            64u32..4096,
+            64u32..4096,
+            (0.0f32..0.4, 0.0f32..0.4, 0.6f32..1.0, 0.6f32..1.0),
+            -std::f32::consts::PI..std::f32::consts::PI,
+        )
+            .prop_map(|(w, h, (x0, y0, x1, y1), angle)| {
+                Affine::crop(x0, y0, x1, y1)
+                    .then(&Affine::rotate_about((0.5, 0.5), angle))
+                    .then(&Affine::letterbox(w, h))
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_inverse_round_trips(t in affine(), x in -1.0f32..2.0, y in -1.0f32..2.0) {
+            let inv = t.inverse().unwrap();
+            prop_assert!(close(inv.apply(t.apply((x, y))), (x, y)));
+            prop_assert!(close(t.apply(inv.apply((x, y))), (x, y)));
+        }
+
+        #[test]
+        fn prop_then_matches_sequential_apply(a in affine(), b in affine(), x in 0.0f32..1.0, y in 0.0f32..1.0) {
+            prop_assert!(close(a.then(&b).apply((x, y)), b.apply(a.apply((x, y)))));
+        }
+
+        #[test]
+        fn prop_letterbox_keeps_aspect(w in 16u32..4096, h in 16u32..4096) {
+            let t = Affine::letterbox(w, h);
+            let (x0, y0) = t.apply((0.0, 0.0));
+            let (x1, y1) = t.apply((1.0, 1.0));
+            // Same pixels-per-unit on both axes, and the frame stays inside the input
+            prop_assert!(((x1 - x0) / (y1 - y0) - w as f32 / h as f32).abs() < 1e-3 * w.max(h) as f32 / w.min(h) as f32);
+            prop_assert!(x0 >= -1e-6 && y0 >= -1e-6 && x1 <= 1.0 + 1e-6 && y1 <= 1.0 + 1e-6);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::Result;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, UdpSocket};
+
+/// Fire-and-forget datagrams to one target from an ephemeral local port.
+/// Shared by the landmark stream, OSC output and the remote pointer link.
+pub struct UdpSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+}
+
+impl UdpSender {
+    /// Binds a non-blocking socket of the same address family as `target`.
+    pub fn new(target: SocketAddr) -> Result<Self> {
+        let bind: SocketAddr = if target.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+        let socket = UdpSocket::bind(bind)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self { socket, target })
+    }
+
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    /// Sends one datagram. Nobody listening or a full send buffer just loses
+    /// it; any other failure is returned.
+    pub fn send(&self, bytes: &[u8]) -> std::io::Result<()> {
+        match self.socket.send_to(bytes, self.target) {
+            Ok(_) => Ok(()),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::ConnectionRefused) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// A datagram the target sent back, if one is waiting; never blocks.
+    /// Anything from another address is dropped.
+    pub fn try_recv(&self, buf: &mut [u8]) -> Option<usize> {
+        while let Ok((len, from)) = self.socket.recv_from(buf) {
+            if from == self.target {
+                return Some(len);
+            }
+        }
+        None
+    }
+}
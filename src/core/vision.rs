@@ -1,78 +1,223 @@
 use ort::session::Session;
-use ort::value::Tensor;
-use image::{DynamicImage, imageops::FilterType};
+use ort::value::TensorRef;
+use crate::core::frame::Frame;
+use crate::core::gesture;
+use crate::core::model::{Bindings, ModelDescriptor, Port, Signature, METADATA_KEY};
+use crate::core::preprocess::TensorInput;
+use crate::core::tracker::{Tracker, TrackerOutput};
+use crate::core::transform::Affine;
+use crate::core::landmarks::{distance, hand_size, WRIST};
 use crate::Result;
+use tracing::info;
 
 pub struct HandResult {
     pub index_tip: (f32, f32),
     pub thumb_tip: (f32, f32),
+    /// All 21 landmarks: x and y normalized to the frame, z relative to the
+    /// wrist in frame widths (negative toward the camera).
+    pub landmarks: Vec<(f32, f32, f32)>,
+    /// Handedness as reported by the model (output 2), if available.
+    pub handedness: Option<Handedness>,
+}
+
+/// One model run, kept even when no hand passed the presence threshold.
+pub struct Detection {
+    /// Presence probability, when the model has a presence output.
+    pub presence: Option<f32>,
+    pub hand: Option<HandResult>,
+}
+
+/// MediaPipe labels hands assuming a mirrored (selfie) image.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Handedness {
+    Left,
+    Right,
+}
+
+/// Opens an ONNX model and checks it against its descriptor (sidecar TOML,
+/// embedded metadata, or `builtin`). `joints` is how many landmarks the caller reads.
+pub(crate) fn open_model(model_path: &str, builtin: ModelDescriptor, joints: usize) -> Result<(Session, ModelDescriptor, Bindings)> {
+    let session = Session::builder()?
+        .commit_from_file(model_path)
+        .map_err(|e| crate::AirLinkError::CoreError(format!("ORT Init Error ({}): {}", model_path, e)))?;
+
+    let ports = |outlets: &[ort::value::Outlet]| -> Vec<Port> {
+        outlets.iter().map(|o| Port {
+            name: o.name().to_string(),
+            shape: o.dtype().tensor_shape().map(|s| s.to_vec()).unwrap_or_default(),
+        }).collect()
+    };
+    let signature = Signature { inputs: ports(session.inputs()), outputs: ports(session.outputs()) };
+    let metadata = session.metadata().ok().and_then(|m| m.custom(METADATA_KEY));
+
+    let (model, source) = ModelDescriptor::resolve(model_path, metadata, builtin)?;
+    let bindings = model.validate(&signature, joints)
+        .map_err(|e| crate::AirLinkError::CoreError(format!("{} ({}): {}", model_path, source, e)))?;
+    info!("Loaded {} with {} descriptor: {}x{} {:?} input", model_path, source, model.size, model.size, model.layout);
+    Ok((session, model, bindings))
 }
 
 pub struct HandDetector {
     session: Session,
+    model: ModelDescriptor,
+    bindings: Bindings,
+    input: TensorInput,
+    /// One slot per hand followed by [`HandDetector::detect_hands`]: the crop
+    /// around that hand's last landmarks, `None` while it searches.
+    slots: Vec<Option<Affine>>,
 }
 
 impl HandDetector {
+    const JOINTS: usize = 21;
+    const INDEX_TIP: usize = 8;
+    const THUMB_TIP: usize = 4;
+    /// A followed hand's next crop spans its last landmarks' larger side times this.
+    const ROI_SCALE: f32 = 2.0;
+
     pub fn new(model_path: &str) -> Result<Self> {
-        let session = Session::builder()?
-            .commit_from_file(model_path)
-            .map_err(|e| crate::AirLinkError::CoreError(format!("ORT Init Error: {}", e)))?;
-        Ok(Self { session })
+        let (session, model, bindings) = open_model(model_path, ModelDescriptor::hand(), Self::JOINTS)?;
+        let input = TensorInput::new(model.size).with_format(model.layout, model.normalization);
+        Ok(Self { session, model, bindings, input, slots: Vec::new() })
     }
 
-    pub fn detect(&mut self, img: &DynamicImage) -> Result<Option<HandResult>> {
-        let resized = img.resize_exact(224, 224, FilterType::Triangle);
-        let rgb_img = resized.to_rgb8();
-
-        let mut pixels = Vec::with_capacity(3 * 224 * 224);
-        for c in 0..3 {
-            for y in 0..224 {
-                for x in 0..224 {
-                    let pix = rgb_img.get_pixel(x, y);
-                    pixels.push(pix[c] as f32 / 255.0);
-                }
+    /// Follows up to two hands, for two-hand touch. Costs one model run per
+    /// hand and frame: each slot searches its half of the frame for a hand,
+    /// then follows it with a crop around its last landmarks.
+    pub fn with_two_hands(mut self) -> Self {
+        self.slots = vec![None; 2];
+        self
+    }
+
+    pub fn detect(&mut self, frame: &Frame) -> Result<Option<HandResult>> {
+        Ok(self.infer(frame)?.hand)
+    }
+
+    /// Like [`HandDetector::detect`], also reporting the presence score.
+    pub fn infer(&mut self, frame: &Frame) -> Result<Detection> {
+        self.run(frame, Affine::letterbox(frame.width, frame.height))
+    }
+
+    /// Every hand found, by slot; just [`HandDetector::detect`] without
+    /// [`HandDetector::with_two_hands`].
+    pub fn detect_hands(&mut self, frame: &Frame) -> Result<Vec<HandResult>> {
+        if self.slots.is_empty() {
+            return Ok(self.detect(frame)?.into_iter().collect());
+        }
+        let mut hands: Vec<HandResult> = Vec::new();
+        for slot in 0..self.slots.len() {
+            let transform = self.slots[slot].take()
+                .unwrap_or_else(|| search_region(slot, self.slots.len(), frame.width, frame.height));
+            let Some(hand) = self.run(frame, transform)?.hand else { continue };
+            // A searching slot can land on a hand another slot already follows
+            if hands.iter().any(|other| same_hand(other, &hand)) {
+                continue;
             }
+            let points: Vec<_> = hand.landmarks.iter().map(|&(x, y, _)| (x, y)).collect();
+            self.slots[slot] = Affine::around(&points, frame.width, frame.height, Self::ROI_SCALE);
+            hands.push(hand);
         }
-        let shape = vec![1, 3, 224, 224];
+        Ok(hands)
+    }
 
-        let outputs = self.session.run(ort::inputs![Tensor::from_array((shape, pixels))?])?;
+    /// One model run on the part of `frame` that `transform` maps onto the input.
+    fn run(&mut self, frame: &Frame, transform: Affine) -> Result<Detection> {
+        // Native buffer -> model input in one pass, no per-frame allocation
+        self.input.fill_with(frame, transform)?;
+        let tensor = TensorRef::from_array_view((self.input.shape(), self.input.data()))?;
+
+        let outputs = self.session.run(ort::inputs![self.bindings.input.as_str() => tensor])?;
         
-        // 1. Check Confidence Score (Output 1)
-        // If the model is from MediaPipe, the second output is usually the hand presence score.
-        if outputs.len() > 1 {
-            let (_, score_data) = outputs[1].try_extract_tensor::<f32>()?;
-            let score = score_data[0];
-            
-            // If score is too low, it's just noise (False Positive)
-            if score < 0.7 {
-                return Ok(None);
+        // 1. Check Confidence Score
+        let presence = match self.bindings.presence {
+            Some(output) => {
+                let (_, score_data) = outputs[output].try_extract_tensor::<f32>()?;
+                Some(self.model.probability(score_data[0]))
             }
+            None => None,
+        };
+        let none = Detection { presence, hand: None };
+        // If score is too low, it's just noise (False Positive)
+        if presence.is_some_and(|p| p < self.model.threshold) {
+            return Ok(none);
         }
 
-        // 2. Extract Landmarks (Output 0)
-        let (_shape, data) = outputs[0].try_extract_tensor::<f32>()
+        // 2. Extract Landmarks
+        let (_shape, data) = outputs[self.bindings.landmarks].try_extract_tensor::<f32>()
             .map_err(|e| crate::AirLinkError::CoreError(format!("Output extraction failed: {}", e)))?;
 
-        if data.len() < 63 { return Ok(None); }
+        if data.len() < self.model.count * self.model.stride { return Ok(none); }
 
-        let idx_8 = 8 * 3;
-        let x8 = data[idx_8];
-        let y8 = data[idx_8 + 1];
-        
-        let (nx, ny) = if x8 > 1.1 || y8 > 1.1 { (x8 / 224.0, y8 / 224.0) } else { (x8, y8) };
+        // Model input -> frame: undo the letterbox so points line up with the camera image.
+        // Depth comes in input widths; an ROI crop would make it depend on the hand's size
+        let to_frame = self.input.transform().inverse().unwrap_or_default();
+        let depth = to_frame.width_scale(frame.width, frame.height);
+        let landmarks: Vec<(f32, f32, f32)> = (0..Self::JOINTS)
+            .map(|joint| {
+                let (x, y, z) = self.model.landmark(data, joint);
+                let (x, y) = to_frame.apply((x, y));
+                (x, y, z * depth)
+            })
+            .collect();
 
+        // Tips in the padding bars are outside the camera's view
+        let (nx, ny, _) = landmarks[Self::INDEX_TIP];
         if nx < 0.0 || nx > 1.0 || ny < 0.0 || ny > 1.0 { 
-            return Ok(None); 
+            return Ok(none); 
         }
-        
-        let idx_4 = 4 * 3;
-        let tx = data[idx_4];
-        let ty = data[idx_4 + 1];
-        let (ntx, nty) = if tx > 1.1 { (tx / 224.0, ty / 224.0) } else { (tx, ty) };
-
-        Ok(Some(HandResult {
-            index_tip: (nx, ny),
-            thumb_tip: (ntx, nty),
+        let (ntx, nty, _) = landmarks[Self::THUMB_TIP];
+
+        // 3. Handedness, probability of a right hand
+        let handedness = match self.bindings.handedness {
+            Some(output) => {
+                let (_, hand_data) = outputs[output].try_extract_tensor::<f32>()?;
+                hand_data.first().map(|&p| if p > 0.5 { Handedness::Right } else { Handedness::Left })
+            }
+            None => None,
+        };
+
+        Ok(Detection {
+            presence,
+            hand: Some(HandResult {
+                index_tip: (nx, ny),
+                thumb_tip: (ntx, nty),
+                landmarks,
+                handedness,
+            }),
+        })
+    }
+}
+
+/// Where slot `slot` of `slots` looks for a new hand: its vertical strip of
+/// the frame, letterboxed.
+fn search_region(slot: usize, slots: usize, width: u32, height: u32) -> Affine {
+    let (x0, x1) = (slot as f32 / slots as f32, (slot + 1) as f32 / slots as f32);
+    Affine::crop(x0, 0.0, x1, 1.0).then(&Affine::letterbox(width / slots as u32, height))
+}
+
+/// Wrists closer than half a hand apart.
+fn same_hand(a: &HandResult, b: &HandResult) -> bool {
+    hand_size(&a.landmarks).is_some_and(|size| distance(a.landmarks[WRIST], b.landmarks[WRIST]) < size / 2.0)
+}
+
+impl Tracker for HandDetector {
+    fn track(&mut self, frame: &Frame) -> Result<Option<TrackerOutput>> {
+        let mut hands = self.detect_hands(frame)?.into_iter();
+        let Some(hand) = hands.next() else { return Ok(None) };
+
+        // Pinch: thumb tip touching the index tip
+        let (x, y) = hand.index_tip;
+        let (tx, ty) = hand.thumb_tip;
+        let dist_sq = (x - tx).powi(2) + (y - ty).powi(2);
+        let threshold_sq = 0.003;
+
+        Ok(Some(TrackerOutput {
+            pointer: hand.index_tip,
+            click: dist_sq < threshold_sq,
+            pose: gesture::classify_pose(&hand),
+            hand: Some(hand),
+            other_hands: hands.collect(),
+            timestamp: frame.timestamp,
+            sequence: frame.sequence,
         }))
     }
-}
\ No newline at end of file
+}
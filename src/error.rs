@@ -14,6 +14,9 @@ pub enum AirLinkError {
     #[error("AI error: {0}")]
     Ort(#[from] ort::Error),
 
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
     #[error("Unknown error occurred")]
     Unknown,
 }
\ No newline at end of file
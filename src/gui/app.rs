@@ -1,9 +1,13 @@
 use eframe::egui;
-use crate::core::app::AppContext;
+use crate::core::app::{AppContext, TrackingStatus};
+use crate::core::camera::CameraStatus;
+use crate::core::clutch::ClutchState;
+use crate::gui::view;
 
 pub struct AirLinkApp {
     context: Option<AppContext>,
     camera_texture: Option<egui::TextureHandle>,
+    status: Option<TrackingStatus>,
 }
 
 impl AirLinkApp {
@@ -11,6 +15,7 @@ impl AirLinkApp {
         Self {
             context: None,
             camera_texture: None,
+            status: None,
         }
     }
 
@@ -24,22 +29,56 @@ impl eframe::App for AirLinkApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // 1. Process Frame & AI
         if let Some(app_ctx) = &mut self.context {
-            if let Ok(frame) = app_ctx.camera.capture_frame() {
-                // Run AI Detection (Pinch logic is inside detector/app)
-                let _ = app_ctx.detector.detect(&frame);
+            // Space works like lifting the mouse off the desk
+            if ctx.input(|i| i.key_pressed(egui::Key::Space)) {
+                let clutch = app_ctx.toggle_clutch();
+                if let Some(status) = &mut self.status {
+                    status.clutch = clutch;
+                }
+            }
 
-                // Convert image::DynamicImage to egui::ColorImage
-                let rgb_img = frame.to_rgb8();
-                let size = [rgb_img.width() as usize, rgb_img.height() as usize];
-                let pixels = rgb_img.as_flat_samples();
-                let color_image = egui::ColorImage::from_rgb(size, pixels.as_slice());
+            // Tab cycles the dwell action (left → right → double → drag)
+            if ctx.input(|i| i.key_pressed(egui::Key::Tab)) {
+                app_ctx.cycle_dwell_action();
+            }
 
-                // Load/Update texture
-                self.camera_texture = Some(ctx.load_texture(
-                    "camera_stream",
-                    color_image,
-                    egui::TextureOptions::LINEAR,
-                ));
+            // Page keys move the mapped region between monitors
+            if ctx.input(|i| i.key_pressed(egui::Key::PageDown)) {
+                app_ctx.switch_screen(true);
+            }
+            if ctx.input(|i| i.key_pressed(egui::Key::PageUp)) {
+                app_ctx.switch_screen(false);
+            }
+
+            // R retakes the gamepad's neutral point
+            if ctx.input(|i| i.key_pressed(egui::Key::R)) {
+                app_ctx.recenter_gamepad();
+            }
+
+            // Commands from `air-link ctl`
+            app_ctx.poll_control();
+
+            if let Ok(frame) = app_ctx.capture_frame() {
+                // Run the full pipeline (detection, clutch, pointer & pinch)
+                if let Ok(status) = app_ctx.process_frame(&frame) {
+                    self.status = Some(status);
+                }
+
+                // Only the preview needs RGB; tracking used the native buffer
+                if let Ok(image) = frame.to_image() {
+                    let rgb_img = image.to_rgb8();
+                    let size = [rgb_img.width() as usize, rgb_img.height() as usize];
+                    let pixels = rgb_img.as_flat_samples();
+                    let color_image = egui::ColorImage::from_rgb(size, pixels.as_slice());
+
+                    // Load/Update texture
+                    self.camera_texture = Some(ctx.load_texture(
+                        "camera_stream",
+                        color_image,
+                        egui::TextureOptions::LINEAR,
+                    ));
+                }
+                app_ctx.camera.recycle(frame);
             }
         }
 
@@ -51,7 +90,10 @@ impl eframe::App for AirLinkApp {
 
                 if let Some(texture) = &self.camera_texture {
                     // Display the video feed
-                    ui.image((texture.id(), ui.available_size()));
+                    let response = ui.image((texture.id(), ui.available_size()));
+                    if let Some(status) = &self.status {
+                        view::draw_overlay(ui.painter(), response.rect, status);
+                    }
                 } else {
                     ui.add_space(100.0);
                     ui.label("Searching for camera stream...");
@@ -61,12 +103,53 @@ impl eframe::App for AirLinkApp {
                 ui.add_space(8.0);
                 ui.separator();
                 ui.label("Status: Running on Wayland/Hyprland");
+                if let Some(app_ctx) = &self.context {
+                    if let CameraStatus::Reconnecting { attempt } = app_ctx.camera.status() {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            format!("Camera lost, reconnecting (attempt {})...", attempt + 1),
+                        );
+                    }
+                    ui.weak(app_ctx.metrics.summary());
+                    if let Some(event) = &app_ctx.metrics.last_camera_event {
+                        ui.weak(format!("Last camera event: {}", event));
+                    }
+                }
+                if let Some(status) = &self.status {
+                    let (text, color) = match status.clutch {
+                        ClutchState::Engaged => ("Pointer: ENGAGED", egui::Color32::LIGHT_GREEN),
+                        ClutchState::Disengaged => ("Pointer: DISENGAGED", egui::Color32::LIGHT_RED),
+                    };
+                    ui.colored_label(color, text);
+                    if status.paused {
+                        ui.weak("⏸️ Paused (air-link ctl resume)");
+                    } else if status.idle {
+                        ui.weak("💤 Idle: waiting for motion");
+                    } else {
+                        ui.label(format!("Hand: {} | Pose: {:?}", if status.hand_present { "yes" } else { "no" }, status.pose));
+                    }
+                }
                 ui.add_space(4.0);
                 ui.weak("Gesture: Pinch thumb + index to Left Click");
+                ui.weak("Clutch: Hold a fist or press Space to engage/disengage");
+                if let Some(app_ctx) = self.context.as_mut() {
+                    if app_ctx.layout.monitors().len() > 1 {
+                        ui.label(format!("Screen: {} (PageUp/PageDown to switch)", app_ctx.layout.current_name()));
+                    }
+                    if let Some(action) = app_ctx.dwell.as_ref().map(|d| d.action()) {
+                        // A real button, so it can be hit with a dwell click too
+                        if ui.button(format!("Dwell Click: {:?} (Tab to cycle)", action)).clicked() {
+                            app_ctx.cycle_dwell_action();
+                        }
+                    }
+                }
             });
         });
 
-        // Request continuous repaint for video stream
-        ctx.request_repaint();
+        // Request continuous repaint for video stream, or just the next motion check when idle
+        match self.context.as_ref().and_then(|c| c.idle.as_ref()).and_then(|i| i.next_check(std::time::Instant::now())) {
+            Some(wait) => ctx.request_repaint_after(wait),
+            None => ctx.request_repaint(),
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,118 @@
+use eframe::egui;
+use crate::core::dataset::DatasetSession;
+use crate::gui::view;
+use tracing::warn;
+
+/// The `capture-dataset` window: live preview with the detector's landmarks,
+/// capture on demand and bad-detection flagging.
+pub struct DatasetApp {
+    session: DatasetSession,
+    camera_texture: Option<egui::TextureHandle>,
+    landmarks: Option<Vec<(f32, f32, f32)>>,
+    presence: Option<f32>,
+    /// Id and flag of the latest saved frame.
+    last: Option<(u64, bool)>,
+    error: Option<String>,
+}
+
+impl DatasetApp {
+    pub fn new(_cc: &eframe::CreationContext, session: DatasetSession) -> Self {
+        Self { session, camera_texture: None, landmarks: None, presence: None, last: None, error: None }
+    }
+
+    fn toggle_last(&mut self) {
+        match self.session.writer.toggle_last() {
+            Ok(last) => self.last = last.or(self.last),
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+}
+
+impl eframe::App for DatasetApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Space or Enter saves the current frame, B flags the last saved one
+        let capture = ctx.input(|i| i.key_pressed(egui::Key::Space) || i.key_pressed(egui::Key::Enter));
+        if ctx.input(|i| i.key_pressed(egui::Key::B)) {
+            self.toggle_last();
+        }
+        // A pauses and resumes interval capture
+        if self.session.interval().is_some() && ctx.input(|i| i.key_pressed(egui::Key::A)) {
+            self.session.auto = !self.session.auto;
+        }
+
+        match self.session.step(capture) {
+            Ok(Some(preview)) => {
+                if let Some(id) = preview.saved {
+                    self.last = Some((id, false));
+                }
+                self.presence = preview.detection.presence;
+                self.landmarks = preview.detection.hand.map(|h| h.landmarks);
+                if let Ok(image) = preview.frame.to_image() {
+                    let rgb_img = image.to_rgb8();
+                    let size = [rgb_img.width() as usize, rgb_img.height() as usize];
+                    let color_image = egui::ColorImage::from_rgb(size, rgb_img.as_flat_samples().as_slice());
+                    self.camera_texture = Some(ctx.load_texture("dataset_stream", color_image, egui::TextureOptions::LINEAR));
+                }
+                self.session.recycle(preview.frame);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!("Dataset capture failed: {}", e);
+                self.error = Some(e.to_string());
+            }
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.heading("🗂️ Air-Link Dataset Capture");
+                ui.add_space(8.0);
+
+                if let Some(texture) = &self.camera_texture {
+                    let response = ui.image((texture.id(), ui.available_size() - egui::vec2(0.0, 120.0)));
+                    if let Some(landmarks) = &self.landmarks {
+                        view::draw_landmarks(ui.painter(), response.rect, landmarks, egui::Color32::LIGHT_GREEN);
+                    }
+                } else {
+                    ui.add_space(100.0);
+                    ui.label("Searching for camera stream...");
+                    ui.spinner();
+                }
+
+                ui.add_space(8.0);
+                ui.separator();
+                let writer = &self.session.writer;
+                ui.label(format!(
+                    "Saved {} frames ({} flagged) to {}",
+                    writer.len(), writer.flagged(), writer.dir().display()
+                ));
+                ui.label(format!(
+                    "Hand: {} | Presence: {}",
+                    if self.landmarks.is_some() { "yes" } else { "no" },
+                    self.presence.map_or("n/a".to_string(), |p| format!("{:.2}", p)),
+                ));
+                if let Some((id, bad)) = self.last {
+                    let (text, color) = if bad {
+                        (format!("Frame {}: flagged as a bad detection", id), egui::Color32::LIGHT_RED)
+                    } else {
+                        (format!("Frame {}: ok", id), egui::Color32::LIGHT_GREEN)
+                    };
+                    ui.colored_label(color, text);
+                }
+                if let Some(interval) = self.session.interval() {
+                    let state = if self.session.auto { "on" } else { "paused" };
+                    ui.weak(format!("Auto capture every {} ms: {} (A to toggle)", interval.as_millis(), state));
+                }
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::YELLOW, error);
+                }
+
+                if ui.button("Flag last as bad (B)").clicked() {
+                    self.toggle_last();
+                }
+                ui.weak("Space or Enter saves the current frame");
+            });
+        });
+
+        ctx.request_repaint();
+    }
+}
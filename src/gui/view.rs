@@ -1,2 +1,46 @@
-// Placeholder for view components
-pub fn draw_overlay() {}
+use eframe::egui;
+use crate::core::app::TrackingStatus;
+use crate::core::dataset::SKELETON;
+use std::f32::consts::TAU;
+
+/// Draws the fingertip marker and dwell-click progress ring over the camera image.
+pub fn draw_overlay(painter: &egui::Painter, image_rect: egui::Rect, status: &TrackingStatus) {
+    let Some((x, y)) = status.pointer else { return };
+    let center = egui::pos2(
+        image_rect.left() + x * image_rect.width(),
+        image_rect.top() + y * image_rect.height(),
+    );
+
+    painter.circle_filled(center, 4.0, egui::Color32::YELLOW);
+
+    if let Some(progress) = status.dwell_progress.filter(|p| *p > 0.0) {
+        let radius = 18.0;
+        let steps = 48;
+        let end = (steps as f32 * progress).ceil() as usize;
+        let points: Vec<egui::Pos2> = (0..=end)
+            .map(|i| {
+                let angle = -TAU / 4.0 + TAU * (i as f32 / steps as f32).min(progress);
+                center + radius * egui::vec2(angle.cos(), angle.sin())
+            })
+            .collect();
+
+        painter.circle_stroke(center, radius, egui::Stroke::new(2.0, egui::Color32::from_gray(90)));
+        painter.add(egui::Shape::line(points, egui::Stroke::new(4.0, egui::Color32::LIGHT_BLUE)));
+    }
+}
+
+/// Draws the hand skeleton over the camera image.
+pub fn draw_landmarks(painter: &egui::Painter, image_rect: egui::Rect, landmarks: &[(f32, f32, f32)], color: egui::Color32) {
+    let to_screen = |&(x, y, _): &(f32, f32, f32)| egui::pos2(
+        image_rect.left() + x * image_rect.width(),
+        image_rect.top() + y * image_rect.height(),
+    );
+    for [a, b] in SKELETON {
+        if let (Some(a), Some(b)) = (landmarks.get(a - 1), landmarks.get(b - 1)) {
+            painter.line_segment([to_screen(a), to_screen(b)], egui::Stroke::new(2.0, color));
+        }
+    }
+    for point in landmarks {
+        painter.circle_filled(to_screen(point), 3.0, color);
+    }
+}
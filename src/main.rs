@@ -16,8 +16,11 @@ async fn main() -> Result<()> {
         }
     }
 
-    // Initialize logging
-    tracing_subscriber::fmt::init();
+    // Initialize logging; to stderr, so `dump` can write records to stdout
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
 
     info!("Air-Link is starting...");
 